@@ -1,51 +1,250 @@
 use std::env;
 use std::fs::File;
 use std::io::Read;
+use std::process;
 
-use lexer::errors::LexerError;
+use lexer::messages::{self, Lang, Message, tr};
 use lexer::token::Token;
 
+use parser::dump::{self, Format};
+use parser::lints::{self, Level, LintConfig, Linter};
+use parser::modules::Loader;
 use parser::parser::engine::Parser;
-use parser::parser::lookups::BindingPower;
+use parser::typeck;
+
+/// What to do with the file.
+enum Command {
+    // `language <file>`, report errors and lints and print the program
+    Check,
+    // `language ast <file>`, dump the syntax tree
+    Ast(Format),
+    // `language parse <file>`, print the program, with `trace` also log
+    // every decision of the expression parser
+    Parse { trace: bool },
+}
+
+/// Command line options, everything but the path is optional. Parsing them
+/// also sets the diagnostics language.
+///
+/// `--lang <code>` picks the diagnostics language (default: `LANG`),
+/// `-W`/`-D`/`-A <lint>` set a lint to warn, deny or allow and
+/// `--deny-warnings` turns every warning into an error. With the `ast`
+/// command, `--format tree|json|sexpr|dot` picks the dump format (default:
+/// `tree`), and with the `parse` command `--trace` logs the parse to stderr.
+/// `-I <dir>` adds a directory imports are looked up in when checking.
+struct Options {
+    command: Command,
+    lints: LintConfig,
+    search_paths: Vec<String>,
+    path: Option<String>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Options {
+        let mut lang = None;
+        let mut lint_flags: Vec<(Level, String)> = Vec::new();
+        let mut lints = LintConfig::new();
+        let mut command = None;
+        let mut format = None;
+        let mut trace = false;
+        let mut search_paths = Vec::new();
+        let mut path = None;
+
+        while let Some(arg) = args.next() {
+            let level = match arg.get(..2) {
+                Some("-W") => Some(Level::Warn),
+                Some("-D") => Some(Level::Deny),
+                Some("-A") => Some(Level::Allow),
+                _ => None,
+            };
+            if let Some(code) = arg.strip_prefix("--lang=") {
+                lang = Lang::from_code(code);
+            } else if arg == "--lang" {
+                lang = args.next().as_deref().and_then(Lang::from_code);
+            } else if let Some(name) = arg.strip_prefix("--format=") {
+                format = Some(name.to_string());
+            } else if arg == "--format" {
+                format = args.next();
+            } else if arg == "--trace" {
+                trace = true;
+            } else if let Some(dir) = arg.strip_prefix("-I") {
+                // both `-I dir` and `-Idir` are accepted
+                match dir {
+                    "" => search_paths.extend(args.next()),
+                    dir => search_paths.push(dir.to_string()),
+                }
+            } else if arg == "--deny-warnings" {
+                lints.deny_warnings();
+            } else if let Some(level) = level {
+                // both `-D name` and `-Dname` are accepted
+                let name = match &arg[2..] {
+                    "" => args.next().unwrap_or_default(),
+                    name => name.to_string(),
+                };
+                lint_flags.push((level, name));
+            } else if (arg == "ast" || arg == "parse") && command.is_none() && path.is_none() {
+                command = Some(arg);
+            } else if path.is_none() {
+                path = Some(arg);
+            }
+        }
+
+        // the language must be known before reporting unknown lints
+        messages::set_lang(lang.unwrap_or_else(Lang::from_env));
+        for (level, name) in lint_flags {
+            if !lints.set(&name, level) {
+                eprintln!(
+                    "{}: {}",
+                    tr(Message::WarningPrefix),
+                    messages::format(Message::UnknownLint, &[&name])
+                );
+            }
+        }
+
+        let command = match command.as_deref() {
+            Some("ast") => {
+                let name = format.unwrap_or_default();
+                match Format::from_name(&name) {
+                    Some(format) => Command::Ast(format),
+                    None if name.is_empty() => Command::Ast(Format::Tree),
+                    None => {
+                        let names: Vec<_> = Format::ALL.iter().map(|f| f.name()).collect();
+                        eprintln!(
+                            "{}: {}",
+                            tr(Message::ErrorPrefix),
+                            messages::format(Message::UnknownFormat, &[&name, &names.join(", ")])
+                        );
+                        process::exit(1);
+                    }
+                }
+            }
+            Some(_) => Command::Parse { trace },
+            None => Command::Check,
+        };
+
+        Options {
+            command,
+            lints,
+            search_paths,
+            path,
+        }
+    }
+}
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let options = Options::parse(env::args().skip(1));
 
-    if args.len() < 2 {
+    let Some(path) = options.path else {
         return;
-    }
+    };
 
     // try to open a File in that path
-    let mut file: File = match File::open(&args[1]) {
+    let mut file: File = match File::open(&path) {
         Ok(f) => f,
         Err(e) => {
-            eprintln!("the path doesnt exists: {e}");
+            eprintln!("{}", messages::format(Message::PathNotFound, &[&e]));
             return;
         }
     };
     // buffer to read the file
     let mut buffer: String = String::new();
     if let Err(e) = file.read_to_string(&mut buffer) {
-        eprintln!("Error al leer el archivo: {e}");
+        eprintln!("{}", messages::format(Message::ReadFailed, &[&e]));
+        return;
     }
 
     // buffer ready to use
 
-    let tokens: Vec<Token> = match lexer::tokenize(&buffer, &args[1]) {
-        Ok(v) => v,
-        Err(e) => {
-            eprintln!("Lexer error: {}", e);
-            return;
+    let (tokens, comments): (Vec<Token>, Vec<Token>) =
+        match lexer::tokenize_with_comments(&buffer, &path) {
+            Ok(v) => v,
+            Err(e) => {
+                eprintln!("{}: {}", tr(Message::LexerErrorPrefix), e);
+                return;
+            }
+        };
+
+    if let Command::Ast(format) = options.command {
+        let mut parser: Parser = Parser::new(&tokens, 0);
+        let program = Parser::parse_program(&mut parser);
+        for e in &parser.errors {
+            eprint!("{}: {}", tr(Message::ParserErrorPrefix), e.report(&buffer, &path));
         }
-    };
+        // the tree is dumped even with errors, they show up as `Error` nodes
+        print!("{}", dump::dump(&program, format));
+        if !parser.errors.is_empty() {
+            process::exit(1);
+        }
+        return;
+    }
+
+    if let Command::Parse { trace } = options.command {
+        let mut parser: Parser = Parser::new(&tokens, 0);
+        if trace {
+            parser.trace = Some(Vec::new());
+        }
+        let program = Parser::parse_program(&mut parser);
+        for event in parser.trace.iter().flatten() {
+            eprintln!("{}", event);
+        }
+        for e in &parser.errors {
+            eprint!("{}: {}", tr(Message::ParserErrorPrefix), e.report(&buffer, &path));
+        }
+        println!("{}", program);
+        if !parser.errors.is_empty() {
+            process::exit(1);
+        }
+        return;
+    }
+
+    let mut linter = Linter::new(&options.lints, &tokens, &comments);
+    for unknown in linter.unknown_lints() {
+        eprint!("{}", unknown.report(&buffer, &path));
+    }
+    lints::check_tokens(&mut linter, &tokens);
 
     let mut parser: Parser = Parser::new(&tokens, 0);
-    let v = Parser::parse_expr(&mut parser, BindingPower::Primary)
-        .map_err(|e| {
-            eprintln!("Parser error: {}", e);
-        })
-        .ok();
-    for expr in v {
-        println!("{:#?}", expr);
+    let mut program = Parser::parse_program(&mut parser);
+    for e in &parser.errors {
+        eprint!("{}: {}", tr(Message::ParserErrorPrefix), e.report(&buffer, &path));
+    }
+    for (lint, span) in &parser.lints {
+        linter.emit(*lint, *span);
+    }
+
+    let warnings = linter.finish();
+    for warning in &warnings {
+        eprint!("{}", warning.report(&buffer, &path));
+    }
+
+    // the file is only type checked once it parses, and the modules it
+    // imports are only loaded once it checks
+    let mut failed = !parser.errors.is_empty();
+    if !failed {
+        let errors = typeck::check(&mut program);
+        for e in &errors {
+            eprint!("{}", e.report(&buffer, &path));
+        }
+        failed = !errors.is_empty();
+    }
+    if !failed {
+        let mut loader = Loader::new();
+        loader.search_paths = options.search_paths.iter().map(Into::into).collect();
+        let root = loader.load_program(&path, buffer.clone(), program);
+        for error in &loader.errors {
+            eprint!("{}", error.report(&loader));
+        }
+        failed = !loader.errors.is_empty();
+        if !failed {
+            println!("{}", loader.module(root).program);
+        }
+    }
+
+    let denied = warnings.iter().filter(|w| w.is_denied()).count();
+    if denied > 0 {
+        eprintln!("{}", messages::format(Message::DeniedWarnings, &[&denied]));
+    }
+    if denied > 0 || failed {
+        process::exit(1);
     }
 }
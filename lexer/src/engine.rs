@@ -27,9 +27,33 @@ fn get_symbols() -> &'static HashMap<char, TokenKind> {
             (',', TokenKind::COMMA),
             (';', TokenKind::SEMICOLON),
             (':', TokenKind::COLON),
+            ('.', TokenKind::DOT),
             ('!', TokenKind::NEGATION),
             ('<', TokenKind::LESS),
             ('>', TokenKind::GREATER),
+            ('^', TokenKind::CARET),
+            ('?', TokenKind::QUESTION),
+        ])
+    })
+}
+
+static DOUBLE_SYMBOLS: OnceLock<HashMap<&'static str, TokenKind>> = OnceLock::new();
+
+fn get_double_symbols() -> &'static HashMap<&'static str, TokenKind> {
+    DOUBLE_SYMBOLS.get_or_init(|| {
+        HashMap::from([
+            ("==", TokenKind::EQUAL_EQUAL),
+            ("!=", TokenKind::NOT_EQUAL),
+            ("<=", TokenKind::LESS_EQUAL),
+            (">=", TokenKind::GREATER_EQUAL),
+            ("&&", TokenKind::DOUBLE_AMPER),
+            ("||", TokenKind::DOUBLE_VERTICAL_BAR),
+            ("<<", TokenKind::DOUBLE_LESS),
+            (">>", TokenKind::DOUBLE_GREATER),
+            ("**", TokenKind::DOUBLE_STAR),
+            ("..", TokenKind::DOUBLE_DOT),
+            ("->", TokenKind::ARROW),
+            ("=>", TokenKind::FAT_ARROW),
         ])
     })
 }
@@ -41,6 +65,9 @@ pub struct Lexer<'a> {
     pos: usize,
     line: usize,
     col: usize,
+    comments: Vec<Token<'a>>,
+    // whether whitespace and comments are returned as tokens
+    lossless: bool,
 }
 
 impl<'a> Lexer<'a> {
@@ -52,9 +79,17 @@ impl<'a> Lexer<'a> {
             pos: 0,
             line: 1,
             col: 1,
+            comments: Vec::new(),
+            lossless: false,
         }
     }
 
+    /// Line comments seen so far, in source order. They are kept out of the
+    /// token stream so the parser never has to skip them.
+    pub fn comments(&self) -> &[Token<'a>] {
+        &self.comments
+    }
+
     fn peek(&mut self) -> Option<char> {
         self.text[self.pos..].chars().next()
     }
@@ -88,7 +123,12 @@ impl<'a> Lexer<'a> {
                 current_pos += c.len_utf8();
                 current_col += 1;
             } else if has_dot && c == '.' {
-                return None;
+                // `1..2` is a range from the integer `1`, while `1.5..2`
+                // starts with the float `1.5`
+                if self.text[..current_pos].ends_with('.') {
+                    return None;
+                }
+                break;
             } else {
                 break;
             }
@@ -153,6 +193,19 @@ impl<'a> Lexer<'a> {
             "for" => TokenKind::FOR,
             "func" => TokenKind::FUNCTION,
             "return" => TokenKind::RETURN,
+            "let" => TokenKind::LET,
+            "var" => TokenKind::VAR,
+            "in" => TokenKind::IN,
+            "break" => TokenKind::BREAK,
+            "continue" => TokenKind::CONTINUE,
+            "as" => TokenKind::AS,
+            "struct" => TokenKind::STRUCT,
+            "enum" => TokenKind::ENUM,
+            "match" => TokenKind::MATCH,
+            "import" => TokenKind::IMPORT,
+            "pub" => TokenKind::PUB,
+            "true" => TokenKind::TRUE,
+            "false" => TokenKind::FALSE,
             _ => TokenKind::IDENTIFIER,
         }
     }
@@ -230,8 +283,27 @@ impl<'a> Lexer<'a> {
         let start = self.pos;
         let start_col = self.col;
 
+        // longer operators take precedence over their first characters, and
+        // `..=` is the only one with three
+        let (len, kind) = if self.text[self.pos..].starts_with("..=") {
+            (3, Some(&TokenKind::DOUBLE_DOT_EQUAL))
+        } else {
+            let double = self.text[self.pos..].get(..2);
+            (2, double.and_then(|s| get_double_symbols().get(s)))
+        };
+        if let Some(kind) = kind {
+            for _ in 0..len {
+                self.advance();
+            }
+            let span = SourceSpan::new(
+                Position::new(self.line, start_col),
+                Position::new(self.line, self.col),
+            );
+            return Some(Token::new(*kind, &self.text[start..self.pos], span));
+        }
+
         let c = self.peek()?;
-        let kind = get_symbols().get(&c)?.clone();
+        let kind = *get_symbols().get(&c)?;
         self.advance();
 
         let span = SourceSpan::new(
@@ -242,7 +314,32 @@ impl<'a> Lexer<'a> {
         Some(Token::new(kind, &self.text[start..self.pos], span))
     }
 
+    fn token_comment(&mut self) -> Option<Token<'a>> {
+        let start = self.pos;
+        let start_col = self.col;
+        for c in self.text[start..].chars() {
+            if c == '\n' {
+                break;
+            }
+            self.advance();
+        }
+
+        let span = SourceSpan::new(
+            Position::new(self.line, start_col),
+            Position::new(self.line, self.col),
+        );
+
+        let comment = Token::new(TokenKind::COMMENT, &self.text[start..self.pos], span);
+        if self.lossless {
+            return Some(comment);
+        }
+        self.comments.push(comment);
+        None
+    }
+
     fn trash(&mut self) -> Option<Token<'a>> {
+        let start = self.pos;
+        let start_pos = Position::new(self.line, self.col);
         for c in self.text[self.pos..].chars() {
             if c == ' ' {
                 self.advance();
@@ -254,40 +351,100 @@ impl<'a> Lexer<'a> {
                 break;
             }
         }
+        if self.lossless && self.pos > start {
+            let span = SourceSpan::new(start_pos, Position::new(self.line, self.col));
+            return Some(Token::new(TokenKind::WHITESPACE, &self.text[start..self.pos], span));
+        }
         None
     }
 
+    /// Scans the token at the current position. Whitespace and, unless
+    /// lossless, comments give `None`.
+    fn next_token(&mut self) -> Result<Option<Token<'a>>, LexerError<'a>> {
+        let token = match self.peek() {
+            // other numerals such as `٣` or `½` are not numbers here
+            Some(c) if c.is_ascii_digit() => self.token_number(),
+            // `.5` is a number, any other `.` is member access
+            Some('.') if self.text[self.pos + 1..].starts_with(|c: char| c.is_ascii_digit()) => {
+                self.token_number()
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => self.token_identifier(self.pos),
+            Some('"') => {
+                if self.text[self.pos..].starts_with("\"\"\"") {
+                    Some(self.token_string_multi()?)
+                } else {
+                    Some(self.token_string()?)
+                }
+            }
+            Some('/') if self.text[self.pos..].starts_with("//") => self.token_comment(),
+            Some(c) if get_symbols().contains_key(&c) => self.token_symbols(),
+            Some(' ' | '\n') => self.trash(),
+            None => None,
+            Some(_) => return Err(self.invalid_character()),
+        };
+        Ok(token)
+    }
+
+    /// Error for the character at the current position.
+    fn invalid_character(&mut self) -> LexerError<'a> {
+        let character = self.peek().unwrap_or_default();
+        LexerError::InvalidCharacter {
+            context: &self.text[self.curr_start_line..self.pos + character.len_utf8()],
+            filename: self.filename,
+            character,
+            line: self.line,
+            col: self.col,
+        }
+    }
+
+    fn eof(&self) -> Token<'a> {
+        let end = Position::new(self.line, self.col);
+        Token::new(TokenKind::EOF, "", SourceSpan::new(end, end))
+    }
+
     pub fn tokenize(&mut self) -> Result<Vec<Token<'a>>, LexerError<'a>> {
         let mut vector: Vec<Token> = Vec::new();
         let len_text = self.text.len();
         while self.pos != len_text {
-            let token: Option<Token<'a>> = match self.peek() {
-                Some(c) if c.is_numeric() || c == '.' => self.token_number(),
-                Some(c) if c.is_alphabetic() => self.token_identifier(self.pos),
-                Some('"') => {
-                    if self.text[self.pos..].starts_with("\"\"\"") {
-                        Some(self.token_string_multi()?)
-                    } else {
-                        Some(self.token_string()?)
-                    }
-                }
-                Some(c) if get_symbols().contains_key(&c) => self.token_symbols(),
-                Some(' ' | '\n') => self.trash(),
-                None => None,
-                Some(invalid_char) => {
-                    return Err(LexerError::InvalidCharacter {
-                        context: &self.text[self.curr_start_line..self.pos + 1],
-                        filename: self.filename,
-                        character: invalid_char,
-                        line: self.line,
-                        col: self.col,
-                    });
-                }
-            };
-            if let Some(t) = token {
+            let start = self.pos;
+            if let Some(t) = self.next_token()? {
                 vector.push(t);
+            } else if self.pos == start {
+                // nothing consumed the character, going on would loop forever
+                return Err(self.invalid_character());
             }
         }
+
+        // an explicit end of input lets the parser report trailing tokens
+        vector.push(self.eof());
         Ok(vector)
     }
+
+    /// Tokenizes the whole text without ever failing, keeping every byte:
+    /// whitespace and comments become `WHITESPACE` and `COMMENT` tokens and
+    /// text that does not lex becomes an `ERROR` token, so concatenating the
+    /// values gives back the text. Ends with an `EOF` token.
+    pub fn tokenize_lossless(&mut self) -> Vec<Token<'a>> {
+        self.lossless = true;
+        let mut vector: Vec<Token> = Vec::new();
+        let len_text = self.text.len();
+        while self.pos != len_text {
+            let start = self.pos;
+            let start_pos = Position::new(self.line, self.col);
+            match self.next_token() {
+                Ok(Some(t)) => vector.push(t),
+                // an error, or a character no rule consumed, covers at least
+                // that character
+                Ok(None) | Err(_) => {
+                    if self.pos == start {
+                        self.advance();
+                    }
+                    let span = SourceSpan::new(start_pos, Position::new(self.line, self.col));
+                    vector.push(Token::new(TokenKind::ERROR, &self.text[start..self.pos], span));
+                }
+            }
+        }
+        vector.push(self.eof());
+        vector
+    }
 }
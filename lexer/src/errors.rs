@@ -1,6 +1,9 @@
 use owo_colors::OwoColorize;
 use std::fmt;
 
+use crate::messages::{Message, tr};
+use crate::token::SourceSpan;
+
 #[derive(Debug)]
 pub enum LexerError<'a> {
     // invalid character, line, position (col_start, col end)
@@ -38,15 +41,14 @@ impl<'a> fmt::Display for LexerError<'a> {
                 line,
                 col,
             } => {
-                writeln!(f, "{} {}", "invalid character".red(), character)?;
+                writeln!(f, "{} {}", tr(Message::InvalidCharacter).red(), character)?;
                 writeln!(f, "{}:{}:{}", filename.blue(), line.green(), col.white())?;
                 writeln!(f, "|\n{:>3} | {}", line.green(), context)?;
                 writeln!(
                     f,
-                    "| {:>width$}^ {}",
-                    "",
-                    "invalid character".red(),
-                    width = *col + 3
+                    "| {}^ {}",
+                    " ".repeat(*col + 3),
+                    tr(Message::InvalidCharacter).red()
                 )
             }
 
@@ -56,15 +58,14 @@ impl<'a> fmt::Display for LexerError<'a> {
                 line,
                 col,
             } => {
-                writeln!(f, "{}", "unexpected EOF".red())?;
+                writeln!(f, "{}", tr(Message::UnexpectedEof).red())?;
                 writeln!(f, "--> {}:{}:{}", filename, line, col)?;
                 writeln!(f, "  |\n{:>3} | {}", line.green(), context)?;
                 writeln!(
                     f,
-                    "  | {:>width$}^ {}",
-                    "",
-                    "unexpected EOF".red(),
-                    width = *col
+                    "  | {}^ {}",
+                    " ".repeat(*col),
+                    tr(Message::UnexpectedEof).red()
                 )
             }
 
@@ -74,17 +75,60 @@ impl<'a> fmt::Display for LexerError<'a> {
                 line,
                 col,
             } => {
-                writeln!(f, "{}", "Unterminated String literal".red())?;
+                writeln!(f, "{}", tr(Message::UnterminatedString).red())?;
                 writeln!(f, " --> {}:{}:{}", filename.blue(), line.green(), col)?;
                 writeln!(f, "  |\n{:>3} | {}", line.green(), context)?;
                 writeln!(
                     f,
-                    "  | {:>width$}^ {}",
-                    "",
-                    "unterminated string".red(),
-                    width = *col
+                    "  | {}^ {}",
+                    " ".repeat(*col),
+                    tr(Message::UnterminatedStringLabel).red()
                 )
             }
         }
     }
 }
+
+/// How a diagnostic rendered by [`write_snippet`] is highlighted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// Writes a diagnostic headed by `title` followed by the source line of
+/// `span` with a caret underline and `label`, in the layout `LexerError` uses.
+pub fn write_snippet(
+    f: &mut dyn fmt::Write,
+    severity: Severity,
+    title: &str,
+    label: &str,
+    filename: &str,
+    source: &str,
+    span: SourceSpan,
+) -> fmt::Result {
+    let line = span.start.line;
+    let col = span.start.column;
+    let context = source.lines().nth(line.saturating_sub(1)).unwrap_or("");
+    let width = if span.end.line == line && span.end.column > col {
+        span.end.column - col
+    } else {
+        1
+    };
+    let paint = |text: &str| match severity {
+        Severity::Error => text.red().to_string(),
+        Severity::Warning => text.yellow().to_string(),
+    };
+
+    writeln!(f, "{}", paint(title))?;
+    writeln!(f, " --> {}:{}:{}", filename.blue(), line.green(), col)?;
+    writeln!(f, "    |\n{:>3} | {}", line.green(), context)?;
+    // padded by hand, as a format width is limited to `u16::MAX`
+    writeln!(
+        f,
+        "    | {}{} {}",
+        " ".repeat(col.saturating_sub(1)),
+        paint(&"^".repeat(width)),
+        paint(label)
+    )
+}
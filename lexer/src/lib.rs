@@ -1,12 +1,14 @@
 use self::errors::LexerError;
 use self::token::Token;
 use self::engine::Lexer;
+use self::token_type::TokenKind;
 
 
 pub mod engine;
 pub mod token;
 pub mod token_type;
 pub mod errors;
+pub mod messages;
 
 
 
@@ -15,3 +17,24 @@ pub fn tokenize<'a>(text: &'a str, file: &'a str) -> Result<Vec<Token<'a>>, Lexe
     let mut lex: Lexer<'a> = Lexer::new(text, file);
     lex.tokenize()
 }
+
+/// Tokenizes `text` and also returns its line comments, which `tokenize` drops.
+pub fn tokenize_with_comments<'a>(
+    text: &'a str,
+    file: &'a str,
+) -> Result<(Vec<Token<'a>>, Vec<Token<'a>>), LexerError<'a>> {
+    let mut lex: Lexer<'a> = Lexer::new(text, file);
+    let tokens = lex.tokenize()?;
+    Ok((tokens, lex.comments().to_vec()))
+}
+
+/// Tokenizes `text` without losing anything. Returns the tokens the parser
+/// reads, including `ERROR` tokens for text that does not lex, and the
+/// whitespace and comments between them, both in source order. Together
+/// they cover every byte of `text`.
+pub fn tokenize_lossless<'a>(text: &'a str, file: &'a str) -> (Vec<Token<'a>>, Vec<Token<'a>>) {
+    let mut lex: Lexer<'a> = Lexer::new(text, file);
+    lex.tokenize_lossless()
+        .into_iter()
+        .partition(|t| !matches!(t.kind, TokenKind::WHITESPACE | TokenKind::COMMENT))
+}
@@ -0,0 +1,372 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Languages a diagnostic can be rendered in. English is the fallback for
+/// any message missing from another catalog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lang {
+    English,
+    Spanish,
+}
+
+impl Lang {
+    pub const ALL: &'static [Lang] = &[Lang::English, Lang::Spanish];
+
+    /// Parses a language code such as `es`, `en_US` or a full locale like
+    /// `es_CO.UTF-8`. Only the language part before `_` or `.` is used.
+    pub fn from_code(code: &str) -> Option<Lang> {
+        let lang = code.split(['_', '.', '-']).next()?.to_ascii_lowercase();
+        match lang.as_str() {
+            "en" => Some(Lang::English),
+            "es" => Some(Lang::Spanish),
+            _ => None,
+        }
+    }
+
+    /// Reads the language from the `LANG` environment variable, falling back
+    /// to English when it is unset or unknown.
+    pub fn from_env() -> Lang {
+        std::env::var("LANG")
+            .ok()
+            .and_then(|code| Lang::from_code(&code))
+            .unwrap_or(Lang::English)
+    }
+}
+
+static CURRENT_LANG: AtomicU8 = AtomicU8::new(0);
+
+/// Sets the language every diagnostic is rendered in from now on.
+pub fn set_lang(lang: Lang) {
+    CURRENT_LANG.store(lang as u8, Ordering::Relaxed);
+}
+
+pub fn current_lang() -> Lang {
+    match CURRENT_LANG.load(Ordering::Relaxed) {
+        1 => Lang::Spanish,
+        _ => Lang::English,
+    }
+}
+
+/// Key of every user-facing message. Messages may contain positional
+/// placeholders (`{0}`, `{1}`, ...) filled in by [`format`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Message {
+    // lexer
+    InvalidCharacter,
+    UnexpectedEof,
+    UnterminatedString,
+    UnterminatedStringLabel,
+
+    // token kinds without a fixed spelling
+    TokenFloat,
+    TokenInteger,
+    TokenIdentifier,
+    TokenString,
+    TokenComment,
+    TokenWhitespace,
+    TokenInvalid,
+    TokenEndOfInput,
+
+    // parser
+    ExpectedFound,
+    ExpectedEndOfInput,
+    ExpectedLabel,
+    OneOf,
+    LookupError,
+    NudNotFound,
+    LedNotFound,
+    InvalidIntegerLiteral,
+    InvalidLiteralLabel,
+    InvalidAssignTarget,
+    InvalidAssignTargetLabel,
+    MissingComma,
+    MissingCommaLabel,
+    UnclosedDelimiter,
+    UnclosedDelimiterLabel,
+    DuplicateField,
+    DuplicateFieldLabel,
+    MissingFields,
+    MissingFieldsLabel,
+    NestingTooDeep,
+    NestingTooDeepLabel,
+    NotTopLevel,
+    NotTopLevelLabel,
+
+    // modules
+    ModuleNotFound,
+    ModuleNotFoundLabel,
+    ModuleReadFailed,
+    ImportCycle,
+    ImportCycleLabel,
+    UnknownImport,
+    UnknownImportLabel,
+    PrivateImport,
+    PrivateImportLabel,
+
+    // types
+    BranchMismatch,
+    BranchMismatchLabel,
+    MissingElse,
+    MissingElseLabel,
+
+    // lints
+    WarningPrefix,
+    ErrorPrefix,
+    LintRedundantSemicolons,
+    LintRedundantSemicolonsLabel,
+    LintRedundantParens,
+    LintRedundantParensLabel,
+    LintUnused,
+    LintUnusedLabel,
+
+    // cli
+    PathNotFound,
+    ReadFailed,
+    LexerErrorPrefix,
+    ParserErrorPrefix,
+    UnknownLint,
+    UnknownLintLabel,
+    UnknownFormat,
+    DeniedWarnings,
+}
+
+impl Message {
+    pub const ALL: &'static [Message] = &[
+        Message::InvalidCharacter,
+        Message::UnexpectedEof,
+        Message::UnterminatedString,
+        Message::UnterminatedStringLabel,
+        Message::TokenFloat,
+        Message::TokenInteger,
+        Message::TokenIdentifier,
+        Message::TokenString,
+        Message::TokenComment,
+        Message::TokenWhitespace,
+        Message::TokenInvalid,
+        Message::TokenEndOfInput,
+        Message::ExpectedFound,
+        Message::ExpectedEndOfInput,
+        Message::ExpectedLabel,
+        Message::OneOf,
+        Message::LookupError,
+        Message::NudNotFound,
+        Message::LedNotFound,
+        Message::InvalidIntegerLiteral,
+        Message::InvalidLiteralLabel,
+        Message::InvalidAssignTarget,
+        Message::InvalidAssignTargetLabel,
+        Message::MissingComma,
+        Message::MissingCommaLabel,
+        Message::UnclosedDelimiter,
+        Message::UnclosedDelimiterLabel,
+        Message::DuplicateField,
+        Message::DuplicateFieldLabel,
+        Message::MissingFields,
+        Message::MissingFieldsLabel,
+        Message::NestingTooDeep,
+        Message::NestingTooDeepLabel,
+        Message::NotTopLevel,
+        Message::NotTopLevelLabel,
+        Message::ModuleNotFound,
+        Message::ModuleNotFoundLabel,
+        Message::ModuleReadFailed,
+        Message::ImportCycle,
+        Message::ImportCycleLabel,
+        Message::UnknownImport,
+        Message::UnknownImportLabel,
+        Message::PrivateImport,
+        Message::PrivateImportLabel,
+        Message::BranchMismatch,
+        Message::BranchMismatchLabel,
+        Message::MissingElse,
+        Message::MissingElseLabel,
+        Message::WarningPrefix,
+        Message::ErrorPrefix,
+        Message::LintRedundantSemicolons,
+        Message::LintRedundantSemicolonsLabel,
+        Message::LintRedundantParens,
+        Message::LintRedundantParensLabel,
+        Message::LintUnused,
+        Message::LintUnusedLabel,
+        Message::PathNotFound,
+        Message::ReadFailed,
+        Message::LexerErrorPrefix,
+        Message::ParserErrorPrefix,
+        Message::UnknownLint,
+        Message::UnknownLintLabel,
+        Message::UnknownFormat,
+        Message::DeniedWarnings,
+    ];
+}
+
+static ENGLISH: OnceLock<HashMap<Message, &'static str>> = OnceLock::new();
+static SPANISH: OnceLock<HashMap<Message, &'static str>> = OnceLock::new();
+
+fn get_english() -> &'static HashMap<Message, &'static str> {
+    ENGLISH.get_or_init(|| {
+        HashMap::from([
+            (Message::InvalidCharacter, "invalid character"),
+            (Message::UnexpectedEof, "unexpected EOF"),
+            (Message::UnterminatedString, "Unterminated String literal"),
+            (Message::UnterminatedStringLabel, "unterminated string"),
+            (Message::TokenFloat, "float"),
+            (Message::TokenInteger, "integer"),
+            (Message::TokenIdentifier, "identifier"),
+            (Message::TokenString, "string"),
+            (Message::TokenComment, "comment"),
+            (Message::TokenWhitespace, "whitespace"),
+            (Message::TokenInvalid, "invalid token"),
+            (Message::TokenEndOfInput, "end of input"),
+            (Message::ExpectedFound, "expected {0}, found {1}"),
+            (Message::ExpectedEndOfInput, "expected {0}, found end of input"),
+            (Message::ExpectedLabel, "expected {0}"),
+            (Message::OneOf, "one of {0}"),
+            (Message::LookupError, "Lookup error"),
+            (Message::NudNotFound, "Nud function for token kind {0} not found"),
+            (Message::LedNotFound, "Led function for token kind {0} not found"),
+            (Message::InvalidIntegerLiteral, "invalid integer literal `{0}`"),
+            (Message::InvalidLiteralLabel, "invalid literal"),
+            (Message::InvalidAssignTarget, "invalid assignment target"),
+            (Message::InvalidAssignTargetLabel, "cannot assign to this expression"),
+            (Message::MissingComma, "missing `,` between elements"),
+            (Message::MissingCommaLabel, "expected `,` before this"),
+            (Message::UnclosedDelimiter, "unclosed {0}"),
+            (Message::UnclosedDelimiterLabel, "expected {0} to close this"),
+            (Message::DuplicateField, "duplicate field `{0}`"),
+            (Message::DuplicateFieldLabel, "`{0}` is already listed before"),
+            (Message::MissingFields, "missing {1} in `{0}` literal"),
+            (Message::MissingFieldsLabel, "add the missing fields"),
+            (Message::NestingTooDeep, "nesting deeper than the limit of {0} levels"),
+            (Message::NestingTooDeepLabel, "too deeply nested, the rest of the file is skipped"),
+            (Message::NotTopLevel, "{0} is only allowed at the top level of a file"),
+            (Message::NotTopLevelLabel, "not at the top level"),
+            (Message::ModuleNotFound, "cannot find module `{0}`"),
+            (Message::ModuleNotFoundLabel, "no file at {0}"),
+            (Message::ModuleReadFailed, "failed to read {0}: {1}"),
+            (Message::ImportCycle, "import cycle: {0}"),
+            (Message::ImportCycleLabel, "this import closes the cycle"),
+            (Message::UnknownImport, "`{1}` has no item named `{0}`"),
+            (Message::UnknownImportLabel, "not declared in the module"),
+            (Message::PrivateImport, "`{0}` is private to `{1}`"),
+            (Message::PrivateImportLabel, "declared without `pub`"),
+            (Message::BranchMismatch, "`if` and `else` have incompatible types"),
+            (Message::BranchMismatchLabel, "expected `{0}`, found `{1}`"),
+            (Message::MissingElse, "`if` may be missing an `else` branch"),
+            (Message::MissingElseLabel, "an `if` without `else` is `()`, found `{0}`"),
+            (Message::WarningPrefix, "warning"),
+            (Message::ErrorPrefix, "error"),
+            (Message::LintRedundantSemicolons, "unnecessary semicolon"),
+            (Message::LintRedundantSemicolonsLabel, "remove this semicolon"),
+            (Message::LintRedundantParens, "unnecessary parentheses"),
+            (Message::LintRedundantParensLabel, "remove these parentheses"),
+            (Message::LintUnused, "unused value"),
+            (Message::LintUnusedLabel, "this value is never used"),
+            (Message::PathNotFound, "the path doesnt exists: {0}"),
+            (Message::ReadFailed, "failed to read the file: {0}"),
+            (Message::LexerErrorPrefix, "Lexer error"),
+            (Message::ParserErrorPrefix, "Parser error"),
+            (Message::UnknownLint, "unknown lint `{0}`"),
+            (Message::UnknownLintLabel, "no lint has this name"),
+            (Message::UnknownFormat, "unknown format `{0}`, expected one of {1}"),
+            (Message::DeniedWarnings, "aborting due to {0} denied warning(s)"),
+        ])
+    })
+}
+
+fn get_spanish() -> &'static HashMap<Message, &'static str> {
+    SPANISH.get_or_init(|| {
+        HashMap::from([
+            (Message::InvalidCharacter, "carácter inválido"),
+            (Message::UnexpectedEof, "fin de archivo inesperado"),
+            (Message::UnterminatedString, "Cadena de texto sin terminar"),
+            (Message::UnterminatedStringLabel, "cadena sin terminar"),
+            (Message::TokenFloat, "flotante"),
+            (Message::TokenInteger, "entero"),
+            (Message::TokenIdentifier, "identificador"),
+            (Message::TokenString, "cadena"),
+            (Message::TokenComment, "comentario"),
+            (Message::TokenWhitespace, "espacio en blanco"),
+            (Message::TokenInvalid, "token inválido"),
+            (Message::TokenEndOfInput, "fin de la entrada"),
+            (Message::ExpectedFound, "se esperaba {0}, se encontró {1}"),
+            (Message::ExpectedEndOfInput, "se esperaba {0}, se encontró el fin de la entrada"),
+            (Message::ExpectedLabel, "se esperaba {0}"),
+            (Message::OneOf, "uno de {0}"),
+            (Message::LookupError, "Error de búsqueda"),
+            (Message::NudNotFound, "No se encontró la función nud para el token {0}"),
+            (Message::LedNotFound, "No se encontró la función led para el token {0}"),
+            (Message::InvalidIntegerLiteral, "literal entero inválido `{0}`"),
+            (Message::InvalidLiteralLabel, "literal inválido"),
+            (Message::InvalidAssignTarget, "destino de asignación inválido"),
+            (Message::InvalidAssignTargetLabel, "no se puede asignar a esta expresión"),
+            (Message::MissingComma, "falta `,` entre elementos"),
+            (Message::MissingCommaLabel, "se esperaba `,` antes de esto"),
+            (Message::UnclosedDelimiter, "{0} sin cerrar"),
+            (Message::UnclosedDelimiterLabel, "se esperaba {0} para cerrar esto"),
+            (Message::DuplicateField, "campo `{0}` duplicado"),
+            (Message::DuplicateFieldLabel, "`{0}` ya aparece antes"),
+            (Message::MissingFields, "faltan {1} en el literal de `{0}`"),
+            (Message::MissingFieldsLabel, "agregue los campos que faltan"),
+            (Message::NestingTooDeep, "anidamiento más profundo que el límite de {0} niveles"),
+            (Message::NestingTooDeepLabel, "anidado demasiado, se omite el resto del archivo"),
+            (Message::NotTopLevel, "{0} solo se permite en el nivel superior de un archivo"),
+            (Message::NotTopLevelLabel, "no está en el nivel superior"),
+            (Message::ModuleNotFound, "no se encontró el módulo `{0}`"),
+            (Message::ModuleNotFoundLabel, "no hay ningún archivo en {0}"),
+            (Message::ModuleReadFailed, "Error al leer {0}: {1}"),
+            (Message::ImportCycle, "ciclo de importaciones: {0}"),
+            (Message::ImportCycleLabel, "esta importación cierra el ciclo"),
+            (Message::UnknownImport, "`{1}` no tiene ningún elemento llamado `{0}`"),
+            (Message::UnknownImportLabel, "no está declarado en el módulo"),
+            (Message::PrivateImport, "`{0}` es privado de `{1}`"),
+            (Message::PrivateImportLabel, "declarado sin `pub`"),
+            (Message::BranchMismatch, "`if` y `else` tienen tipos incompatibles"),
+            (Message::BranchMismatchLabel, "se esperaba `{0}`, se encontró `{1}`"),
+            (Message::MissingElse, "puede faltar una rama `else` en el `if`"),
+            (Message::MissingElseLabel, "un `if` sin `else` es `()`, se encontró `{0}`"),
+            (Message::WarningPrefix, "advertencia"),
+            (Message::ErrorPrefix, "error"),
+            (Message::LintRedundantSemicolons, "punto y coma innecesario"),
+            (Message::LintRedundantSemicolonsLabel, "elimina este punto y coma"),
+            (Message::LintRedundantParens, "paréntesis innecesarios"),
+            (Message::LintRedundantParensLabel, "elimina estos paréntesis"),
+            (Message::LintUnused, "valor sin usar"),
+            (Message::LintUnusedLabel, "este valor nunca se usa"),
+            (Message::PathNotFound, "la ruta no existe: {0}"),
+            (Message::ReadFailed, "Error al leer el archivo: {0}"),
+            (Message::LexerErrorPrefix, "Error léxico"),
+            (Message::ParserErrorPrefix, "Error de sintaxis"),
+            (Message::UnknownLint, "lint desconocido `{0}`"),
+            (Message::UnknownLintLabel, "ningún lint tiene este nombre"),
+            (Message::UnknownFormat, "formato desconocido `{0}`, se esperaba uno de {1}"),
+            (Message::DeniedWarnings, "abortando por {0} advertencia(s) denegada(s)"),
+        ])
+    })
+}
+
+/// Returns the catalog of messages for a language.
+pub fn catalog(lang: Lang) -> &'static HashMap<Message, &'static str> {
+    match lang {
+        Lang::English => get_english(),
+        Lang::Spanish => get_spanish(),
+    }
+}
+
+/// Looks up a message in the current language, falling back to English.
+pub fn tr(msg: Message) -> &'static str {
+    catalog(current_lang())
+        .get(&msg)
+        .or_else(|| get_english().get(&msg))
+        .copied()
+        .unwrap_or("")
+}
+
+/// Looks up a message and replaces its `{n}` placeholders with `args[n]`.
+pub fn format(msg: Message, args: &[&dyn std::fmt::Display]) -> String {
+    let mut text = tr(msg).to_string();
+    for (i, arg) in args.iter().enumerate() {
+        text = text.replace(&format!("{{{}}}", i), &arg.to_string());
+    }
+    text
+}
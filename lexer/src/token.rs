@@ -15,7 +15,8 @@ impl SourceSpan {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+// ordered by line, then column, which is source order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Position {
     pub line: usize,
     pub column: usize,
@@ -29,7 +30,7 @@ impl Position {
 
 impl SourceSpan {
     pub fn new(start: Position, end: Position) -> SourceSpan {
-        SourceSpan { start: start, end: end }
+        SourceSpan { start, end }
     }
 }
 
@@ -49,7 +50,7 @@ impl<'a> Token<'a> {
         Token {
             kind: (kind),
             value: (value),
-            span: span,
+            span,
         }
     }
 }
@@ -1,6 +1,6 @@
+use crate::messages::{Message, tr};
 
-
-
+#[allow(non_camel_case_types)]
 #[derive(Debug, Copy,Clone, PartialEq, Eq, Hash)]
 pub enum TokenKind{
     FLOAT,
@@ -17,6 +17,22 @@ pub enum TokenKind{
     LESS,
     NEGATION,
     VERTICAL_BAR,
+    CARET,
+    QUESTION,
+
+    EQUAL_EQUAL,
+    NOT_EQUAL,
+    LESS_EQUAL,
+    GREATER_EQUAL,
+    DOUBLE_AMPER,
+    DOUBLE_VERTICAL_BAR,
+    DOUBLE_LESS,
+    DOUBLE_GREATER,
+    DOUBLE_STAR,
+    DOUBLE_DOT,
+    DOUBLE_DOT_EQUAL,
+    ARROW,
+    FAT_ARROW,
 
     SEMICOLON,
 
@@ -26,6 +42,19 @@ pub enum TokenKind{
     WHILE,
     FUNCTION,
     RETURN,
+    LET,
+    VAR,
+    IN,
+    BREAK,
+    CONTINUE,
+    AS,
+    STRUCT,
+    ENUM,
+    MATCH,
+    IMPORT,
+    PUB,
+    TRUE,
+    FALSE,
     LPAREN,
     RPAREN,
     LBRACE,
@@ -34,6 +63,87 @@ pub enum TokenKind{
     RBRACKET,
     COMMA,
     COLON,
+    DOT,
     IDENTIFIER,
     LITERALSTRING,
+
+    COMMENT,
+    // only produced by lossless lexing: a run of spaces and newlines, and
+    // text that does not form a token
+    WHITESPACE,
+    ERROR,
+    EOF,
+}
+
+impl std::fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // symbols and keywords are quoted as written, the other kinds are
+        // named in the language of the diagnostics
+        let name = match self {
+            TokenKind::FLOAT => tr(Message::TokenFloat),
+            TokenKind::INTEGER => tr(Message::TokenInteger),
+            TokenKind::PLUS => "`+`",
+            TokenKind::STAR => "`*`",
+            TokenKind::EQUAL => "`=`",
+            TokenKind::MODULO => "`%`",
+            TokenKind::SLASH => "`/`",
+            TokenKind::DASH => "`-`",
+            TokenKind::AMPER => "`&`",
+            TokenKind::GREATER => "`>`",
+            TokenKind::LESS => "`<`",
+            TokenKind::NEGATION => "`!`",
+            TokenKind::VERTICAL_BAR => "`|`",
+            TokenKind::CARET => "`^`",
+            TokenKind::QUESTION => "`?`",
+            TokenKind::EQUAL_EQUAL => "`==`",
+            TokenKind::NOT_EQUAL => "`!=`",
+            TokenKind::LESS_EQUAL => "`<=`",
+            TokenKind::GREATER_EQUAL => "`>=`",
+            TokenKind::DOUBLE_AMPER => "`&&`",
+            TokenKind::DOUBLE_VERTICAL_BAR => "`||`",
+            TokenKind::DOUBLE_LESS => "`<<`",
+            TokenKind::DOUBLE_GREATER => "`>>`",
+            TokenKind::DOUBLE_STAR => "`**`",
+            TokenKind::DOUBLE_DOT => "`..`",
+            TokenKind::DOUBLE_DOT_EQUAL => "`..=`",
+            TokenKind::ARROW => "`->`",
+            TokenKind::FAT_ARROW => "`=>`",
+            TokenKind::SEMICOLON => "`;`",
+            TokenKind::IF => "`if`",
+            TokenKind::ELSE => "`else`",
+            TokenKind::FOR => "`for`",
+            TokenKind::WHILE => "`while`",
+            TokenKind::FUNCTION => "`func`",
+            TokenKind::RETURN => "`return`",
+            TokenKind::LET => "`let`",
+            TokenKind::VAR => "`var`",
+            TokenKind::IN => "`in`",
+            TokenKind::BREAK => "`break`",
+            TokenKind::CONTINUE => "`continue`",
+            TokenKind::AS => "`as`",
+            TokenKind::STRUCT => "`struct`",
+            TokenKind::ENUM => "`enum`",
+            TokenKind::MATCH => "`match`",
+            TokenKind::IMPORT => "`import`",
+            TokenKind::PUB => "`pub`",
+            TokenKind::TRUE => "`true`",
+            TokenKind::FALSE => "`false`",
+            TokenKind::LPAREN => "`(`",
+            TokenKind::RPAREN => "`)`",
+            TokenKind::LBRACE => "`{`",
+            TokenKind::RBRACE => "`}`",
+            TokenKind::LBRACKET => "`[`",
+            TokenKind::RBRACKET => "`]`",
+            TokenKind::COMMA => "`,`",
+            TokenKind::COLON => "`:`",
+            TokenKind::DOT => "`.`",
+            TokenKind::IDENTIFIER => tr(Message::TokenIdentifier),
+            TokenKind::LITERALSTRING => tr(Message::TokenString),
+            TokenKind::COMMENT => tr(Message::TokenComment),
+            TokenKind::WHITESPACE => tr(Message::TokenWhitespace),
+            TokenKind::ERROR => tr(Message::TokenInvalid),
+            TokenKind::EOF => tr(Message::TokenEndOfInput),
+        };
+        write!(f, "{}", name)
+    }
 }
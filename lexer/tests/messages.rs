@@ -0,0 +1,34 @@
+use lexer::messages::{Lang, Message, catalog, set_lang};
+use lexer::token_type::TokenKind;
+
+#[test]
+fn every_message_exists_in_every_catalog() {
+    for lang in Lang::ALL {
+        for msg in Message::ALL {
+            assert!(
+                catalog(*lang).contains_key(msg),
+                "{:?} is missing from the {:?} catalog",
+                msg,
+                lang
+            );
+        }
+    }
+}
+
+#[test]
+fn lang_codes_accept_full_locales() {
+    assert_eq!(Lang::from_code("es_CO.UTF-8"), Some(Lang::Spanish));
+    assert_eq!(Lang::from_code("en"), Some(Lang::English));
+    assert_eq!(Lang::from_code("C"), None);
+}
+
+#[test]
+fn token_kinds_are_named_in_the_current_language() {
+    set_lang(Lang::Spanish);
+    assert_eq!(TokenKind::IDENTIFIER.to_string(), "identificador");
+    assert_eq!(TokenKind::EOF.to_string(), "fin de la entrada");
+    assert_eq!(TokenKind::PLUS.to_string(), "`+`");
+    set_lang(Lang::English);
+    assert_eq!(TokenKind::IDENTIFIER.to_string(), "identifier");
+    assert_eq!(TokenKind::EOF.to_string(), "end of input");
+}
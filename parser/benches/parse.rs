@@ -0,0 +1,135 @@
+//! Parses a large generated program and reports the parse time and the heap
+//! allocations the parser makes. Run with `cargo bench -p parser`.
+//!
+//! Lexing happens once up front, only `Parser::parse_program` is measured.
+//!
+//! Baseline on this input (60,000 lines, 536k tokens, release build, 10
+//! runs), measured on one core of an Intel Xeon with rustc 1.95.0. Each row
+//! is a clean `cargo bench -p parser` at the commit named, the first one
+//! with this file copied in:
+//!
+//! | AST                        | commit  | median  | allocations              |
+//! |----------------------------|---------|---------|--------------------------|
+//! | boxed nodes                | 9dc6933 | 11.9 ms | 468,033 (0.87 per token) |
+//! | arena of nodes             | 436278e | 9.4 ms  | 240,037 (0.45 per token) |
+//! | arena, with later features | dff4ace | 12.4 ms | 244,038 (0.46 per token) |
+//!
+//! Times depend on the machine, so compare them against a run of those
+//! commits on the same one. Allocation counts do not, and a count well
+//! above 0.46 per token means something allocates per node again.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use parser::parser::engine::Parser;
+
+/// Counts every allocation made through the global allocator.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// One block of statements, repeated to build the input. It touches every
+/// kind of expression so no node type is left out of the measurement.
+const CHUNK: &str = r#"
+struct Point{i} { x: int, y: int }
+enum Shape{i} { Circle(float), Rect(float, float), Empty }
+func area{i}(s: Shape{i}, scale: f64) -> f64 {
+    let base = match s {
+        Shape{i}.Circle(r) if r > 0.0 => 3.14 * r * r,
+        Rect(w, h) => w * h,
+        _ => 0.0,
+    };
+    return base * scale as f64;
+}
+func walk{i}(points: [Point{i}], limit: int) -> int {
+    var total = 0;
+    for p in points {
+        if p.x > limit && !(p.y == 0) {
+            total = total + p.x * p.y - (p.x % 3) ** 2;
+        } else if p.x < 0 {
+            continue;
+        } else {
+            total = total - 1;
+        }
+    }
+    while total > 1000 {
+        total = total >> 1 | 1;
+    }
+    let m = {"a": [1, 2, 3], "b": [4, 5]};
+    let q = Point{i} { x: total, y: m["a"][0] };
+    let pair = (q.x, q.y, -total);
+    return f(q.x, pair, [total, limit + 1]);
+}
+"#;
+
+const REPETITIONS: usize = 2_000;
+const RUNS: usize = 10;
+
+fn generate() -> String {
+    let mut source = String::with_capacity(CHUNK.len() * REPETITIONS);
+    for i in 0..REPETITIONS {
+        source.push_str(&CHUNK.replace("{i}", &i.to_string()));
+    }
+    source
+}
+
+fn main() {
+    let source = generate();
+    let tokens = lexer::tokenize(&source, "bench").expect("generated source lexes");
+
+    let mut times: Vec<Duration> = Vec::with_capacity(RUNS);
+    let mut allocations = 0;
+    let mut bytes = 0;
+    for _ in 0..RUNS {
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        let before_bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+        let start = Instant::now();
+
+        let mut parser = Parser::new(&tokens, 0);
+        let program = Parser::parse_program(&mut parser);
+        times.push(start.elapsed());
+        allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+        bytes = ALLOCATED_BYTES.load(Ordering::Relaxed) - before_bytes;
+
+        assert!(parser.errors.is_empty(), "generated source parses");
+        black_box(program);
+    }
+    times.sort();
+
+    println!(
+        "parse: {} lines, {} tokens",
+        source.lines().count(),
+        tokens.len()
+    );
+    println!(
+        "  time        min {:>8.2?}  median {:>8.2?}",
+        times[0],
+        times[RUNS / 2]
+    );
+    println!("  allocations {:>8} ({:.2} per token)", allocations, allocations as f64 / tokens.len() as f64);
+    println!("  allocated   {:>8} KiB", bytes / 1024);
+}
@@ -0,0 +1,80 @@
+//! Lossless concrete syntax tree, for tools that must keep the source as
+//! written, like formatters and refactorings.
+//!
+//! The tree is built in two layers. The green tree holds every token with
+//! its exact text, whitespace and comments included, and is immutable. The
+//! red tree of `SyntaxNode`s wraps it with positions and parent links. Its
+//! nodes are the nodes of the typed AST, so each one leads to the
+//! `ExprKind` or `StmtKind` it was parsed into.
+
+mod builder;
+pub mod green;
+pub mod syntax;
+
+use std::rc::Rc;
+
+use lexer::token::Token;
+
+use crate::cst::syntax::SyntaxNode;
+use crate::nodes::exprs::{ExprId, ExprKind};
+use crate::nodes::stmts::{Program, StmtId, StmtKind};
+use crate::parser::engine::Parser;
+use crate::parser::errors::ParserError;
+use crate::parser::events::SyntaxKind;
+
+/// A parsed file as both a lossless syntax tree and a typed AST.
+///
+/// # Example
+/// ```
+/// use parser::cst::Cst;
+/// let source = "let x = 1 + 2; // three\n";
+/// let (tokens, trivia) = lexer::tokenize_lossless(source, "example");
+/// let cst = Cst::parse(&tokens, &trivia);
+/// assert_eq!(cst.root.text(), source);
+/// ```
+pub struct Cst<'a> {
+    pub root: SyntaxNode,
+    pub program: Program,
+    /// Syntax errors of the parse. The tree is complete even with errors.
+    pub errors: Vec<ParserError<'a>>,
+}
+
+impl<'a> Cst<'a> {
+    /// Parses the output of `lexer::tokenize_lossless`.
+    pub fn parse(tokens: &'a [Token<'a>], trivia: &[Token<'a>]) -> Cst<'a> {
+        let mut p = Parser::new(tokens, 0);
+        p.events = Some(Vec::new());
+        let program = Parser::parse_program(&mut p);
+        let events = p.events.take().unwrap_or_default();
+        let green = builder::build_tree(tokens, trivia, &events);
+        Cst {
+            root: SyntaxNode::new_root(Rc::new(green)),
+            program,
+            errors: p.errors,
+        }
+    }
+
+    /// The expression `node` stands for.
+    pub fn expr(&self, node: &SyntaxNode) -> Option<&ExprKind> {
+        node.expr().map(|id| self.program.ast.expr(id))
+    }
+
+    /// The statement `node` stands for.
+    pub fn stmt(&self, node: &SyntaxNode) -> Option<&StmtKind> {
+        node.stmt().map(|id| self.program.ast.stmt(id))
+    }
+
+    /// The syntax node of an expression.
+    pub fn expr_node(&self, id: ExprId) -> Option<SyntaxNode> {
+        self.root
+            .descendants()
+            .find(|node| node.kind() == SyntaxKind::Expr(id))
+    }
+
+    /// The syntax node of a statement.
+    pub fn stmt_node(&self, id: StmtId) -> Option<SyntaxNode> {
+        self.root
+            .descendants()
+            .find(|node| node.kind() == SyntaxKind::Stmt(id))
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use lexer::token::Token;
+use lexer::token_type::TokenKind;
+
+use crate::cst::green::{GreenElement, GreenNode, GreenToken};
+use crate::parser::events::{Event, SyntaxKind};
+
+/// Builds a green tree by replaying the events of a parse over its tokens,
+/// putting the trivia back between them.
+///
+/// Finished elements wait on a stack, each with a key giving its place in
+/// the token stream: `2 * i + 1` for the token `i` and `2 * i` for the
+/// trivia before it. A node takes the elements from its first token on,
+/// so the trivia before a node stays outside of it.
+struct Builder<'a> {
+    stack: Vec<(usize, GreenElement)>,
+    // equal tokens are shared, most of them are keywords, punctuation and
+    // single spaces
+    cache: HashMap<(TokenKind, &'a str), Rc<GreenToken>>,
+}
+
+impl<'a> Builder<'a> {
+    fn push_token(&mut self, key: usize, token: &Token<'a>) {
+        let green = self
+            .cache
+            .entry((token.kind, token.value))
+            .or_insert_with(|| Rc::new(GreenToken::new(token.kind, token.value)));
+        self.stack.push((key, GreenElement::Token(Rc::clone(green))));
+    }
+
+    fn push_node(&mut self, kind: SyntaxKind, start: usize) {
+        let split = self.stack.partition_point(|(key, _)| *key < 2 * start + 1);
+        let children: Vec<(usize, GreenElement)> = self.stack.drain(split..).collect();
+        let key = children.first().map_or(2 * start, |(key, _)| *key);
+        let node = GreenNode::new(kind, children.into_iter().map(|(_, e)| e).collect());
+        self.stack.push((key, GreenElement::Node(Rc::new(node))));
+    }
+}
+
+/// `tokens` are the tokens the parser read and `trivia` the whitespace and
+/// comments between them, as split by `lexer::tokenize_lossless`.
+pub(crate) fn build_tree<'a>(
+    tokens: &[Token<'a>],
+    trivia: &[Token<'a>],
+    events: &[Event],
+) -> GreenNode {
+    let mut builder = Builder {
+        stack: Vec::with_capacity(tokens.len() + trivia.len()),
+        cache: HashMap::new(),
+    };
+    let mut trivia = trivia.iter().peekable();
+    let mut consumed = 0;
+    for event in events {
+        match *event {
+            Event::Token => {
+                let token = &tokens[consumed];
+                while let Some(t) = trivia.next_if(|t| t.span.start < token.span.start) {
+                    builder.push_token(2 * consumed, t);
+                }
+                builder.push_token(2 * consumed + 1, token);
+                consumed += 1;
+            }
+            Event::Node { kind, start } => builder.push_node(kind, start),
+        }
+    }
+
+    // whatever the parser left, at least the end of input, goes to the root
+    for (i, token) in tokens.iter().enumerate().skip(consumed) {
+        while let Some(t) = trivia.next_if(|t| t.span.start < token.span.start) {
+            builder.push_token(2 * i, t);
+        }
+        if token.kind != TokenKind::EOF {
+            builder.push_token(2 * i + 1, token);
+        }
+    }
+    for t in trivia {
+        builder.push_token(2 * tokens.len(), t);
+    }
+
+    let children = builder.stack.into_iter().map(|(_, e)| e).collect();
+    GreenNode::new(SyntaxKind::Program, children)
+}
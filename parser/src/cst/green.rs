@@ -0,0 +1,115 @@
+use std::rc::Rc;
+
+use lexer::token_type::TokenKind;
+
+use crate::parser::events::SyntaxKind;
+
+/// A token of the green tree: its kind and exact text. Trivia, whitespace
+/// and comments, are tokens too.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GreenToken {
+    kind: TokenKind,
+    text: Box<str>,
+}
+
+impl GreenToken {
+    pub fn new(kind: TokenKind, text: &str) -> GreenToken {
+        GreenToken {
+            kind,
+            text: text.into(),
+        }
+    }
+
+    pub fn kind(&self) -> TokenKind {
+        self.kind
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn text_len(&self) -> usize {
+        self.text.len()
+    }
+}
+
+/// A child of a green node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GreenElement {
+    Node(Rc<GreenNode>),
+    Token(Rc<GreenToken>),
+}
+
+impl GreenElement {
+    pub fn text_len(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.text_len(),
+            GreenElement::Token(token) => token.text_len(),
+        }
+    }
+}
+
+/// A node of the green tree. Green nodes are immutable and know their
+/// kind, children and text length, but neither their position nor their
+/// parent, which `SyntaxNode` adds on top. The builder shares equal tokens
+/// but not equal nodes, each node is built anew.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GreenNode {
+    kind: SyntaxKind,
+    text_len: usize,
+    children: Vec<GreenElement>,
+}
+
+impl GreenNode {
+    pub fn new(kind: SyntaxKind, children: Vec<GreenElement>) -> GreenNode {
+        let text_len = children.iter().map(GreenElement::text_len).sum();
+        GreenNode {
+            kind,
+            text_len,
+            children,
+        }
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    /// Length of the text of the node, in bytes.
+    pub fn text_len(&self) -> usize {
+        self.text_len
+    }
+
+    pub fn children(&self) -> &[GreenElement] {
+        &self.children
+    }
+
+    /// Appends the text of every token under the node to `out`.
+    pub fn write_text(&self, out: &mut String) {
+        // a long chain of operations nests deep, so this is a loop
+        let mut stack = vec![self.children.iter()];
+        while let Some(children) = stack.last_mut() {
+            match children.next() {
+                Some(GreenElement::Node(node)) => stack.push(node.children.iter()),
+                Some(GreenElement::Token(token)) => out.push_str(token.text()),
+                None => {
+                    stack.pop();
+                }
+            }
+        }
+    }
+}
+
+// nodes nothing else holds on to are dropped in a loop, as they may nest
+// deep
+impl Drop for GreenNode {
+    fn drop(&mut self) {
+        let mut children = std::mem::take(&mut self.children);
+        while let Some(child) = children.pop() {
+            if let GreenElement::Node(node) = child
+                && let Ok(mut node) = Rc::try_unwrap(node)
+            {
+                children.append(&mut node.children);
+            }
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use std::fmt;
+use std::ops::Range;
+use std::rc::Rc;
+
+use lexer::token_type::TokenKind;
+
+use crate::cst::green::{GreenElement, GreenNode, GreenToken};
+use crate::nodes::exprs::ExprId;
+use crate::nodes::stmts::StmtId;
+use crate::parser::events::SyntaxKind;
+
+/// A node of the red tree: a green node seen at a position in the file,
+/// with a link to its parent. Red nodes are created on demand while
+/// walking down from the root and are cheap to clone.
+#[derive(Clone)]
+pub struct SyntaxNode(Rc<NodeData>);
+
+struct NodeData {
+    green: Rc<GreenNode>,
+    // byte offset of the node in the file
+    offset: usize,
+    parent: Option<SyntaxNode>,
+}
+
+// a node deep in a long chain may hold the last link to all of its
+// ancestors, so they are dropped in a loop
+impl Drop for NodeData {
+    fn drop(&mut self) {
+        let mut parent = self.parent.take();
+        while let Some(SyntaxNode(data)) = parent {
+            parent = match Rc::try_unwrap(data) {
+                Ok(mut data) => data.parent.take(),
+                Err(_) => None,
+            };
+        }
+    }
+}
+
+/// A token of the red tree, see `SyntaxNode`.
+#[derive(Clone)]
+pub struct SyntaxToken {
+    green: Rc<GreenToken>,
+    offset: usize,
+    parent: SyntaxNode,
+}
+
+/// A child of a syntax node.
+#[derive(Clone, Debug)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxNode {
+    pub fn new_root(green: Rc<GreenNode>) -> SyntaxNode {
+        SyntaxNode(Rc::new(NodeData {
+            green,
+            offset: 0,
+            parent: None,
+        }))
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.green.kind()
+    }
+
+    pub fn green(&self) -> &Rc<GreenNode> {
+        &self.0.green
+    }
+
+    pub fn parent(&self) -> Option<SyntaxNode> {
+        self.0.parent.clone()
+    }
+
+    /// The byte range of the node in the file.
+    pub fn text_range(&self) -> Range<usize> {
+        self.0.offset..self.0.offset + self.0.green.text_len()
+    }
+
+    /// The exact source text of the node, trivia included.
+    pub fn text(&self) -> String {
+        let mut text = String::with_capacity(self.0.green.text_len());
+        self.0.green.write_text(&mut text);
+        text
+    }
+
+    /// The expression the node stands for, if any.
+    pub fn expr(&self) -> Option<ExprId> {
+        match self.kind() {
+            SyntaxKind::Expr(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// The statement the node stands for, if any.
+    pub fn stmt(&self) -> Option<StmtId> {
+        match self.kind() {
+            SyntaxKind::Stmt(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    pub fn children_with_tokens(&self) -> impl Iterator<Item = SyntaxElement> + '_ {
+        let mut offset = self.0.offset;
+        self.0.green.children().iter().map(move |child| {
+            let element = match child {
+                GreenElement::Node(node) => SyntaxElement::Node(SyntaxNode(Rc::new(NodeData {
+                    green: Rc::clone(node),
+                    offset,
+                    parent: Some(self.clone()),
+                }))),
+                GreenElement::Token(token) => SyntaxElement::Token(SyntaxToken {
+                    green: Rc::clone(token),
+                    offset,
+                    parent: self.clone(),
+                }),
+            };
+            offset += child.text_len();
+            element
+        })
+    }
+
+    pub fn children(&self) -> impl Iterator<Item = SyntaxNode> + '_ {
+        self.children_with_tokens().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    /// The node and every node below it, in source order.
+    pub fn descendants(&self) -> impl Iterator<Item = SyntaxNode> + use<> {
+        let mut stack = vec![self.clone()];
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            let children: Vec<SyntaxNode> = node.children().collect();
+            stack.extend(children.into_iter().rev());
+            Some(node)
+        })
+    }
+
+    /// Every token below the node, in source order.
+    pub fn tokens(&self) -> Vec<SyntaxToken> {
+        let mut tokens = Vec::new();
+        let mut stack = vec![SyntaxElement::Node(self.clone())];
+        while let Some(element) = stack.pop() {
+            match element {
+                SyntaxElement::Node(node) => {
+                    let children: Vec<SyntaxElement> = node.children_with_tokens().collect();
+                    stack.extend(children.into_iter().rev());
+                }
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+        tokens
+    }
+}
+
+impl PartialEq for SyntaxNode {
+    fn eq(&self, other: &SyntaxNode) -> bool {
+        Rc::ptr_eq(&self.0.green, &other.0.green) && self.0.offset == other.0.offset
+    }
+}
+
+impl Eq for SyntaxNode {}
+
+impl fmt::Debug for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}@{:?}", self.kind(), self.text_range())
+    }
+}
+
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text())
+    }
+}
+
+impl SyntaxToken {
+    pub fn kind(&self) -> TokenKind {
+        self.green.kind()
+    }
+
+    pub fn text(&self) -> &str {
+        self.green.text()
+    }
+
+    pub fn text_range(&self) -> Range<usize> {
+        self.offset..self.offset + self.green.text_len()
+    }
+
+    pub fn parent(&self) -> SyntaxNode {
+        self.parent.clone()
+    }
+
+    /// Whether the token is whitespace or a comment, which the parser
+    /// never sees.
+    pub fn is_trivia(&self) -> bool {
+        matches!(self.kind(), TokenKind::WHITESPACE | TokenKind::COMMENT)
+    }
+}
+
+impl fmt::Debug for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}@{:?} {:?}", self.kind(), self.text_range(), self.text())
+    }
+}
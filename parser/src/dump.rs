@@ -0,0 +1,784 @@
+//! Dumps of the AST for people and tools, in four formats:
+//!
+//! - `tree`, an indented outline with the span of every node;
+//! - `json`, following the schema below, for external tools;
+//! - `sexpr`, one s-expression per statement without spans, stable enough
+//!   for golden tests;
+//! - `dot`, a Graphviz graph, rendered with `dot -Tsvg`.
+//!
+//! Every format shows the same nodes. A node has a kind, a span, some
+//! attributes and some children, each under a field name. Lists such as
+//! the arguments of a call are one field holding several nodes, and an
+//! optional child that is missing, like the value of `let x;`, is left out
+//! of every format but JSON, where it is `null`.
+//!
+//! # JSON schema
+//!
+//! The document is the `Program` node. Every node is an object with
+//!
+//! - `"kind"`: the node kind, a string such as `"Binary"` or `"Let"`;
+//! - `"span"`: `{"start": {"line": L, "column": C}, "end": {...}}`, the end
+//!   being exclusive;
+//! - one member per attribute, holding a string, number or boolean, like
+//!   `"op": "+"` or `"mutable": false`;
+//! - one member per child field, holding a node, an array of nodes, or
+//!   `null` for a missing optional child.
+//!
+//! Member names are the field names of the AST types, so a `Binary` node
+//! has `"op"`, `"left"` and `"right"`. Literals are the kinds `Int`,
+//! `Float`, `Bool` and `Str` with a `"value"`, and an `Int` too large for
+//! a double keeps all its digits.
+
+use std::fmt::Write;
+
+use lexer::token::SourceSpan;
+
+use crate::nodes::ast::Ast;
+use crate::nodes::decls::{FieldDecl, ImportSource, Param, VariantDecl};
+use crate::nodes::exprs::{ArmBody, ExprId, ExprKind, LambdaBody, LiteralValue, MatchArm};
+use crate::nodes::patterns::{Pattern, PatternKind};
+use crate::nodes::stmts::{Block, Program, StmtId, StmtKind};
+use crate::nodes::type_exprs::{TypeExpr, TypeExprKind};
+
+/// Output format of a dump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Tree,
+    Json,
+    Sexpr,
+    Dot,
+}
+
+impl Format {
+    pub const ALL: &'static [Format] = &[Format::Tree, Format::Json, Format::Sexpr, Format::Dot];
+
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "tree" => Some(Format::Tree),
+            "json" => Some(Format::Json),
+            "sexpr" => Some(Format::Sexpr),
+            "dot" => Some(Format::Dot),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Tree => "tree",
+            Format::Json => "json",
+            Format::Sexpr => "sexpr",
+            Format::Dot => "dot",
+        }
+    }
+}
+
+/// Dumps a whole program in the given format.
+///
+/// # Example
+/// ```
+/// use parser::dump::{Format, dump};
+/// use parser::parser::engine::Parser;
+/// let tokens = lexer::tokenize("4 + 5;", "example").unwrap();
+/// let mut parser = Parser::new(&tokens, 0);
+/// let program = Parser::parse_program(&mut parser);
+/// assert_eq!(
+///     dump(&program, Format::Sexpr),
+///     "(Expr (Binary :op \"+\" (Int :value 4) (Int :value 5)))\n"
+/// );
+/// ```
+pub fn dump(program: &Program, format: Format) -> String {
+    let root = Builder { ast: &program.ast }.program(program);
+    let mut out = String::new();
+    match format {
+        Format::Tree => write_tree(&mut out, &root),
+        Format::Json => {
+            write_json(&mut out, &root);
+            out.push('\n');
+        }
+        Format::Sexpr => {
+            // the program itself is implied, one line per statement
+            for (_, field) in &root.fields {
+                if let Field::Nodes(stmts) = field {
+                    for stmt in stmts {
+                        write_sexpr(&mut out, stmt);
+                        out.push('\n');
+                    }
+                }
+            }
+        }
+        Format::Dot => write_dot(&mut out, &root),
+    }
+    out
+}
+
+/// A node of the dump, independent of the format.
+struct Node {
+    kind: &'static str,
+    span: SourceSpan,
+    fields: Vec<(&'static str, Field)>,
+}
+
+// the nodes of a long chain nest deep, so they are dropped in a loop
+impl Drop for Node {
+    fn drop(&mut self) {
+        let mut nodes = Vec::new();
+        self.take_children(&mut nodes);
+        while let Some(mut node) = nodes.pop() {
+            node.take_children(&mut nodes);
+        }
+    }
+}
+
+enum Field {
+    Value(Value),
+    Node(Node),
+    Opt(Option<Node>),
+    Nodes(Vec<Node>),
+}
+
+enum Value {
+    Str(String),
+    Int(i128),
+    Float(f64),
+    Bool(bool),
+}
+
+impl Node {
+    fn new(kind: &'static str, span: SourceSpan) -> Node {
+        Node {
+            kind,
+            span,
+            fields: Vec::new(),
+        }
+    }
+
+    fn value(mut self, name: &'static str, value: Value) -> Node {
+        self.fields.push((name, Field::Value(value)));
+        self
+    }
+
+    /// A boolean shown only when set, for flags most nodes leave off.
+    fn flag(self, name: &'static str, set: bool) -> Node {
+        if set {
+            self.value(name, Value::Bool(true))
+        } else {
+            self
+        }
+    }
+
+    fn str(self, name: &'static str, value: &str) -> Node {
+        self.value(name, Value::Str(value.to_string()))
+    }
+
+    fn child(mut self, name: &'static str, node: Node) -> Node {
+        self.fields.push((name, Field::Node(node)));
+        self
+    }
+
+    fn opt(mut self, name: &'static str, node: Option<Node>) -> Node {
+        self.fields.push((name, Field::Opt(node)));
+        self
+    }
+
+    fn list(mut self, name: &'static str, nodes: Vec<Node>) -> Node {
+        self.fields.push((name, Field::Nodes(nodes)));
+        self
+    }
+
+    /// Moves the children of the node into `nodes`.
+    fn take_children(&mut self, nodes: &mut Vec<Node>) {
+        for (_, field) in self.fields.drain(..) {
+            match field {
+                Field::Value(_) | Field::Opt(None) => {}
+                Field::Node(node) | Field::Opt(Some(node)) => nodes.push(node),
+                Field::Nodes(children) => nodes.extend(children),
+            }
+        }
+    }
+
+    /// Children in order, named by their field and, inside a list, their
+    /// position, as in `args[1]`.
+    fn children(&self) -> Vec<(String, &Node)> {
+        let mut children = Vec::new();
+        for (name, field) in &self.fields {
+            match field {
+                Field::Value(_) | Field::Opt(None) => {}
+                Field::Node(node) | Field::Opt(Some(node)) => {
+                    children.push((name.to_string(), node))
+                }
+                Field::Nodes(nodes) => {
+                    for (i, node) in nodes.iter().enumerate() {
+                        children.push((format!("{}[{}]", name, i), node));
+                    }
+                }
+            }
+        }
+        children
+    }
+
+    fn values(&self) -> impl Iterator<Item = (&'static str, &Value)> {
+        self.fields.iter().filter_map(|(name, field)| match field {
+            Field::Value(value) => Some((*name, value)),
+            _ => None,
+        })
+    }
+}
+
+/// Converts the AST into dump nodes.
+struct Builder<'t> {
+    ast: &'t Ast,
+}
+
+impl Builder<'_> {
+    fn program(&self, program: &Program) -> Node {
+        let stmts: Vec<Node> = program.stmts.iter().map(|s| self.stmt(*s)).collect();
+        let span = match (program.stmts.first(), program.stmts.last()) {
+            (Some(first), Some(last)) => self
+                .ast
+                .stmt_span(*first)
+                .combine(&self.ast.stmt_span(*last)),
+            _ => empty_span(),
+        };
+        Node::new("Program", span).list("stmts", stmts)
+    }
+
+    fn stmt(&self, id: StmtId) -> Node {
+        let span = self.ast.stmt_span(id);
+        match self.ast.stmt(id) {
+            StmtKind::Expr(expr) => Node::new("Expr", span).child("expr", self.expr(*expr)),
+            StmtKind::Let {
+                mutable,
+                name,
+                ty,
+                value,
+                ..
+            } => Node::new("Let", span)
+                .value("mutable", Value::Bool(*mutable))
+                .str("name", name)
+                .opt("ty", ty.as_ref().map(|ty| self.type_expr(ty)))
+                .opt("value", value.map(|value| self.expr(value))),
+            StmtKind::Assign { target, value } => Node::new("Assign", span)
+                .child("target", self.expr(*target))
+                .child("value", self.expr(*value)),
+            StmtKind::Block(block) => self.block(block),
+            StmtKind::If {
+                cond,
+                then_branch,
+                else_branch,
+            } => Node::new("If", span)
+                .child("cond", self.expr(*cond))
+                .child("then_branch", self.block(then_branch))
+                .opt("else_branch", else_branch.map(|stmt| self.stmt(stmt))),
+            StmtKind::While { cond, body } => Node::new("While", span)
+                .child("cond", self.expr(*cond))
+                .child("body", self.block(body)),
+            StmtKind::For {
+                var,
+                iterable,
+                body,
+                ..
+            } => Node::new("For", span)
+                .str("var", var)
+                .child("iterable", self.expr(*iterable))
+                .child("body", self.block(body)),
+            StmtKind::Return(value) => {
+                Node::new("Return", span).opt("value", value.map(|value| self.expr(value)))
+            }
+            StmtKind::Break => Node::new("Break", span),
+            StmtKind::Continue => Node::new("Continue", span),
+            StmtKind::Func(decl) => Node::new("Func", span)
+                .flag("public", decl.public)
+                .str("name", &decl.name)
+                .list(
+                    "params",
+                    decl.params.iter().map(|p| self.param(p)).collect(),
+                )
+                .opt(
+                    "return_type",
+                    decl.return_type.as_ref().map(|ty| self.type_expr(ty)),
+                )
+                .child("body", self.block(&decl.body)),
+            StmtKind::Struct(decl) => Node::new("Struct", span)
+                .flag("public", decl.public)
+                .str("name", &decl.name)
+                .list(
+                    "fields",
+                    decl.fields.iter().map(|f| self.field_decl(f)).collect(),
+                ),
+            StmtKind::Enum(decl) => Node::new("Enum", span)
+                .flag("public", decl.public)
+                .str("name", &decl.name)
+                .list(
+                    "variants",
+                    decl.variants.iter().map(|v| self.variant_decl(v)).collect(),
+                ),
+            StmtKind::Import(decl) => {
+                let node = Node::new("Import", span);
+                let node = match &decl.source {
+                    ImportSource::File(path) => node.str("path", path),
+                    ImportSource::Module(_) => node.str("module", &decl.source.to_string()),
+                };
+                match &decl.items {
+                    Some(items) => node.list(
+                        "items",
+                        items
+                            .iter()
+                            .map(|item| Node::new("Item", item.span).str("name", &item.name))
+                            .collect(),
+                    ),
+                    None => node,
+                }
+            }
+            StmtKind::Error => Node::new("Error", span),
+        }
+    }
+
+    fn block(&self, block: &Block) -> Node {
+        let stmts = block.stmts.iter().map(|s| self.stmt(*s)).collect();
+        Node::new("Block", block.span)
+            .flag("tail", block.tail)
+            .list("stmts", stmts)
+    }
+
+    fn exprs(&self, exprs: &[ExprId]) -> Vec<Node> {
+        exprs.iter().map(|e| self.expr(*e)).collect()
+    }
+
+    fn expr(&self, id: ExprId) -> Node {
+        let span = self.ast.expr_span(id);
+        match self.ast.expr(id) {
+            ExprKind::Literal(value) => literal(value, span),
+            ExprKind::Ident(name) => Node::new("Ident", span).str("name", name),
+            ExprKind::Binary { .. }
+            | ExprKind::Call { .. }
+            | ExprKind::Index { .. }
+            | ExprKind::Member { .. }
+            | ExprKind::Cast { .. } => self.chain(id),
+            ExprKind::Unary { op, expr, .. } => Node::new("Unary", span)
+                .str("op", op.symbol())
+                .child("expr", self.expr(*expr)),
+            ExprKind::Grouping(expr) => Node::new("Grouping", span).child("expr", self.expr(*expr)),
+            ExprKind::Array(elements) => {
+                Node::new("Array", span).list("elements", self.exprs(elements))
+            }
+            ExprKind::Map(entries) => {
+                let entries = entries
+                    .iter()
+                    .map(|(key, value)| {
+                        let span = self
+                            .ast
+                            .expr_span(*key)
+                            .combine(&self.ast.expr_span(*value));
+                        Node::new("Entry", span)
+                            .child("key", self.expr(*key))
+                            .child("value", self.expr(*value))
+                    })
+                    .collect();
+                Node::new("Map", span).list("entries", entries)
+            }
+            ExprKind::Tuple(elements) => {
+                Node::new("Tuple", span).list("elements", self.exprs(elements))
+            }
+            ExprKind::Struct { name, fields, .. } => {
+                let fields = fields
+                    .iter()
+                    .map(|field| {
+                        let span = field.name_span.combine(&self.ast.expr_span(field.value));
+                        Node::new("FieldInit", span)
+                            .str("name", &field.name)
+                            .value("shorthand", Value::Bool(field.shorthand))
+                            .child("value", self.expr(field.value))
+                    })
+                    .collect();
+                Node::new("Struct", span)
+                    .str("name", name)
+                    .list("fields", fields)
+            }
+            ExprKind::Match { scrutinee, arms } => Node::new("Match", span)
+                .child("scrutinee", self.expr(*scrutinee))
+                .list("arms", arms.iter().map(|arm| self.arm(arm)).collect()),
+            ExprKind::If {
+                cond,
+                then_branch,
+                else_branch,
+            } => Node::new("If", span)
+                .child("cond", self.expr(*cond))
+                .child("then_branch", self.block(then_branch))
+                .opt("else_branch", else_branch.map(|branch| self.expr(branch))),
+            ExprKind::Block(block) => self.block(block),
+            ExprKind::Lambda(lambda) => {
+                let body = match &lambda.body {
+                    LambdaBody::Expr(expr) => self.expr(*expr),
+                    LambdaBody::Block(block) => self.block(block),
+                };
+                let params = lambda.params.iter().map(|p| self.param(p)).collect();
+                Node::new("Lambda", span)
+                    .list("params", params)
+                    .opt(
+                        "return_type",
+                        lambda.return_type.as_ref().map(|ty| self.type_expr(ty)),
+                    )
+                    .child("body", body)
+            }
+            ExprKind::Error => Node::new("Error", span),
+        }
+    }
+
+    /// Builds a chain of operations in a loop, see `Ast::chain`, from the
+    /// operand it starts with out.
+    fn chain(&self, id: ExprId) -> Node {
+        let chain = self.ast.chain(id);
+        let (first, links) = chain.split_last().expect("a chain has an operand");
+        let mut node = self.expr(*first);
+        for link in links.iter().rev() {
+            let span = self.ast.expr_span(*link);
+            node = match self.ast.expr(*link) {
+                ExprKind::Binary { op, right, .. } => Node::new("Binary", span)
+                    .str("op", op.symbol())
+                    .child("left", node)
+                    .child("right", self.expr(*right)),
+                ExprKind::Call { args, .. } => Node::new("Call", span)
+                    .child("callee", node)
+                    .list("args", self.exprs(args)),
+                ExprKind::Index { index, .. } => Node::new("Index", span)
+                    .child("object", node)
+                    .child("index", self.expr(*index)),
+                ExprKind::Member { field, .. } => Node::new("Member", span)
+                    .str("field", field)
+                    .child("object", node),
+                ExprKind::Cast { ty, .. } => Node::new("Cast", span)
+                    .child("expr", node)
+                    .child("ty", self.type_expr(ty)),
+                _ => unreachable!("only the last of a chain is not a link"),
+            };
+        }
+        node
+    }
+
+    fn arm(&self, arm: &MatchArm) -> Node {
+        let body = match &arm.body {
+            ArmBody::Expr(expr) => self.expr(*expr),
+            ArmBody::Block(block) => self.block(block),
+        };
+        Node::new("Arm", arm.span)
+            .child("pattern", self.pattern(&arm.pattern))
+            .opt("guard", arm.guard.map(|guard| self.expr(guard)))
+            .child("body", body)
+    }
+
+    fn param(&self, param: &Param) -> Node {
+        Node::new("Param", param.span).str("name", &param.name).opt(
+            "type_annotation",
+            param.type_annotation.as_ref().map(|ty| self.type_expr(ty)),
+        )
+    }
+
+    fn field_decl(&self, field: &FieldDecl) -> Node {
+        Node::new("FieldDecl", field.span)
+            .str("name", &field.name)
+            .child("ty", self.type_expr(&field.ty))
+    }
+
+    fn variant_decl(&self, variant: &VariantDecl) -> Node {
+        Node::new("VariantDecl", variant.span)
+            .str("name", &variant.name)
+            .list("fields", self.type_exprs(&variant.fields))
+    }
+
+    fn pattern(&self, pattern: &Pattern) -> Node {
+        let span = pattern.span;
+        match &pattern.kind {
+            PatternKind::Wildcard => Node::new("WildcardPat", span),
+            PatternKind::Literal(value) => {
+                Node::new("LiteralPat", span).child("value", literal(value, span))
+            }
+            PatternKind::Binding(name) => Node::new("BindingPat", span).str("name", name),
+            PatternKind::Tuple(items) => Node::new("TuplePat", span)
+                .list("items", items.iter().map(|p| self.pattern(p)).collect()),
+            PatternKind::Variant { path, fields } => {
+                let node = Node::new("VariantPat", span).str("path", &path.join("."));
+                match fields {
+                    Some(fields) => {
+                        node.list("fields", fields.iter().map(|p| self.pattern(p)).collect())
+                    }
+                    None => node,
+                }
+            }
+        }
+    }
+
+    fn type_exprs(&self, types: &[TypeExpr]) -> Vec<Node> {
+        types.iter().map(|ty| self.type_expr(ty)).collect()
+    }
+
+    fn type_expr(&self, ty: &TypeExpr) -> Node {
+        let span = ty.span;
+        match &ty.kind {
+            TypeExprKind::Primitive(p) => Node::new("PrimitiveType", span).str("name", p.name()),
+            TypeExprKind::Named(name) => Node::new("NamedType", span).str("name", name),
+            TypeExprKind::Array(elem) => {
+                Node::new("ArrayType", span).child("elem", self.type_expr(elem))
+            }
+            TypeExprKind::Map(key, value) => Node::new("MapType", span)
+                .child("key", self.type_expr(key))
+                .child("value", self.type_expr(value)),
+            TypeExprKind::Tuple(items) => {
+                Node::new("TupleType", span).list("items", self.type_exprs(items))
+            }
+            TypeExprKind::Function { params, ret } => Node::new("FunctionType", span)
+                .list("params", self.type_exprs(params))
+                .opt("ret", ret.as_ref().map(|ret| self.type_expr(ret))),
+            TypeExprKind::Optional(inner) => {
+                Node::new("OptionalType", span).child("inner", self.type_expr(inner))
+            }
+        }
+    }
+}
+
+fn literal(value: &LiteralValue, span: SourceSpan) -> Node {
+    match value {
+        LiteralValue::Int(v) => Node::new("Int", span).value("value", Value::Int(*v)),
+        LiteralValue::Float(v) => Node::new("Float", span).value("value", Value::Float(*v)),
+        LiteralValue::Bool(v) => Node::new("Bool", span).value("value", Value::Bool(*v)),
+        LiteralValue::Str(v) => Node::new("Str", span).str("value", v),
+    }
+}
+
+// the span of an empty program
+fn empty_span() -> SourceSpan {
+    let start = lexer::token::Position::new(1, 1);
+    SourceSpan { start, end: start }
+}
+
+fn write_span(out: &mut String, span: SourceSpan) {
+    let _ = write!(
+        out,
+        "{}:{}-{}:{}",
+        span.start.line, span.start.column, span.end.line, span.end.column
+    );
+}
+
+/// Writes a value as in JSON, which s-expressions and labels share.
+fn write_value(out: &mut String, value: &Value) {
+    match value {
+        Value::Str(s) => write_string(out, s),
+        Value::Int(v) => {
+            let _ = write!(out, "{}", v);
+        }
+        Value::Float(v) if v.is_finite() => {
+            let _ = write!(out, "{:?}", v);
+        }
+        // JSON has no infinity, nor does the source
+        Value::Float(_) => out.push_str("null"),
+        Value::Bool(v) => {
+            let _ = write!(out, "{}", v);
+        }
+    }
+}
+
+/// Writes a quoted string with JSON escapes.
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// `Kind 1:1-1:5 name="x"` with the parts split by `sep`.
+fn write_label(out: &mut String, node: &Node, sep: char) {
+    out.push_str(node.kind);
+    out.push(sep);
+    write_span(out, node.span);
+    for (name, value) in node.values() {
+        out.push(sep);
+        let _ = write!(out, "{}=", name);
+        write_value(out, value);
+    }
+}
+
+/// What is left to write of a dump: a node, with what its format needs to
+/// know about where it is, or the text that follows a child. A long chain
+/// nests its nodes deep, so every format writes them in a loop.
+enum Step<'n, T> {
+    Node(&'n Node, T),
+    Text(String),
+}
+
+/// Writes `root` and its subtree. `write` writes a node up to its first
+/// child and returns the rest of it, children and text in order.
+fn write_nodes<'n, T>(
+    out: &mut String,
+    root: &'n Node,
+    context: T,
+    mut write: impl FnMut(&mut String, &'n Node, T) -> Vec<Step<'n, T>>,
+) {
+    let mut steps = vec![Step::Node(root, context)];
+    while let Some(step) = steps.pop() {
+        match step {
+            Step::Node(node, context) => {
+                let rest = write(out, node, context);
+                steps.extend(rest.into_iter().rev());
+            }
+            Step::Text(text) => out.push_str(&text),
+        }
+    }
+}
+
+/// Each node is written after its prefix and field name.
+fn write_tree(out: &mut String, root: &Node) {
+    write_nodes(out, root, (String::new(), String::new()), |out, node, (prefix, field)| {
+        out.push_str(&field);
+        write_label(out, node, ' ');
+        out.push('\n');
+        let children = node.children();
+        let count = children.len();
+        let mut rest = Vec::new();
+        for (i, (name, child)) in children.into_iter().enumerate() {
+            let (branch, indent) = if i + 1 == count {
+                ("└─ ", "   ")
+            } else {
+                ("├─ ", "│  ")
+            };
+            let field = format!("{}{}{}: ", prefix, branch, name);
+            rest.push(Step::Node(child, (format!("{}{}", prefix, indent), field)));
+        }
+        rest
+    });
+}
+
+fn json_indent(out: &mut String, depth: usize) {
+    out.push('\n');
+    for _ in 0..depth {
+        out.push_str("  ");
+    }
+}
+
+/// Each node is written at its depth of indentation.
+fn write_json(out: &mut String, root: &Node) {
+    write_nodes(out, root, 0, |out, node, depth| {
+        out.push('{');
+        json_indent(out, depth + 1);
+        let _ = write!(out, "\"kind\": \"{}\",", node.kind);
+        json_indent(out, depth + 1);
+        let (start, end) = (node.span.start, node.span.end);
+        let _ = write!(
+            out,
+            "\"span\": {{\"start\": {{\"line\": {}, \"column\": {}}}, \"end\": {{\"line\": {}, \"column\": {}}}}}",
+            start.line, start.column, end.line, end.column
+        );
+        // the text after the last child written so far
+        let mut text = String::new();
+        let mut rest = Vec::new();
+        for (name, field) in &node.fields {
+            text.push(',');
+            json_indent(&mut text, depth + 1);
+            let _ = write!(text, "\"{}\": ", name);
+            match field {
+                Field::Value(value) => write_value(&mut text, value),
+                Field::Node(child) | Field::Opt(Some(child)) => {
+                    rest.push(Step::Text(std::mem::take(&mut text)));
+                    rest.push(Step::Node(child, depth + 1));
+                }
+                Field::Opt(None) => text.push_str("null"),
+                Field::Nodes(children) if children.is_empty() => text.push_str("[]"),
+                Field::Nodes(children) => {
+                    text.push('[');
+                    for (i, child) in children.iter().enumerate() {
+                        if i > 0 {
+                            text.push(',');
+                        }
+                        json_indent(&mut text, depth + 2);
+                        rest.push(Step::Text(std::mem::take(&mut text)));
+                        rest.push(Step::Node(child, depth + 2));
+                    }
+                    json_indent(&mut text, depth + 1);
+                    text.push(']');
+                }
+            }
+        }
+        json_indent(&mut text, depth);
+        text.push('}');
+        rest.push(Step::Text(text));
+        rest
+    });
+}
+
+/// `(Kind :attr value child ...)`. Children that always exist and lists
+/// are written in place, an optional child after its field name, so
+/// `let x: int;` and `let x = int;` still read differently.
+fn write_sexpr(out: &mut String, root: &Node) {
+    write_nodes(out, root, (), |out, node, ()| {
+        out.push('(');
+        out.push_str(node.kind);
+        let mut text = String::new();
+        let mut rest = Vec::new();
+        for (name, field) in &node.fields {
+            match field {
+                Field::Value(value) => {
+                    let _ = write!(text, " :{} ", name);
+                    write_value(&mut text, value);
+                }
+                Field::Node(child) => {
+                    text.push(' ');
+                    rest.push(Step::Text(std::mem::take(&mut text)));
+                    rest.push(Step::Node(child, ()));
+                }
+                Field::Opt(Some(child)) => {
+                    let _ = write!(text, " :{} ", name);
+                    rest.push(Step::Text(std::mem::take(&mut text)));
+                    rest.push(Step::Node(child, ()));
+                }
+                Field::Opt(None) => {}
+                Field::Nodes(children) => {
+                    for child in children {
+                        text.push(' ');
+                        rest.push(Step::Text(std::mem::take(&mut text)));
+                        rest.push(Step::Node(child, ()));
+                    }
+                }
+            }
+        }
+        text.push(')');
+        rest.push(Step::Text(text));
+        rest
+    });
+}
+
+/// Nodes are numbered in the order they are written, and the edge from
+/// its parent follows the subtree of each node.
+fn write_dot(out: &mut String, root: &Node) {
+    out.push_str("digraph ast {\n");
+    out.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+    let mut next = 0;
+    write_nodes(out, root, None, |out, node, parent: Option<(usize, String)>| {
+        let id = next;
+        next += 1;
+        let mut label = String::new();
+        write_label(&mut label, node, '\n');
+        let _ = write!(out, "    n{} [label=", id);
+        write_string(out, &label);
+        out.push_str("];\n");
+        let mut rest: Vec<_> = node
+            .children()
+            .into_iter()
+            .map(|(name, child)| Step::Node(child, Some((id, name))))
+            .collect();
+        if let Some((parent, name)) = parent {
+            let edge = format!("    n{} -> n{} [label=\"{}\"];\n", parent, id, name);
+            rest.push(Step::Text(edge));
+        }
+        rest
+    });
+    out.push_str("}\n");
+}
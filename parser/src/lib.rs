@@ -1,4 +1,11 @@
-mod nodes;
+pub mod nodes;
+pub mod cst;
+pub mod dump;
+pub mod lints;
+pub mod modules;
 pub mod parser;
+pub mod resolve;
+pub mod typeck;
+pub mod unparse;
 
 
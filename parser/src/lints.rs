@@ -0,0 +1,337 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use lexer::errors::{Severity, write_snippet};
+use lexer::messages::{self, Message, tr};
+use lexer::token::{SourceSpan, Token};
+use lexer::token_type::TokenKind;
+
+/// How a lint is reported when it fires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl Level {
+    pub fn from_name(name: &str) -> Option<Level> {
+        match name {
+            "allow" => Some(Level::Allow),
+            "warn" => Some(Level::Warn),
+            "deny" => Some(Level::Deny),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lint {
+    // `;;`, an empty statement after another one
+    RedundantSemicolons,
+    // `(x)`, parentheses around a single operand
+    RedundantParens,
+    // `1 + 2;`, an expression statement whose value is thrown away
+    Unused,
+}
+
+impl Lint {
+    pub const ALL: &'static [Lint] = &[Lint::RedundantSemicolons, Lint::RedundantParens, Lint::Unused];
+
+    /// Name used by `-W`/`-D`/`-A` and by `// allow(...)` comments.
+    pub fn name(self) -> &'static str {
+        match self {
+            Lint::RedundantSemicolons => "redundant_semicolons",
+            Lint::RedundantParens => "redundant_parens",
+            Lint::Unused => "unused",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Lint> {
+        Lint::ALL.iter().copied().find(|lint| lint.name() == name)
+    }
+
+    pub fn default_level(self) -> Level {
+        match self {
+            Lint::RedundantSemicolons => Level::Warn,
+            Lint::RedundantParens => Level::Warn,
+            Lint::Unused => Level::Warn,
+        }
+    }
+
+    fn message(self) -> Message {
+        match self {
+            Lint::RedundantSemicolons => Message::LintRedundantSemicolons,
+            Lint::RedundantParens => Message::LintRedundantParens,
+            Lint::Unused => Message::LintUnused,
+        }
+    }
+
+    fn label(self) -> Message {
+        match self {
+            Lint::RedundantSemicolons => Message::LintRedundantSemicolonsLabel,
+            Lint::RedundantParens => Message::LintRedundantParensLabel,
+            Lint::Unused => Message::LintUnusedLabel,
+        }
+    }
+}
+
+/// Name that stands for every lint, as in `-D warnings` or `// allow(warnings)`.
+pub const ALL_LINTS: &str = "warnings";
+
+/// Lint levels chosen by the user, on top of each lint's default level.
+#[derive(Debug, Clone, Default)]
+pub struct LintConfig {
+    levels: HashMap<Lint, Level>,
+    deny_warnings: bool,
+}
+
+impl LintConfig {
+    pub fn new() -> LintConfig {
+        LintConfig::default()
+    }
+
+    /// Sets the level of the lint called `name`, or of every lint for
+    /// `warnings`. Returns `false` when no lint has that name.
+    pub fn set(&mut self, name: &str, level: Level) -> bool {
+        if name == ALL_LINTS {
+            for lint in Lint::ALL {
+                self.levels.insert(*lint, level);
+            }
+            return true;
+        }
+        match Lint::from_name(name) {
+            Some(lint) => {
+                self.levels.insert(lint, level);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Turns every warning into an error, for CI runs.
+    pub fn deny_warnings(&mut self) {
+        self.deny_warnings = true;
+    }
+
+    pub fn level(&self, lint: Lint) -> Level {
+        let level = self
+            .levels
+            .get(&lint)
+            .copied()
+            .unwrap_or(lint.default_level());
+        self.escalate(level)
+    }
+
+    fn escalate(&self, level: Level) -> Level {
+        if self.deny_warnings && level == Level::Warn {
+            Level::Deny
+        } else {
+            level
+        }
+    }
+}
+
+/// A `// allow(name, ...)`, `// warn(...)` or `// deny(...)` comment. When
+/// it trails code it applies to its own line, otherwise to the next line
+/// with code on it.
+#[derive(Debug, Clone)]
+struct Directive {
+    line: usize,
+    level: Level,
+    names: Vec<String>,
+    span: SourceSpan,
+}
+
+impl Directive {
+    fn parse(comment: &Token, tokens: &[Token]) -> Option<Directive> {
+        let text = comment.value.trim_start_matches('/').trim();
+        let (level, rest) = text.split_once('(')?;
+        let level = Level::from_name(level.trim())?;
+        let names = rest.strip_suffix(')')?;
+        let start = comment.span.start;
+        let after = tokens.partition_point(|t| t.span.start < start);
+        let line = match (after.checked_sub(1).map(|i| &tokens[i]), tokens.get(after)) {
+            (Some(before), _) if before.span.end.line == start.line => start.line,
+            (_, Some(next)) => next.span.start.line,
+            _ => start.line + 1,
+        };
+        Some(Directive {
+            line,
+            level,
+            names: names.split(',').map(|n| n.trim().to_string()).collect(),
+            span: comment.span,
+        })
+    }
+
+    fn applies_to(&self, lint: Lint, line: usize) -> bool {
+        line == self.line
+            && self
+                .names
+                .iter()
+                .any(|name| name == lint.name() || name == ALL_LINTS)
+    }
+}
+
+/// A lint that fired, already resolved to the level it is reported at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub lint: Lint,
+    pub level: Level,
+    pub span: SourceSpan,
+}
+
+impl Warning {
+    pub fn is_denied(&self) -> bool {
+        self.level == Level::Deny
+    }
+
+    /// Pairs the warning with its source so it can be displayed with a snippet.
+    pub fn report<'r>(&'r self, source: &'r str, filename: &'r str) -> Report<'r> {
+        Report {
+            warning: self,
+            source,
+            filename,
+        }
+    }
+}
+
+/// A `Warning` rendered with the source snippet and caret underline.
+pub struct Report<'r> {
+    warning: &'r Warning,
+    source: &'r str,
+    filename: &'r str,
+}
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (severity, prefix) = if self.warning.is_denied() {
+            (Severity::Error, tr(Message::ErrorPrefix))
+        } else {
+            (Severity::Warning, tr(Message::WarningPrefix))
+        };
+        let title = format!(
+            "{}: {} [{}]",
+            prefix,
+            tr(self.warning.lint.message()),
+            self.warning.lint.name()
+        );
+        write_snippet(
+            f,
+            severity,
+            &title,
+            tr(self.warning.lint.label()),
+            self.filename,
+            self.source,
+            self.warning.span,
+        )
+    }
+}
+
+/// A name in a directive comment that is neither a lint nor `warnings`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownLint {
+    pub name: String,
+    pub span: SourceSpan,
+}
+
+impl UnknownLint {
+    /// Pairs the name with its source so it can be displayed with a snippet.
+    pub fn report<'r>(&'r self, source: &'r str, filename: &'r str) -> UnknownLintReport<'r> {
+        UnknownLintReport {
+            unknown: self,
+            source,
+            filename,
+        }
+    }
+}
+
+/// An `UnknownLint` rendered as the warning `-A <lint>` gives for an unknown
+/// name, with the directive's source snippet.
+pub struct UnknownLintReport<'r> {
+    unknown: &'r UnknownLint,
+    source: &'r str,
+    filename: &'r str,
+}
+
+impl fmt::Display for UnknownLintReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let title = format!(
+            "{}: {}",
+            tr(Message::WarningPrefix),
+            messages::format(Message::UnknownLint, &[&self.unknown.name])
+        );
+        write_snippet(
+            f,
+            Severity::Warning,
+            &title,
+            tr(Message::UnknownLintLabel),
+            self.filename,
+            self.source,
+            self.unknown.span,
+        )
+    }
+}
+
+/// Collects the lints that fire in one file and resolves their levels from
+/// the configuration and the file's suppression comments.
+pub struct Linter<'c> {
+    config: &'c LintConfig,
+    directives: Vec<Directive>,
+    warnings: Vec<Warning>,
+}
+
+impl<'c> Linter<'c> {
+    /// Reads the directives out of `comments`, placing them by the `tokens`
+    /// of the same file.
+    pub fn new(config: &'c LintConfig, tokens: &[Token], comments: &[Token]) -> Linter<'c> {
+        Linter {
+            config,
+            directives: comments
+                .iter()
+                .filter_map(|comment| Directive::parse(comment, tokens))
+                .collect(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// The names in directives that no lint goes by, in source order.
+    pub fn unknown_lints(&self) -> Vec<UnknownLint> {
+        self.directives
+            .iter()
+            .flat_map(|d| d.names.iter().map(move |name| (d, name)))
+            .filter(|(_, name)| name.as_str() != ALL_LINTS && Lint::from_name(name).is_none())
+            .map(|(d, name)| UnknownLint {
+                name: name.clone(),
+                span: d.span,
+            })
+            .collect()
+    }
+
+    /// Records that `lint` fired at `span`, unless it resolves to `allow`.
+    pub fn emit(&mut self, lint: Lint, span: SourceSpan) {
+        let line = span.start.line;
+        let level = self
+            .directives
+            .iter()
+            .rev()
+            .find(|d| d.applies_to(lint, line))
+            .map_or(self.config.level(lint), |d| self.config.escalate(d.level));
+        if level != Level::Allow {
+            self.warnings.push(Warning { lint, level, span });
+        }
+    }
+
+    pub fn finish(self) -> Vec<Warning> {
+        self.warnings
+    }
+}
+
+/// Runs the lints that only need the token stream.
+pub fn check_tokens(linter: &mut Linter, tokens: &[Token]) {
+    for pair in tokens.windows(2) {
+        if pair[0].kind == TokenKind::SEMICOLON && pair[1].kind == TokenKind::SEMICOLON {
+            linter.emit(Lint::RedundantSemicolons, pair[1].span);
+        }
+    }
+}
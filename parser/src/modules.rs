@@ -0,0 +1,453 @@
+//! Programs spread over several files. A file names the modules it uses
+//! with `import`, and the loader finds, parses and checks each of them once,
+//! however many files import it.
+//!
+//! An import is looked up next to the importing file first and then in
+//! every search path, in order. `import "shapes/circle"` names a path and
+//! `import geometry.shapes` the path `geometry/shapes`, both taking the
+//! extension of the importing file when they have none. Only the
+//! functions, structs and enums a module declares `pub` can be imported.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use lexer::errors::{Severity, write_snippet};
+use lexer::messages::{Message, format, tr};
+use lexer::token::SourceSpan;
+
+use crate::nodes::decls::{ImportDecl, ImportSource};
+use crate::nodes::stmts::{Program, StmtKind};
+use crate::parser::engine::Parser;
+
+/// Index of a module in its `Loader`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ModuleId(usize);
+
+impl ModuleId {
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// A function, struct or enum declared at the top level of a module.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item {
+    pub name: String,
+    pub public: bool,
+    pub span: SourceSpan,
+}
+
+/// An import of a module once resolved: the module it found and the names
+/// it brings in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Import {
+    pub module: ModuleId,
+    pub items: Vec<String>,
+    pub span: SourceSpan,
+}
+
+/// A parsed source file.
+#[derive(Debug)]
+pub struct Module {
+    pub path: PathBuf,
+    pub source: String,
+    pub program: Program,
+    pub items: Vec<Item>,
+    // the imports that found their module, in source order
+    pub imports: Vec<Import>,
+}
+
+impl Module {
+    pub fn item(&self, name: &str) -> Option<&Item> {
+        self.items.iter().find(|item| item.name == name)
+    }
+}
+
+/// Where an import appears, for errors pointing at it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImportSite {
+    pub module: ModuleId,
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModuleError {
+    // the file could not be read, `at` is `None` for the file loading
+    // started from
+    Unreadable {
+        path: PathBuf,
+        message: String,
+        at: Option<ImportSite>,
+    },
+
+    // lexer or parser errors of a module, already rendered against its
+    // source
+    Syntax {
+        path: PathBuf,
+        report: String,
+    },
+
+    // no file matches the import, `tried` lists every path looked at
+    NotFound {
+        source: String,
+        tried: Vec<PathBuf>,
+        at: ImportSite,
+    },
+
+    // the import leads back to a module still loading its own imports,
+    // `chain` goes from that module to itself through this import
+    Cycle {
+        chain: Vec<PathBuf>,
+        at: ImportSite,
+    },
+
+    // an imported name is not declared in the module
+    UnknownItem {
+        name: String,
+        module: PathBuf,
+        at: ImportSite,
+    },
+
+    // an imported name is declared without `pub`
+    PrivateItem {
+        name: String,
+        module: PathBuf,
+        at: ImportSite,
+    },
+}
+
+impl ModuleError {
+    /// The import the error points at, `None` for errors of a whole file.
+    pub fn site(&self) -> Option<ImportSite> {
+        match self {
+            ModuleError::Unreadable { at, .. } => *at,
+            ModuleError::Syntax { .. } => None,
+            ModuleError::NotFound { at, .. }
+            | ModuleError::Cycle { at, .. }
+            | ModuleError::UnknownItem { at, .. }
+            | ModuleError::PrivateItem { at, .. } => Some(*at),
+        }
+    }
+
+    fn title(&self) -> String {
+        match self {
+            ModuleError::Unreadable { path, message, .. } => {
+                format(Message::ModuleReadFailed, &[&path.display(), message])
+            }
+            ModuleError::Syntax { report, .. } => report.clone(),
+            ModuleError::NotFound { source, .. } => format(Message::ModuleNotFound, &[source]),
+            ModuleError::Cycle { chain, .. } => {
+                let chain: Vec<String> = chain.iter().map(|p| p.display().to_string()).collect();
+                format(Message::ImportCycle, &[&chain.join(" -> ")])
+            }
+            ModuleError::UnknownItem { name, module, .. } => {
+                format(Message::UnknownImport, &[name, &module.display()])
+            }
+            ModuleError::PrivateItem { name, module, .. } => {
+                format(Message::PrivateImport, &[name, &module.display()])
+            }
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            ModuleError::Unreadable { .. } | ModuleError::Syntax { .. } => String::new(),
+            ModuleError::NotFound { tried, .. } => {
+                let tried: Vec<String> = tried.iter().map(|p| p.display().to_string()).collect();
+                format(Message::ModuleNotFoundLabel, &[&tried.join(", ")])
+            }
+            ModuleError::Cycle { .. } => tr(Message::ImportCycleLabel).to_string(),
+            ModuleError::UnknownItem { .. } => tr(Message::UnknownImportLabel).to_string(),
+            ModuleError::PrivateItem { .. } => tr(Message::PrivateImportLabel).to_string(),
+        }
+    }
+
+    /// Pairs the error with the loader holding the source of the import it
+    /// points at, so it can be displayed with a snippet.
+    pub fn report<'r>(&'r self, loader: &'r Loader) -> Report<'r> {
+        Report {
+            error: self,
+            loader,
+        }
+    }
+}
+
+impl fmt::Display for ModuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.title())
+    }
+}
+
+/// A `ModuleError` rendered with the source snippet of its import.
+pub struct Report<'r> {
+    error: &'r ModuleError,
+    loader: &'r Loader,
+}
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let ModuleError::Syntax { report, .. } = self.error {
+            return write!(f, "{}", report);
+        }
+        let title = format!("{}: {}", tr(Message::ErrorPrefix), self.error.title());
+        let Some(site) = self.error.site() else {
+            return writeln!(f, "{}", title);
+        };
+        let module = self.loader.module(site.module);
+        write_snippet(
+            f,
+            Severity::Error,
+            &title,
+            &self.error.label(),
+            &module.path.display().to_string(),
+            &module.source,
+            site.span,
+        )
+    }
+}
+
+/// Loads a file and every module it imports, directly or not.
+///
+/// # Example
+/// ```
+/// use parser::modules::Loader;
+/// let mut loader = Loader::new();
+/// let root = loader.load_source("main.txt", "pub func main() {}".to_string());
+/// assert!(loader.errors.is_empty());
+/// assert!(loader.module(root).item("main").unwrap().public);
+/// ```
+#[derive(Debug, Default)]
+pub struct Loader {
+    /// Directories imports are looked up in after the importing file's own.
+    pub search_paths: Vec<PathBuf>,
+    /// Errors of every module loaded so far, in the order they were found.
+    pub errors: Vec<ModuleError>,
+    modules: Vec<Module>,
+    // every module by its canonical path, so each file is parsed once
+    by_path: HashMap<PathBuf, ModuleId>,
+    // the modules whose imports are being loaded, innermost last
+    loading: Vec<ModuleId>,
+}
+
+impl Loader {
+    pub fn new() -> Loader {
+        Loader::default()
+    }
+
+    pub fn module(&self, id: ModuleId) -> &Module {
+        &self.modules[id.0]
+    }
+
+    /// Every module loaded, in the order loading them started.
+    pub fn modules(&self) -> impl Iterator<Item = (ModuleId, &Module)> {
+        self.modules.iter().enumerate().map(|(i, m)| (ModuleId(i), m))
+    }
+
+    /// Reads and loads the file at `path` with its imports. `None` when the
+    /// file cannot be read, which is recorded in `errors`.
+    pub fn load(&mut self, path: impl AsRef<Path>) -> Option<ModuleId> {
+        self.load_file(path.as_ref(), None)
+    }
+
+    /// Loads the file at `path` from its already read `source`.
+    pub fn load_source(&mut self, path: impl AsRef<Path>, source: String) -> ModuleId {
+        let path = path.as_ref();
+        let (program, errors) = parse(path, &source);
+        self.add(path, source, program, errors)
+    }
+
+    /// Loads the imports of a file that is already parsed, without parsing
+    /// it again.
+    pub fn load_program(
+        &mut self,
+        path: impl AsRef<Path>,
+        source: String,
+        program: Program,
+    ) -> ModuleId {
+        self.add(path.as_ref(), source, program, Vec::new())
+    }
+
+    fn load_file(&mut self, path: &Path, at: Option<ImportSite>) -> Option<ModuleId> {
+        if let Some(id) = fs::canonicalize(path).ok().and_then(|key| self.by_path.get(&key)) {
+            return Some(*id);
+        }
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(e) => {
+                self.errors.push(ModuleError::Unreadable {
+                    path: path.to_path_buf(),
+                    message: e.to_string(),
+                    at,
+                });
+                return None;
+            }
+        };
+        Some(self.load_source(path, source))
+    }
+
+    /// Registers a parsed module and loads what it imports.
+    fn add(
+        &mut self,
+        path: &Path,
+        source: String,
+        program: Program,
+        errors: Vec<String>,
+    ) -> ModuleId {
+        let id = ModuleId(self.modules.len());
+        let key = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        self.by_path.insert(key, id);
+        self.errors.extend(errors.into_iter().map(|report| ModuleError::Syntax {
+            path: path.to_path_buf(),
+            report,
+        }));
+        let decls: Vec<ImportDecl> = program
+            .stmts
+            .iter()
+            .filter_map(|stmt| match program.ast.stmt(*stmt) {
+                StmtKind::Import(decl) => Some((**decl).clone()),
+                _ => None,
+            })
+            .collect();
+        self.modules.push(Module {
+            path: path.to_path_buf(),
+            items: items(&program),
+            source,
+            program,
+            imports: Vec::new(),
+        });
+
+        self.loading.push(id);
+        for decl in decls {
+            if let Some(import) = self.import(id, &decl) {
+                self.modules[id.0].imports.push(import);
+            }
+        }
+        self.loading.pop();
+        id
+    }
+
+    /// Finds and loads the module `decl` names, then checks the items it
+    /// brings in.
+    fn import(&mut self, importer: ModuleId, decl: &ImportDecl) -> Option<Import> {
+        let at = ImportSite {
+            module: importer,
+            span: decl.source_span,
+        };
+        let candidates = self.candidates(importer, &decl.source);
+        let Some(path) = candidates.iter().find(|path| path.is_file()) else {
+            self.errors.push(ModuleError::NotFound {
+                source: decl.source.to_string(),
+                tried: candidates,
+                at,
+            });
+            return None;
+        };
+        let module = self.load_file(&path.clone(), Some(at))?;
+
+        // a module still loading its imports can only be reached again
+        // through a cycle
+        if let Some(start) = self.loading.iter().position(|id| *id == module) {
+            let mut chain: Vec<PathBuf> = self.loading[start..]
+                .iter()
+                .map(|id| self.modules[id.0].path.clone())
+                .collect();
+            chain.push(self.modules[module.0].path.clone());
+            self.errors.push(ModuleError::Cycle { chain, at });
+            return None;
+        }
+
+        let target = &self.modules[module.0];
+        let items = match &decl.items {
+            None => target
+                .items
+                .iter()
+                .filter(|item| item.public)
+                .map(|item| item.name.clone())
+                .collect(),
+            Some(items) => {
+                let mut names = Vec::new();
+                for item in items {
+                    let at = ImportSite {
+                        module: importer,
+                        span: item.span,
+                    };
+                    let (name, module) = (item.name.clone(), target.path.clone());
+                    match target.item(&item.name) {
+                        Some(found) if found.public => names.push(name),
+                        Some(_) => self.errors.push(ModuleError::PrivateItem { name, module, at }),
+                        None => self.errors.push(ModuleError::UnknownItem { name, module, at }),
+                    }
+                }
+                names
+            }
+        };
+        Some(Import {
+            module,
+            items,
+            span: decl.span,
+        })
+    }
+
+    /// Paths an import may name, in the order they are tried.
+    fn candidates(&self, importer: ModuleId, source: &ImportSource) -> Vec<PathBuf> {
+        let importer = &self.modules[importer.0].path;
+        let relative = match source {
+            ImportSource::File(path) => PathBuf::from(path),
+            ImportSource::Module(segments) => segments.iter().collect(),
+        };
+        let dir = importer.parent().unwrap_or(Path::new(""));
+        let mut candidates = Vec::new();
+        for dir in std::iter::once(dir).chain(self.search_paths.iter().map(PathBuf::as_path)) {
+            let path = dir.join(&relative);
+            match importer.extension() {
+                Some(ext) if path.extension().is_none() => {
+                    candidates.push(path.with_extension(ext))
+                }
+                _ => candidates.push(path),
+            }
+        }
+        candidates
+    }
+}
+
+/// Parses a module, rendering its errors while the tokens they point into
+/// are still around.
+fn parse(path: &Path, source: &str) -> (Program, Vec<String>) {
+    let filename = path.display().to_string();
+    let tokens = match lexer::tokenize(source, &filename) {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            let report = format!("{}: {}", tr(Message::LexerErrorPrefix), e);
+            return (Program::default(), vec![report]);
+        }
+    };
+    let mut parser = Parser::new(&tokens, 0);
+    let program = Parser::parse_program(&mut parser);
+    let errors = parser
+        .errors
+        .iter()
+        .map(|e| format!("{}: {}", tr(Message::ParserErrorPrefix), e.report(source, &filename)))
+        .collect();
+    (program, errors)
+}
+
+/// The functions, structs and enums declared at the top level.
+fn items(program: &Program) -> Vec<Item> {
+    program
+        .stmts
+        .iter()
+        .filter_map(|stmt| match program.ast.stmt(*stmt) {
+            StmtKind::Func(decl) => Some((&decl.name, decl.public, decl.name_span)),
+            StmtKind::Struct(decl) => Some((&decl.name, decl.public, decl.name_span)),
+            StmtKind::Enum(decl) => Some((&decl.name, decl.public, decl.name_span)),
+            _ => None,
+        })
+        .map(|(name, public, span)| Item {
+            name: name.clone(),
+            public,
+            span,
+        })
+        .collect()
+}
@@ -1,5 +1,13 @@
+pub mod ast;
+pub(crate) mod chain;
+pub mod decls;
 pub mod exprs;
+pub mod fold;
+pub mod patterns;
+pub mod stmts;
+pub mod type_exprs;
 pub mod types;
 pub mod value;
-mod visitor;
+pub mod visitor;
+pub mod visitor_mut;
 
@@ -0,0 +1,382 @@
+use crate::nodes::decls::Param;
+use crate::nodes::exprs::{ArmBody, ExprId, ExprKind, LambdaBody};
+use crate::nodes::stmts::{Block, StmtId, StmtKind};
+use crate::nodes::types::Type;
+use lexer::token::SourceSpan;
+use std::fmt::{self, Display};
+
+/// Arena holding every node of a program. Nodes are stored in flat vectors
+/// and addressed by `ExprId` and `StmtId`, so building a tree costs a few
+/// vector pushes instead of one heap allocation per node. Spans and types
+/// are side tables indexed the same way. The parser assigns no types, so
+/// the type table only grows once something sets one.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ast {
+    exprs: Vec<ExprKind>,
+    expr_spans: Vec<SourceSpan>,
+    expr_types: Vec<Option<Type>>,
+    stmts: Vec<StmtKind>,
+    stmt_spans: Vec<SourceSpan>,
+}
+
+impl Ast {
+    pub fn new() -> Self {
+        Ast::default()
+    }
+
+    /// An arena with room for `exprs` expressions and `stmts` statements,
+    /// so filling it does not have to grow the vectors.
+    pub fn with_capacity(exprs: usize, stmts: usize) -> Self {
+        Ast {
+            exprs: Vec::with_capacity(exprs),
+            expr_spans: Vec::with_capacity(exprs),
+            expr_types: Vec::new(),
+            stmts: Vec::with_capacity(stmts),
+            stmt_spans: Vec::with_capacity(stmts),
+        }
+    }
+
+    pub fn alloc_expr(&mut self, kind: ExprKind, span: SourceSpan) -> ExprId {
+        let id = ExprId(self.exprs.len() as u32);
+        self.exprs.push(kind);
+        self.expr_spans.push(span);
+        id
+    }
+
+    pub fn alloc_stmt(&mut self, kind: StmtKind, span: SourceSpan) -> StmtId {
+        let id = StmtId(self.stmts.len() as u32);
+        self.stmts.push(kind);
+        self.stmt_spans.push(span);
+        id
+    }
+
+    pub fn expr(&self, id: ExprId) -> &ExprKind {
+        &self.exprs[id.index()]
+    }
+
+    pub fn expr_mut(&mut self, id: ExprId) -> &mut ExprKind {
+        &mut self.exprs[id.index()]
+    }
+
+    pub fn expr_span(&self, id: ExprId) -> SourceSpan {
+        self.expr_spans[id.index()]
+    }
+
+    pub fn expr_type(&self, id: ExprId) -> Option<&Type> {
+        self.expr_types.get(id.index()).and_then(Option::as_ref)
+    }
+
+    pub fn set_expr_type(&mut self, id: ExprId, typ: Type) {
+        if self.expr_types.len() <= id.index() {
+            self.expr_types.resize(self.exprs.len(), None);
+        }
+        self.expr_types[id.index()] = Some(typ);
+    }
+
+    pub fn stmt(&self, id: StmtId) -> &StmtKind {
+        &self.stmts[id.index()]
+    }
+
+    pub fn stmt_mut(&mut self, id: StmtId) -> &mut StmtKind {
+        &mut self.stmts[id.index()]
+    }
+
+    pub fn stmt_span(&self, id: StmtId) -> SourceSpan {
+        self.stmt_spans[id.index()]
+    }
+
+    /// The chain of operations `expr` starts, outermost first, each the
+    /// `chained_operand` of the one before, down to the operand the chain
+    /// starts with, which comes last. A chain nests one level per link, so
+    /// anything that walks the tree follows it with this instead of
+    /// recursing: `x.f(1) + 2` is `[+, call, member, x]`.
+    pub fn chain(&self, expr: ExprId) -> Vec<ExprId> {
+        let mut chain = vec![expr];
+        let mut link = expr;
+        while let Some(operand) = self.expr(link).chained_operand() {
+            chain.push(operand);
+            link = operand;
+        }
+        chain
+    }
+
+    /// Number of expressions in the arena.
+    pub fn expr_count(&self) -> usize {
+        self.exprs.len()
+    }
+
+    /// Number of statements in the arena.
+    pub fn stmt_count(&self) -> usize {
+        self.stmts.len()
+    }
+
+    /// Displays an expression fully parenthesized.
+    pub fn display_expr(&self, id: ExprId) -> ExprDisplay<'_> {
+        ExprDisplay { ast: self, id }
+    }
+
+    /// Displays a statement as one line of source.
+    pub fn display_stmt(&self, id: StmtId) -> StmtDisplay<'_> {
+        StmtDisplay { ast: self, id }
+    }
+}
+
+/// An expression paired with its arena, see `Ast::display_expr`.
+pub struct ExprDisplay<'t> {
+    ast: &'t Ast,
+    id: ExprId,
+}
+
+/// A statement paired with its arena, see `Ast::display_stmt`.
+pub struct StmtDisplay<'t> {
+    ast: &'t Ast,
+    id: StmtId,
+}
+
+/// Writes `items` separated by commas.
+fn write_exprs(f: &mut fmt::Formatter<'_>, ast: &Ast, items: &[ExprId]) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", ast.display_expr(*item))?;
+    }
+    Ok(())
+}
+
+/// Writes `params` separated by commas.
+fn write_params(f: &mut fmt::Formatter<'_>, params: &[Param]) -> fmt::Result {
+    for (i, param) in params.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", param)?;
+    }
+    Ok(())
+}
+
+/// Writes a block, its tail expression without the `;`.
+fn write_block(f: &mut fmt::Formatter<'_>, ast: &Ast, block: &Block) -> fmt::Result {
+    write!(f, "{{")?;
+    for (i, stmt) in block.stmts.iter().enumerate() {
+        match ast.stmt(*stmt) {
+            StmtKind::Expr(expr) if block.tail && i + 1 == block.stmts.len() => {
+                write!(f, " {}", ast.display_expr(*expr))?
+            }
+            _ => write!(f, " {}", ast.display_stmt(*stmt))?,
+        }
+    }
+    write!(f, " }}")
+}
+
+/// Writes a chain of operations in a loop, see `Ast::chain`: the opening
+/// parenthesis of every link, the operand at its start, then the rest of
+/// every link from the innermost out.
+fn write_chain(f: &mut fmt::Formatter<'_>, ast: &Ast, expr: ExprId) -> fmt::Result {
+    let chain = ast.chain(expr);
+    let (first, links) = chain.split_last().expect("a chain has an operand");
+    for link in links {
+        if matches!(ast.expr(*link), ExprKind::Binary { .. } | ExprKind::Cast { .. }) {
+            write!(f, "(")?;
+        }
+    }
+    write!(f, "{}", ast.display_expr(*first))?;
+    for link in links.iter().rev() {
+        match ast.expr(*link) {
+            ExprKind::Binary { op, right, .. } => {
+                write!(f, " {} {})", op.symbol(), ast.display_expr(*right))?
+            }
+            ExprKind::Call { args, .. } => {
+                write!(f, "(")?;
+                write_exprs(f, ast, args)?;
+                write!(f, ")")?
+            }
+            ExprKind::Index { index, .. } => write!(f, "[{}]", ast.display_expr(*index))?,
+            ExprKind::Member { field, .. } => write!(f, ".{}", field)?,
+            ExprKind::Cast { ty, .. } => write!(f, " as {})", ty)?,
+            _ => unreachable!("only the last of a chain is not a link"),
+        }
+    }
+    Ok(())
+}
+
+impl Display for ExprDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ast = self.ast;
+        let show = |id: ExprId| ast.display_expr(id);
+        match ast.expr(self.id) {
+            ExprKind::Literal(value) => write!(f, "{}", value),
+            ExprKind::Ident(name) => write!(f, "{}", name),
+            ExprKind::Binary { .. }
+            | ExprKind::Call { .. }
+            | ExprKind::Index { .. }
+            | ExprKind::Member { .. }
+            | ExprKind::Cast { .. } => write_chain(f, ast, self.id),
+            ExprKind::Unary { op, expr, .. } => write!(f, "({}{})", op.symbol(), show(*expr)),
+            ExprKind::Grouping(expr) => write!(f, "({})", show(*expr)),
+            ExprKind::Array(elements) => {
+                write!(f, "[")?;
+                write_exprs(f, ast, elements)?;
+                write!(f, "]")
+            }
+            ExprKind::Map(entries) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", show(*key), show(*value))?;
+                }
+                write!(f, "}}")
+            }
+            ExprKind::Tuple(elements) => {
+                write!(f, "(")?;
+                write_exprs(f, ast, elements)?;
+                if elements.len() == 1 {
+                    write!(f, ",")?;
+                }
+                write!(f, ")")
+            }
+            ExprKind::Struct { name, fields, .. } => {
+                write!(f, "{} {{", name)?;
+                for (i, field) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    if field.shorthand {
+                        write!(f, " {}", field.name)?;
+                    } else {
+                        write!(f, " {}: {}", field.name, show(field.value))?;
+                    }
+                }
+                write!(f, " }}")
+            }
+            ExprKind::Match { scrutinee, arms } => {
+                write!(f, "match {} {{", show(*scrutinee))?;
+                for (i, arm) in arms.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, " {}", arm.pattern)?;
+                    if let Some(guard) = arm.guard {
+                        write!(f, " if {}", show(guard))?;
+                    }
+                    write!(f, " => ")?;
+                    match &arm.body {
+                        ArmBody::Expr(expr) => write!(f, "{}", show(*expr))?,
+                        ArmBody::Block(block) => write_block(f, ast, block)?,
+                    }
+                }
+                write!(f, " }}")
+            }
+            ExprKind::If {
+                cond,
+                then_branch,
+                else_branch,
+            } => {
+                write!(f, "if {} ", show(*cond))?;
+                write_block(f, ast, then_branch)?;
+                if let Some(else_branch) = else_branch {
+                    write!(f, " else {}", show(*else_branch))?;
+                }
+                Ok(())
+            }
+            ExprKind::Block(block) => write_block(f, ast, block),
+            ExprKind::Lambda(lambda) => {
+                let (open, close) = match lambda.body {
+                    LambdaBody::Block(_) => ("func(", ")"),
+                    LambdaBody::Expr(_) => ("|", "|"),
+                };
+                write!(f, "{}", open)?;
+                write_params(f, &lambda.params)?;
+                write!(f, "{}", close)?;
+                if let Some(ty) = &lambda.return_type {
+                    write!(f, " -> {}", ty)?;
+                }
+                match &lambda.body {
+                    LambdaBody::Block(block) => {
+                        write!(f, " ")?;
+                        write_block(f, ast, block)
+                    }
+                    LambdaBody::Expr(expr) => write!(f, " {}", show(*expr)),
+                }
+            }
+            ExprKind::Error => write!(f, "<error>"),
+        }
+    }
+}
+
+impl Display for StmtDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ast = self.ast;
+        let show = |id: ExprId| ast.display_expr(id);
+        match ast.stmt(self.id) {
+            StmtKind::Expr(expr) => write!(f, "{};", show(*expr)),
+            StmtKind::Let {
+                mutable,
+                name,
+                ty,
+                value,
+                ..
+            } => {
+                let keyword = if *mutable { "var" } else { "let" };
+                write!(f, "{} {}", keyword, name)?;
+                if let Some(ty) = ty {
+                    write!(f, ": {}", ty)?;
+                }
+                if let Some(value) = value {
+                    write!(f, " = {}", show(*value))?;
+                }
+                write!(f, ";")
+            }
+            StmtKind::Assign { target, value } => write!(f, "{} = {};", show(*target), show(*value)),
+            StmtKind::Block(block) => write_block(f, ast, block),
+            StmtKind::If {
+                cond,
+                then_branch,
+                else_branch,
+            } => {
+                write!(f, "if {} ", show(*cond))?;
+                write_block(f, ast, then_branch)?;
+                if let Some(else_branch) = else_branch {
+                    write!(f, " else {}", ast.display_stmt(*else_branch))?;
+                }
+                Ok(())
+            }
+            StmtKind::While { cond, body } => {
+                write!(f, "while {} ", show(*cond))?;
+                write_block(f, ast, body)
+            }
+            StmtKind::For {
+                var,
+                iterable,
+                body,
+                ..
+            } => {
+                write!(f, "for {} in {} ", var, show(*iterable))?;
+                write_block(f, ast, body)
+            }
+            StmtKind::Return(Some(value)) => write!(f, "return {};", show(*value)),
+            StmtKind::Return(None) => write!(f, "return;"),
+            StmtKind::Break => write!(f, "break;"),
+            StmtKind::Continue => write!(f, "continue;"),
+            StmtKind::Func(func) => {
+                if func.public {
+                    write!(f, "pub ")?;
+                }
+                write!(f, "func {}(", func.name)?;
+                write_params(f, &func.params)?;
+                write!(f, ")")?;
+                if let Some(ty) = &func.return_type {
+                    write!(f, " -> {}", ty)?;
+                }
+                write!(f, " ")?;
+                write_block(f, ast, &func.body)
+            }
+            StmtKind::Struct(decl) => write!(f, "{}", decl),
+            StmtKind::Enum(decl) => write!(f, "{}", decl),
+            StmtKind::Import(decl) => write!(f, "{}", decl),
+            StmtKind::Error => write!(f, "<error>;"),
+        }
+    }
+}
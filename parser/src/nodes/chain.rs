@@ -0,0 +1,67 @@
+//! How the walks of `visitor`, `visitor_mut` and `fold` follow a chain of
+//! operations such as `a + b + c` or `x.f().g`, see `Ast::chain`.
+//!
+//! A chain nests one level per link, so the walk of the outermost link goes
+//! down the chain in a loop. It walks the operand the chain starts with,
+//! then calls `visit_expr` or `fold_expr` on every other link from the
+//! innermost out, and finally walks the rest of the outermost link. A pass
+//! still sees every link, and the operands of the chain in source order.
+//! A fold that replaces an inner link without walking it leaves what the
+//! operand of that link folded into unused in the new arena.
+//!
+//! The `walk_*` function of an inner link reached that way must not go down
+//! its operand again. The loop marks the link here before handing it to the
+//! pass, and the walk takes the mark when it gets to that link.
+
+use std::cell::Cell;
+
+use crate::nodes::ast::Ast;
+use crate::nodes::exprs::ExprId;
+
+thread_local! {
+    static MARK: Cell<Option<Mark>> = const { Cell::new(None) };
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct Mark {
+    // address of the arena the link is in
+    arena: usize,
+    link: ExprId,
+    // what stands for the operand of the link, already walked
+    operand: ExprId,
+}
+
+/// Runs `visit` on `link` of a chain in `arena`, whose operand the caller
+/// has walked already, into `operand` for a fold.
+pub(crate) fn visit_link<R>(
+    arena: *const Ast,
+    link: ExprId,
+    operand: ExprId,
+    visit: impl FnOnce() -> R,
+) -> R {
+    // put back whatever mark was there, even if `visit` panics
+    struct Restore(Option<Mark>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            MARK.set(self.0);
+        }
+    }
+    let mark = Mark {
+        arena: arena as usize,
+        link,
+        operand,
+    };
+    let _restore = Restore(MARK.replace(Some(mark)));
+    visit()
+}
+
+/// When `link` is the link of `arena` handed to `visit_link`, what stands
+/// for its operand, which is then not to be walked again.
+pub(crate) fn walked_operand(arena: *const Ast, link: ExprId) -> Option<ExprId> {
+    let mark = MARK.get()?;
+    if mark.arena != arena as usize || mark.link != link {
+        return None;
+    }
+    MARK.set(None);
+    Some(mark.operand)
+}
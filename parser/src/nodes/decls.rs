@@ -0,0 +1,180 @@
+use crate::nodes::stmts::Block;
+use crate::nodes::type_exprs::TypeExpr;
+use lexer::token::SourceSpan;
+use std::fmt::{self, Display};
+
+/// A function parameter, `name` or `name: type`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    pub name: String,
+    pub name_span: SourceSpan,
+    pub type_annotation: Option<TypeExpr>,
+    // from the name to the end of the annotation
+    pub span: SourceSpan,
+}
+
+impl Display for Param {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(ty) = &self.type_annotation {
+            write!(f, ": {}", ty)?;
+        }
+        Ok(())
+    }
+}
+
+/// `func name(params) -> type { ... }`, the return type is optional. It
+/// displays through `Ast::display_stmt`, since the body lives in the arena.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuncDecl {
+    // declared `pub`, visible to the files importing this one
+    pub public: bool,
+    pub name: String,
+    pub name_span: SourceSpan,
+    pub params: Vec<Param>,
+    pub return_type: Option<TypeExpr>,
+    pub body: Block,
+    // from `pub` or `func` to the return type, or to `)` without one
+    pub signature_span: SourceSpan,
+    pub span: SourceSpan,
+}
+
+/// A field of a struct declaration, `name: type`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDecl {
+    pub name: String,
+    pub name_span: SourceSpan,
+    pub ty: TypeExpr,
+    pub span: SourceSpan,
+}
+
+impl Display for FieldDecl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.ty)
+    }
+}
+
+/// `struct Name { field: type, ... }`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructDecl {
+    pub public: bool,
+    pub name: String,
+    pub name_span: SourceSpan,
+    pub fields: Vec<FieldDecl>,
+    pub span: SourceSpan,
+}
+
+impl Display for StructDecl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.public {
+            write!(f, "pub ")?;
+        }
+        write!(f, "struct {} {{", self.name)?;
+        for (i, field) in self.fields.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, " {}", field)?;
+        }
+        write!(f, " }}")
+    }
+}
+
+/// A variant of an enum declaration, `Name` or `Name(type, ...)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariantDecl {
+    pub name: String,
+    pub name_span: SourceSpan,
+    pub fields: Vec<TypeExpr>,
+    pub span: SourceSpan,
+}
+
+impl Display for VariantDecl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.fields.is_empty() {
+            write!(f, "(")?;
+            for (i, field) in self.fields.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}", field)?;
+            }
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+/// `enum Name { Variant, Variant(type, ...), ... }`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumDecl {
+    pub public: bool,
+    pub name: String,
+    pub name_span: SourceSpan,
+    pub variants: Vec<VariantDecl>,
+    pub span: SourceSpan,
+}
+
+impl Display for EnumDecl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.public {
+            write!(f, "pub ")?;
+        }
+        write!(f, "enum {} {{", self.name)?;
+        for (i, variant) in self.variants.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, " {}", variant)?;
+        }
+        write!(f, " }}")
+    }
+}
+
+/// `import "path/to/file";` or `import geometry.{Point, area};`. Without a
+/// list of items every public item of the module is imported.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportDecl {
+    pub source: ImportSource,
+    pub source_span: SourceSpan,
+    pub items: Option<Vec<ImportItem>>,
+    pub span: SourceSpan,
+}
+
+/// Where an import finds its module.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportSource {
+    // a path in a string, `"shapes/circle"`, relative to the importing file
+    File(String),
+    // a dotted module name, `geometry.shapes`, looked up as a file
+    // `geometry/shapes` next to the importing file or in a search path
+    Module(Vec<String>),
+}
+
+impl Display for ImportSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportSource::File(path) => write!(f, "\"{}\"", path),
+            ImportSource::Module(segments) => write!(f, "{}", segments.join(".")),
+        }
+    }
+}
+
+/// A name in the item list of an import.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportItem {
+    pub name: String,
+    pub span: SourceSpan,
+}
+
+impl Display for ImportDecl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "import {}", self.source)?;
+        if let Some(items) = &self.items {
+            let names: Vec<&str> = items.iter().map(|item| item.name.as_str()).collect();
+            write!(f, ".{{{}}}", names.join(", "))?;
+        }
+        write!(f, ";")
+    }
+}
@@ -1,53 +1,170 @@
-use crate::nodes::types::Type;
-use lexer::token::{SourceSpan, Token};
+use crate::nodes::decls::Param;
+use crate::nodes::patterns::Pattern;
+use crate::nodes::stmts::Block;
+use crate::nodes::type_exprs::TypeExpr;
+use lexer::token::SourceSpan;
 use lexer::token_type::TokenKind;
-use std::fmt::Debug;
+use std::fmt::{self, Display};
 
-pub trait Expr: Debug {
-    // start with a span method to get the source span of the expression
-    fn span(&self) -> SourceSpan;
-    fn type_info(&self) -> Option<Type>;
-    fn set_type_info(&mut self, typ: Type);
-}
+/// Index of an expression in the `Ast` arena. Spans and types of the
+/// expression live in side tables of the arena under the same index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ExprId(pub(crate) u32);
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum LiteralValue {
-    Int(i128),
-    Float(f64),
-    Bool(bool),
-    Str(String),
+impl ExprId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
 }
 
-#[derive(Debug, Clone)]
-pub struct LiteralExpr {
-    pub value: LiteralValue,
-    pub type_info: Option<Type>,
-    pub span: SourceSpan,
+/// Every kind of expression. Children are referenced by `ExprId`, so a
+/// whole tree is a flat list of nodes in one arena, and displaying a node
+/// goes through `Ast::display_expr`, which prints it fully parenthesized:
+/// `1 - 2 - 3` displays as `((1 - 2) - 3)`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprKind {
+    Literal(LiteralValue),
+    Ident(String),
+    Binary {
+        left: ExprId,
+        op: BinaryOp,
+        right: ExprId,
+    },
+    Unary {
+        op: UnaryOp,
+        op_span: SourceSpan,
+        expr: ExprId,
+    },
+    // `(expr)`, kept in the tree so spans and lints can see the parentheses
+    Grouping(ExprId),
+    // `callee(a, b)`
+    Call {
+        callee: ExprId,
+        args: Vec<ExprId>,
+    },
+    // `object[index]`
+    Index {
+        object: ExprId,
+        index: ExprId,
+    },
+    // `object.field`
+    Member {
+        object: ExprId,
+        field: String,
+        field_span: SourceSpan,
+    },
+    // `expr as T`
+    Cast {
+        expr: ExprId,
+        ty: TypeExpr,
+    },
+    // `[1, 2, 3]`
+    Array(Vec<ExprId>),
+    // `{"a": 1, "b": 2}`, entries keep their source order
+    Map(Vec<(ExprId, ExprId)>),
+    // `(a, b)`, a single element needs a trailing comma, `(a,)`, and `()`
+    // is the unit value
+    Tuple(Vec<ExprId>),
+    // `Point { x: 1, y: 2 }`
+    Struct {
+        name: String,
+        name_span: SourceSpan,
+        fields: Vec<FieldInit>,
+    },
+    // `match scrutinee { arm, ... }`, the first arm whose pattern matches
+    // and whose guard holds is taken
+    Match {
+        scrutinee: ExprId,
+        arms: Vec<MatchArm>,
+    },
+    // `if cond { ... } else { ... }` where a value is expected, the else
+    // branch is a `Block` or another `If`. Blocks are boxed here and in
+    // `Block`, like lambdas, to keep every expression small
+    If {
+        cond: ExprId,
+        then_branch: Box<Block>,
+        else_branch: Option<ExprId>,
+    },
+    // `{ ... }` where a value is expected, telling it from a map by the
+    // `:` after the first key of a map
+    Block(Box<Block>),
+    // `|a, b| a + b`, or `func(a, b) -> int { ... }` with a block body
+    Lambda(Box<LambdaExpr>),
+    // placeholder for a missing expression, as in `let x = ;`, the error
+    // itself is recorded by the parser
+    Error,
 }
 
-impl LiteralExpr {
-    pub fn new(value: LiteralValue, span: SourceSpan) -> Self {
-        LiteralExpr {
-            value: value,
-            span: span,
-            type_info: None,
+impl ExprKind {
+    /// Whether the expression is a single operand, which no operator around
+    /// it can take apart, so precedence never calls for parentheses around
+    /// it. Where it stands may still call for them, see
+    /// `Parser::parse_grouping_expr`. A lambda with an expression body is
+    /// not atomic, as its body takes in every operator that follows.
+    pub fn is_atomic(&self) -> bool {
+        match self {
+            ExprKind::Binary { .. } | ExprKind::Unary { .. } | ExprKind::Cast { .. } => false,
+            ExprKind::Lambda(lambda) => !lambda.has_expr_body(),
+            _ => true,
         }
     }
-}
 
+    /// The operand this expression continues when it is a link of a chain
+    /// such as `a + b + c` or `x.f().g`: the left operand of an operator,
+    /// the callee of a call, the object of an index or member, or the
+    /// expression of a cast. The parser reads a chain in a loop, and so
+    /// does anything that walks one, see `Ast::chain`.
+    pub fn chained_operand(&self) -> Option<ExprId> {
+        match self {
+            ExprKind::Binary { left: operand, .. }
+            | ExprKind::Call { callee: operand, .. }
+            | ExprKind::Index { object: operand, .. }
+            | ExprKind::Member { object: operand, .. }
+            | ExprKind::Cast { expr: operand, .. } => Some(*operand),
+            _ => None,
+        }
+    }
 
-impl Expr for LiteralExpr {
-    fn span(&self) -> SourceSpan {
-        // Assuming the span is derived from the value, this is a placeholder
-        self.span.clone()
+    /// Whether the expression names a place that can be assigned to.
+    pub fn is_place(&self) -> bool {
+        matches!(
+            self,
+            ExprKind::Ident(_) | ExprKind::Index { .. } | ExprKind::Member { .. }
+        )
     }
 
-    fn type_info(&self) -> Option<Type> {
-        self.type_info.clone()
+    /// Whether evaluating the node itself does something besides producing
+    /// a value, so using it as a statement is meaningful. An `Error` counts,
+    /// which keeps the `unused` lint quiet about code that did not parse.
+    pub fn has_side_effects(&self) -> bool {
+        // the arms of a `match` and the branches of an `if` may do anything
+        matches!(
+            self,
+            ExprKind::Call { .. }
+                | ExprKind::Match { .. }
+                | ExprKind::If { .. }
+                | ExprKind::Block(_)
+                | ExprKind::Error
+        )
     }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiteralValue {
+    Int(i128),
+    Float(f64),
+    Bool(bool),
+    Str(String),
+}
 
-    fn set_type_info(&mut self, typ: Type) {
-        self.type_info = Some(typ);
+impl Display for LiteralValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LiteralValue::Int(v) => write!(f, "{}", v),
+            LiteralValue::Float(v) => write!(f, "{:?}", v),
+            LiteralValue::Bool(v) => write!(f, "{}", v),
+            LiteralValue::Str(v) => write!(f, "{:?}", v),
+        }
     }
 }
 
@@ -71,50 +188,99 @@ pub enum BinaryOp {
     // Lógicos (si soportas booleanos)
     And, // &&
     Or,  // ||
-}
 
-#[derive(Debug)]
-pub struct BinaryExpr<'a> {
-    pub left: Box<dyn Expr + 'a>,
-    pub op: BinaryOp,
-    pub right: Box<dyn Expr + 'a>,
-    pub type_info: Option<Type>,
-}
+    // Bits
+    BitAnd, // &
+    BitOr,  // |
+    BitXor, // ^
+    Shl,    // <<
+    Shr,    // >>
 
-impl<'a> Expr for BinaryExpr<'a> {
-    fn span(&self) -> SourceSpan {
-        // Assuming the span is derived from the left and right expressions
-        let left_span = self.left.span();
-        let right_span = self.right.span();
-        SourceSpan::combine(&left_span, &right_span)
-    }
+    Pow, // **
 
-    fn type_info(&self) -> Option<Type> {
-        self.type_info.clone()
-    }
-
-    fn set_type_info(&mut self, typ: Type) {
-        self.type_info = Some(typ);
-    }
+    // Rangos, sin incluir el final o incluyéndolo
+    Range,          // ..
+    RangeInclusive, // ..=
 }
 
 impl BinaryOp {
-    pub fn from_token(kind: TokenKind) -> Self {
+    pub fn from_token(kind: TokenKind) -> Option<Self> {
         match kind {
-            TokenKind::PLUS => BinaryOp::Add,
-            TokenKind::DASH => BinaryOp::Sub,
-            TokenKind::STAR => BinaryOp::Mul,
-            TokenKind::SLASH => BinaryOp::Div,
-            TokenKind::MODULO => BinaryOp::Mod,
-            //TokenKind::EQUAL_EQUAL => BinaryOp::Equal,
-            //TokenKind::BANG_EQUAL => BinaryOp::NotEqual,
-            TokenKind::LESS => BinaryOp::Less,
-            //TokenKind::LESS_EQUAL => BinaryOp::LessEqual,
-            TokenKind::GREATER => BinaryOp::Greater,
-            //TokenKind::GREATER_EQUAL => BinaryOp::GreaterEqual,
-            TokenKind::AMPER => BinaryOp::And,
-            TokenKind::VERTICAL_BAR => BinaryOp::Or,
-            _ => panic!("Unknown binary operator"),
+            TokenKind::PLUS => Some(BinaryOp::Add),
+            TokenKind::DASH => Some(BinaryOp::Sub),
+            TokenKind::STAR => Some(BinaryOp::Mul),
+            TokenKind::SLASH => Some(BinaryOp::Div),
+            TokenKind::MODULO => Some(BinaryOp::Mod),
+            TokenKind::EQUAL_EQUAL => Some(BinaryOp::Equal),
+            TokenKind::NOT_EQUAL => Some(BinaryOp::NotEqual),
+            TokenKind::LESS => Some(BinaryOp::Less),
+            TokenKind::LESS_EQUAL => Some(BinaryOp::LessEqual),
+            TokenKind::GREATER => Some(BinaryOp::Greater),
+            TokenKind::GREATER_EQUAL => Some(BinaryOp::GreaterEqual),
+            TokenKind::DOUBLE_AMPER => Some(BinaryOp::And),
+            TokenKind::DOUBLE_VERTICAL_BAR => Some(BinaryOp::Or),
+            TokenKind::AMPER => Some(BinaryOp::BitAnd),
+            TokenKind::VERTICAL_BAR => Some(BinaryOp::BitOr),
+            TokenKind::CARET => Some(BinaryOp::BitXor),
+            TokenKind::DOUBLE_LESS => Some(BinaryOp::Shl),
+            TokenKind::DOUBLE_GREATER => Some(BinaryOp::Shr),
+            TokenKind::DOUBLE_STAR => Some(BinaryOp::Pow),
+            TokenKind::DOUBLE_DOT => Some(BinaryOp::Range),
+            TokenKind::DOUBLE_DOT_EQUAL => Some(BinaryOp::RangeInclusive),
+            _ => None,
+        }
+    }
+
+    /// The token of the operator, the inverse of `from_token`.
+    pub fn token_kind(self) -> TokenKind {
+        match self {
+            BinaryOp::Add => TokenKind::PLUS,
+            BinaryOp::Sub => TokenKind::DASH,
+            BinaryOp::Mul => TokenKind::STAR,
+            BinaryOp::Div => TokenKind::SLASH,
+            BinaryOp::Mod => TokenKind::MODULO,
+            BinaryOp::Equal => TokenKind::EQUAL_EQUAL,
+            BinaryOp::NotEqual => TokenKind::NOT_EQUAL,
+            BinaryOp::Less => TokenKind::LESS,
+            BinaryOp::LessEqual => TokenKind::LESS_EQUAL,
+            BinaryOp::Greater => TokenKind::GREATER,
+            BinaryOp::GreaterEqual => TokenKind::GREATER_EQUAL,
+            BinaryOp::And => TokenKind::DOUBLE_AMPER,
+            BinaryOp::Or => TokenKind::DOUBLE_VERTICAL_BAR,
+            BinaryOp::BitAnd => TokenKind::AMPER,
+            BinaryOp::BitOr => TokenKind::VERTICAL_BAR,
+            BinaryOp::BitXor => TokenKind::CARET,
+            BinaryOp::Shl => TokenKind::DOUBLE_LESS,
+            BinaryOp::Shr => TokenKind::DOUBLE_GREATER,
+            BinaryOp::Pow => TokenKind::DOUBLE_STAR,
+            BinaryOp::Range => TokenKind::DOUBLE_DOT,
+            BinaryOp::RangeInclusive => TokenKind::DOUBLE_DOT_EQUAL,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Mod => "%",
+            BinaryOp::Equal => "==",
+            BinaryOp::NotEqual => "!=",
+            BinaryOp::Less => "<",
+            BinaryOp::LessEqual => "<=",
+            BinaryOp::Greater => ">",
+            BinaryOp::GreaterEqual => ">=",
+            BinaryOp::And => "&&",
+            BinaryOp::Or => "||",
+            BinaryOp::BitAnd => "&",
+            BinaryOp::BitOr => "|",
+            BinaryOp::BitXor => "^",
+            BinaryOp::Shl => "<<",
+            BinaryOp::Shr => ">>",
+            BinaryOp::Pow => "**",
+            BinaryOp::Range => "..",
+            BinaryOp::RangeInclusive => "..=",
         }
     }
 }
@@ -127,9 +293,78 @@ pub enum UnaryOp {
     Not,     // ! (booleanos)
 }
 
-pub struct UnaryExpr {
-    pub op: UnaryOp,
-    pub expr: Box<dyn Expr>,
-    pub type_info: Option<Type>,
+impl UnaryOp {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            UnaryOp::Ref => "&",
+            UnaryOp::Deref => "*",
+            UnaryOp::Neg => "-",
+            UnaryOp::Not => "!",
+        }
+    }
+
+    pub fn from_token(kind: TokenKind) -> Option<Self> {
+        match kind {
+            TokenKind::AMPER => Some(UnaryOp::Ref),
+            TokenKind::STAR => Some(UnaryOp::Deref),
+            TokenKind::DASH => Some(UnaryOp::Neg),
+            TokenKind::NEGATION => Some(UnaryOp::Not),
+            _ => None,
+        }
+    }
 }
 
+/// One field of a struct literal. With the shorthand `Point { x }` the
+/// value is the variable of the same name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldInit {
+    pub name: String,
+    pub name_span: SourceSpan,
+    pub value: ExprId,
+    pub shorthand: bool,
+}
+
+/// What a `match` arm evaluates to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArmBody {
+    // `pat => expr,`
+    Expr(ExprId),
+    // `pat => { ... }`
+    Block(Block),
+}
+
+/// One arm of a `match`, `pattern if guard => body`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<ExprId>,
+    pub body: ArmBody,
+    pub span: SourceSpan,
+}
+
+/// A lambda, boxed in `ExprKind::Lambda` as `FuncDecl` is in
+/// `StmtKind::Func`, so the parameters do not make every expression
+/// larger. The variables it captures are found by `resolve::captures`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LambdaExpr {
+    pub params: Vec<Param>,
+    pub return_type: Option<TypeExpr>,
+    pub body: LambdaBody,
+}
+
+impl LambdaExpr {
+    /// Whether the body is an expression, which takes in every operator
+    /// that follows the lambda.
+    pub fn has_expr_body(&self) -> bool {
+        matches!(self.body, LambdaBody::Expr(_))
+    }
+}
+
+/// What a lambda evaluates to when called.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LambdaBody {
+    // `|x| expr`
+    Expr(ExprId),
+    // `|x| { ... }` or `func(x) { ... }`
+    Block(Block),
+}
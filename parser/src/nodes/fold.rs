@@ -0,0 +1,325 @@
+//! Rebuilding traversal of a program.
+//!
+//! A `Fold` reads nodes from one arena and allocates what it makes of them
+//! in another, children before parents, as the parser does. By default
+//! every node is copied unchanged, with its span and type.
+
+use crate::nodes::ast::Ast;
+use crate::nodes::chain;
+use crate::nodes::decls::{EnumDecl, FuncDecl, StructDecl};
+use crate::nodes::exprs::{ArmBody, ExprId, ExprKind, LambdaBody, MatchArm};
+use crate::nodes::patterns::{Pattern, PatternKind};
+use crate::nodes::stmts::{Block, Program, StmtId, StmtKind};
+use crate::nodes::type_exprs::{TypeExpr, TypeExprKind};
+
+pub trait Fold: Sized {
+    fn fold_program(&mut self, program: Program) -> Program {
+        walk_program(self, program)
+    }
+
+    fn fold_stmt(&mut self, from: &Ast, into: &mut Ast, stmt: StmtId) -> StmtId {
+        walk_stmt(self, from, into, stmt)
+    }
+
+    fn fold_block(&mut self, from: &Ast, into: &mut Ast, block: Block) -> Block {
+        walk_block(self, from, into, block)
+    }
+
+    fn fold_expr(&mut self, from: &Ast, into: &mut Ast, expr: ExprId) -> ExprId {
+        walk_expr(self, from, into, expr)
+    }
+
+    fn fold_match_arm(&mut self, from: &Ast, into: &mut Ast, arm: MatchArm) -> MatchArm {
+        walk_match_arm(self, from, into, arm)
+    }
+
+    fn fold_func_decl(&mut self, from: &Ast, into: &mut Ast, decl: FuncDecl) -> FuncDecl {
+        walk_func_decl(self, from, into, decl)
+    }
+
+    fn fold_struct_decl(&mut self, decl: StructDecl) -> StructDecl {
+        walk_struct_decl(self, decl)
+    }
+
+    fn fold_enum_decl(&mut self, decl: EnumDecl) -> EnumDecl {
+        walk_enum_decl(self, decl)
+    }
+
+    fn fold_pattern(&mut self, pattern: Pattern) -> Pattern {
+        walk_pattern(self, pattern)
+    }
+
+    fn fold_type_expr(&mut self, ty: TypeExpr) -> TypeExpr {
+        walk_type_expr(self, ty)
+    }
+}
+
+pub fn walk_program<F: Fold>(f: &mut F, program: Program) -> Program {
+    let from = program.ast;
+    let mut into = Ast::with_capacity(from.expr_count(), from.stmt_count());
+    let stmts = program
+        .stmts
+        .into_iter()
+        .map(|stmt| f.fold_stmt(&from, &mut into, stmt))
+        .collect();
+    Program { ast: into, stmts }
+}
+
+pub fn walk_stmt<F: Fold>(f: &mut F, from: &Ast, into: &mut Ast, stmt: StmtId) -> StmtId {
+    let kind = match from.stmt(stmt).clone() {
+        StmtKind::Expr(expr) => StmtKind::Expr(f.fold_expr(from, into, expr)),
+        StmtKind::Let {
+            mutable,
+            name,
+            name_span,
+            ty,
+            value,
+        } => StmtKind::Let {
+            mutable,
+            name,
+            name_span,
+            ty: ty.map(|ty| f.fold_type_expr(ty)),
+            value: value.map(|value| f.fold_expr(from, into, value)),
+        },
+        StmtKind::Assign { target, value } => StmtKind::Assign {
+            target: f.fold_expr(from, into, target),
+            value: f.fold_expr(from, into, value),
+        },
+        StmtKind::Block(block) => StmtKind::Block(f.fold_block(from, into, block)),
+        StmtKind::If {
+            cond,
+            then_branch,
+            else_branch,
+        } => StmtKind::If {
+            cond: f.fold_expr(from, into, cond),
+            then_branch: f.fold_block(from, into, then_branch),
+            else_branch: else_branch.map(|stmt| f.fold_stmt(from, into, stmt)),
+        },
+        StmtKind::While { cond, body } => StmtKind::While {
+            cond: f.fold_expr(from, into, cond),
+            body: f.fold_block(from, into, body),
+        },
+        StmtKind::For {
+            var,
+            var_span,
+            iterable,
+            body,
+        } => StmtKind::For {
+            var,
+            var_span,
+            iterable: f.fold_expr(from, into, iterable),
+            body: f.fold_block(from, into, body),
+        },
+        StmtKind::Return(value) => {
+            StmtKind::Return(value.map(|value| f.fold_expr(from, into, value)))
+        }
+        StmtKind::Func(decl) => StmtKind::Func(Box::new(f.fold_func_decl(from, into, *decl))),
+        StmtKind::Struct(decl) => StmtKind::Struct(Box::new(f.fold_struct_decl(*decl))),
+        StmtKind::Enum(decl) => StmtKind::Enum(Box::new(f.fold_enum_decl(*decl))),
+        kind @ (StmtKind::Break | StmtKind::Continue | StmtKind::Import(_) | StmtKind::Error) => {
+            kind
+        }
+    };
+    into.alloc_stmt(kind, from.stmt_span(stmt))
+}
+
+pub fn walk_block<F: Fold>(f: &mut F, from: &Ast, into: &mut Ast, block: Block) -> Block {
+    Block {
+        stmts: block
+            .stmts
+            .into_iter()
+            .map(|stmt| f.fold_stmt(from, into, stmt))
+            .collect(),
+        tail: block.tail,
+        span: block.span,
+    }
+}
+
+/// Folds the children of an expression and allocates it. The operand of a
+/// link of a chain such as `a + b + c` is folded in a loop, see
+/// `nodes::chain`.
+pub fn walk_expr<F: Fold>(f: &mut F, from: &Ast, into: &mut Ast, expr: ExprId) -> ExprId {
+    // what the operand of a link folds into, unused by other kinds
+    let operand = match from.expr(expr).chained_operand() {
+        Some(_) => {
+            chain::walked_operand(from, expr).unwrap_or_else(|| fold_chain(f, from, into, expr))
+        }
+        None => expr,
+    };
+    let mut fold = |e: ExprId| f.fold_expr(from, into, e);
+    let kind = match from.expr(expr).clone() {
+        ExprKind::Binary { op, right, .. } => ExprKind::Binary {
+            left: operand,
+            op,
+            right: fold(right),
+        },
+        ExprKind::Unary { op, op_span, expr } => ExprKind::Unary {
+            op,
+            op_span,
+            expr: fold(expr),
+        },
+        ExprKind::Grouping(expr) => ExprKind::Grouping(fold(expr)),
+        ExprKind::Call { args, .. } => ExprKind::Call {
+            callee: operand,
+            args: args.into_iter().map(fold).collect(),
+        },
+        ExprKind::Index { index, .. } => ExprKind::Index {
+            object: operand,
+            index: fold(index),
+        },
+        ExprKind::Member {
+            field, field_span, ..
+        } => ExprKind::Member {
+            object: operand,
+            field,
+            field_span,
+        },
+        ExprKind::Cast { ty, .. } => ExprKind::Cast {
+            expr: operand,
+            ty: f.fold_type_expr(ty),
+        },
+        ExprKind::Array(elements) => ExprKind::Array(elements.into_iter().map(fold).collect()),
+        ExprKind::Tuple(elements) => ExprKind::Tuple(elements.into_iter().map(fold).collect()),
+        ExprKind::Map(entries) => ExprKind::Map(
+            entries
+                .into_iter()
+                .map(|(key, value)| (fold(key), fold(value)))
+                .collect(),
+        ),
+        ExprKind::Struct {
+            name,
+            name_span,
+            mut fields,
+        } => {
+            for field in &mut fields {
+                field.value = fold(field.value);
+            }
+            ExprKind::Struct {
+                name,
+                name_span,
+                fields,
+            }
+        }
+        ExprKind::Match { scrutinee, arms } => ExprKind::Match {
+            scrutinee: fold(scrutinee),
+            arms: arms
+                .into_iter()
+                .map(|arm| f.fold_match_arm(from, into, arm))
+                .collect(),
+        },
+        ExprKind::Lambda(mut lambda) => {
+            for param in &mut lambda.params {
+                param.type_annotation = param.type_annotation.take().map(|ty| f.fold_type_expr(ty));
+            }
+            lambda.return_type = lambda.return_type.map(|ty| f.fold_type_expr(ty));
+            lambda.body = match lambda.body {
+                LambdaBody::Expr(expr) => LambdaBody::Expr(f.fold_expr(from, into, expr)),
+                LambdaBody::Block(block) => LambdaBody::Block(f.fold_block(from, into, block)),
+            };
+            ExprKind::Lambda(lambda)
+        }
+        ExprKind::If {
+            cond,
+            then_branch,
+            else_branch,
+        } => ExprKind::If {
+            cond: f.fold_expr(from, into, cond),
+            then_branch: Box::new(f.fold_block(from, into, *then_branch)),
+            else_branch: else_branch.map(|branch| f.fold_expr(from, into, branch)),
+        },
+        ExprKind::Block(block) => ExprKind::Block(Box::new(f.fold_block(from, into, *block))),
+        kind @ (ExprKind::Literal(_) | ExprKind::Ident(_) | ExprKind::Error) => kind,
+    };
+    let id = into.alloc_expr(kind, from.expr_span(expr));
+    if let Some(ty) = from.expr_type(expr) {
+        into.set_expr_type(id, *ty);
+    }
+    id
+}
+
+/// Folds the operand a chain starts with, then every link below `expr`
+/// from the innermost out, and returns what the operand of `expr` folds
+/// into.
+fn fold_chain<F: Fold>(f: &mut F, from: &Ast, into: &mut Ast, expr: ExprId) -> ExprId {
+    let chain = from.chain(expr);
+    let (first, links) = chain.split_last().expect("a chain has an operand");
+    let mut operand = f.fold_expr(from, into, *first);
+    for link in links[1..].iter().rev() {
+        operand = chain::visit_link(from, *link, operand, || f.fold_expr(from, into, *link));
+    }
+    operand
+}
+
+pub fn walk_match_arm<F: Fold>(f: &mut F, from: &Ast, into: &mut Ast, arm: MatchArm) -> MatchArm {
+    MatchArm {
+        pattern: f.fold_pattern(arm.pattern),
+        guard: arm.guard.map(|guard| f.fold_expr(from, into, guard)),
+        body: match arm.body {
+            ArmBody::Expr(expr) => ArmBody::Expr(f.fold_expr(from, into, expr)),
+            ArmBody::Block(block) => ArmBody::Block(f.fold_block(from, into, block)),
+        },
+        span: arm.span,
+    }
+}
+
+pub fn walk_func_decl<F: Fold>(f: &mut F, from: &Ast, into: &mut Ast, mut decl: FuncDecl) -> FuncDecl {
+    for param in &mut decl.params {
+        param.type_annotation = param.type_annotation.take().map(|ty| f.fold_type_expr(ty));
+    }
+    decl.return_type = decl.return_type.map(|ty| f.fold_type_expr(ty));
+    decl.body = f.fold_block(from, into, decl.body);
+    decl
+}
+
+pub fn walk_struct_decl<F: Fold>(f: &mut F, mut decl: StructDecl) -> StructDecl {
+    for field in &mut decl.fields {
+        field.ty = f.fold_type_expr(field.ty.clone());
+    }
+    decl
+}
+
+pub fn walk_enum_decl<F: Fold>(f: &mut F, mut decl: EnumDecl) -> EnumDecl {
+    for variant in &mut decl.variants {
+        variant.fields = std::mem::take(&mut variant.fields)
+            .into_iter()
+            .map(|ty| f.fold_type_expr(ty))
+            .collect();
+    }
+    decl
+}
+
+pub fn walk_pattern<F: Fold>(f: &mut F, pattern: Pattern) -> Pattern {
+    let kind = match pattern.kind {
+        PatternKind::Tuple(items) => {
+            PatternKind::Tuple(items.into_iter().map(|p| f.fold_pattern(p)).collect())
+        }
+        PatternKind::Variant { path, fields } => PatternKind::Variant {
+            path,
+            fields: fields.map(|items| items.into_iter().map(|p| f.fold_pattern(p)).collect()),
+        },
+        kind @ (PatternKind::Wildcard | PatternKind::Literal(_) | PatternKind::Binding(_)) => kind,
+    };
+    Pattern::new(kind, pattern.span)
+}
+
+pub fn walk_type_expr<F: Fold>(f: &mut F, ty: TypeExpr) -> TypeExpr {
+    let kind = match ty.kind {
+        TypeExprKind::Array(inner) => TypeExprKind::Array(Box::new(f.fold_type_expr(*inner))),
+        TypeExprKind::Optional(inner) => {
+            TypeExprKind::Optional(Box::new(f.fold_type_expr(*inner)))
+        }
+        TypeExprKind::Map(key, value) => TypeExprKind::Map(
+            Box::new(f.fold_type_expr(*key)),
+            Box::new(f.fold_type_expr(*value)),
+        ),
+        TypeExprKind::Tuple(items) => {
+            TypeExprKind::Tuple(items.into_iter().map(|t| f.fold_type_expr(t)).collect())
+        }
+        TypeExprKind::Function { params, ret } => TypeExprKind::Function {
+            params: params.into_iter().map(|t| f.fold_type_expr(t)).collect(),
+            ret: ret.map(|t| Box::new(f.fold_type_expr(*t))),
+        },
+        kind @ (TypeExprKind::Primitive(_) | TypeExprKind::Named(_)) => kind,
+    };
+    TypeExpr::new(kind, ty.span)
+}
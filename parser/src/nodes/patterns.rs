@@ -0,0 +1,91 @@
+use crate::nodes::exprs::LiteralValue;
+use lexer::token::SourceSpan;
+use std::fmt::{self, Display};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatternKind {
+    // `_`, matches anything and binds nothing
+    Wildcard,
+    // `1`, `-2.5`, `"a"`, `true`
+    Literal(LiteralValue),
+    // `x`, matches anything and binds it to the name
+    Binding(String),
+    // `(a, b)`, `()` matches the unit value
+    Tuple(Vec<Pattern>),
+    // `Shape.Circle(r)` or `Circle(r)`, `fields` is `None` for a variant
+    // written without parentheses such as `Color.Red`
+    Variant {
+        path: Vec<String>,
+        fields: Option<Vec<Pattern>>,
+    },
+}
+
+/// A pattern on the left side of a `match` arm.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    pub kind: PatternKind,
+    pub span: SourceSpan,
+}
+
+impl Pattern {
+    pub fn new(kind: PatternKind, span: SourceSpan) -> Self {
+        Pattern { kind, span }
+    }
+
+    /// Names bound by the pattern, in source order.
+    pub fn bindings(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        self.collect_bindings(&mut names);
+        names
+    }
+
+    fn collect_bindings<'p>(&'p self, names: &mut Vec<&'p str>) {
+        match &self.kind {
+            PatternKind::Binding(name) => names.push(name),
+            PatternKind::Tuple(items) => items.iter().for_each(|p| p.collect_bindings(names)),
+            PatternKind::Variant {
+                fields: Some(fields),
+                ..
+            } => fields.iter().for_each(|p| p.collect_bindings(names)),
+            _ => {}
+        }
+    }
+}
+
+/// Writes `items` separated by commas.
+fn write_list(f: &mut fmt::Formatter<'_>, items: &[Pattern]) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            PatternKind::Wildcard => write!(f, "_"),
+            PatternKind::Literal(value) => write!(f, "{}", value),
+            PatternKind::Binding(name) => write!(f, "{}", name),
+            PatternKind::Tuple(items) => {
+                write!(f, "(")?;
+                write_list(f, items)?;
+                if items.len() == 1 {
+                    write!(f, ",")?;
+                }
+                write!(f, ")")
+            }
+            PatternKind::Variant { path, fields } => {
+                write!(f, "{}", path.join("."))?;
+                if let Some(fields) = fields {
+                    write!(f, "(")?;
+                    write_list(f, fields)?;
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use crate::nodes::ast::Ast;
+use crate::nodes::decls::{EnumDecl, FuncDecl, ImportDecl, StructDecl};
+use crate::nodes::exprs::ExprId;
+use crate::nodes::type_exprs::TypeExpr;
+use lexer::token::SourceSpan;
+use std::fmt::{self, Display};
+
+/// Index of a statement in the `Ast` arena, its span lives in a side table
+/// under the same index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StmtId(pub(crate) u32);
+
+impl StmtId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// A braced list of statements, `{ ... }`. With `tail` set the block
+/// evaluates to its last statement, which is then an expression written
+/// without its `;`, or an `if`, `match` or block statement, as in
+/// `{ let y = x * 2; y + 1 }`. Otherwise it evaluates to `()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub stmts: Vec<StmtId>,
+    pub tail: bool,
+    pub span: SourceSpan,
+}
+
+/// Every kind of statement. Statements display as one-line source through
+/// `Ast::display_stmt`, with expressions fully parenthesized.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StmtKind {
+    // an expression evaluated for its effect, `f(x);`
+    Expr(ExprId),
+    // a `let` (immutable) or `var` (mutable) declaration, with an optional
+    // type annotation
+    Let {
+        mutable: bool,
+        name: String,
+        name_span: SourceSpan,
+        ty: Option<TypeExpr>,
+        value: Option<ExprId>,
+    },
+    // an assignment to a place expression, `x = 1;` or `p.x = 1;`
+    Assign {
+        target: ExprId,
+        value: ExprId,
+    },
+    Block(Block),
+    // `if cond { ... }`, optionally followed by `else { ... }` or `else if`
+    If {
+        cond: ExprId,
+        then_branch: Block,
+        // a `Block` or, for `else if`, another `If`
+        else_branch: Option<StmtId>,
+    },
+    While {
+        cond: ExprId,
+        body: Block,
+    },
+    // `for name in iterable { ... }`, where a range yields its integers,
+    // an array its elements, a map its keys in source order and a string
+    // its characters
+    For {
+        var: String,
+        var_span: SourceSpan,
+        iterable: ExprId,
+        body: Block,
+    },
+    Return(Option<ExprId>),
+    Break,
+    Continue,
+    // declarations are rare and large, boxing them keeps every other
+    // statement small
+    Func(Box<FuncDecl>),
+    Struct(Box<StructDecl>),
+    Enum(Box<EnumDecl>),
+    // only at the top level of a file
+    Import(Box<ImportDecl>),
+    // placeholder for a statement that failed to parse, the error itself is
+    // recorded by the parser and the span covers the tokens skipped to recover
+    Error,
+}
+
+/// The statements of a whole source file, together with the arena that
+/// holds their nodes.
+#[derive(Debug, Clone, Default)]
+pub struct Program {
+    pub ast: Ast,
+    pub stmts: Vec<StmtId>,
+}
+
+impl Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, stmt) in self.stmts.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", self.ast.display_stmt(*stmt))?;
+        }
+        Ok(())
+    }
+}
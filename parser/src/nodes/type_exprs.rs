@@ -0,0 +1,129 @@
+use lexer::token::SourceSpan;
+use std::fmt::{self, Display};
+
+/// Built-in types that have a name in the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Primitive {
+    Int,
+    I16,
+    I32,
+    I64,
+    I128,
+    Float,
+    F32,
+    F64,
+    String,
+    Bool,
+}
+
+impl Primitive {
+    pub fn from_name(name: &str) -> Option<Primitive> {
+        match name {
+            "int" => Some(Primitive::Int),
+            "i16" => Some(Primitive::I16),
+            "i32" => Some(Primitive::I32),
+            "i64" => Some(Primitive::I64),
+            "i128" => Some(Primitive::I128),
+            "float" => Some(Primitive::Float),
+            "f32" => Some(Primitive::F32),
+            "f64" => Some(Primitive::F64),
+            "string" => Some(Primitive::String),
+            "bool" => Some(Primitive::Bool),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Primitive::Int => "int",
+            Primitive::I16 => "i16",
+            Primitive::I32 => "i32",
+            Primitive::I64 => "i64",
+            Primitive::I128 => "i128",
+            Primitive::Float => "float",
+            Primitive::F32 => "f32",
+            Primitive::F64 => "f64",
+            Primitive::String => "string",
+            Primitive::Bool => "bool",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeExprKind {
+    // int, f64, string, bool, ...
+    Primitive(Primitive),
+    // a user type such as `Point`
+    Named(String),
+    // [T]
+    Array(Box<TypeExpr>),
+    // {K: V}
+    Map(Box<TypeExpr>, Box<TypeExpr>),
+    // (A, B), `()` is the unit type
+    Tuple(Vec<TypeExpr>),
+    // fn(A, B) -> C, without `->` the function returns unit
+    Function {
+        params: Vec<TypeExpr>,
+        ret: Option<Box<TypeExpr>>,
+    },
+    // T?
+    Optional(Box<TypeExpr>),
+}
+
+/// A type written in the source, as used in declarations, parameters,
+/// return types and casts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeExpr {
+    pub kind: TypeExprKind,
+    pub span: SourceSpan,
+}
+
+impl TypeExpr {
+    pub fn new(kind: TypeExprKind, span: SourceSpan) -> Self {
+        TypeExpr { kind, span }
+    }
+}
+
+/// Writes `items` separated by commas.
+fn write_list(f: &mut fmt::Formatter<'_>, items: &[TypeExpr]) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+impl Display for TypeExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            TypeExprKind::Primitive(p) => write!(f, "{}", p.name()),
+            TypeExprKind::Named(name) => write!(f, "{}", name),
+            TypeExprKind::Array(elem) => write!(f, "[{}]", elem),
+            TypeExprKind::Map(key, value) => write!(f, "{{{}: {}}}", key, value),
+            TypeExprKind::Tuple(items) => {
+                write!(f, "(")?;
+                write_list(f, items)?;
+                if items.len() == 1 {
+                    write!(f, ",")?;
+                }
+                write!(f, ")")
+            }
+            TypeExprKind::Function { params, ret } => {
+                write!(f, "fn(")?;
+                write_list(f, params)?;
+                write!(f, ")")?;
+                if let Some(ret) = ret {
+                    write!(f, " -> {}", ret)?;
+                }
+                Ok(())
+            }
+            // `fn() -> int?` would make the return type optional instead
+            TypeExprKind::Optional(inner) if matches!(inner.kind, TypeExprKind::Function { .. }) => {
+                write!(f, "({})?", inner)
+            }
+            TypeExprKind::Optional(inner) => write!(f, "{}?", inner),
+        }
+    }
+}
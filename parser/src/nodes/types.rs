@@ -1,11 +1,23 @@
+use std::fmt::{self, Display};
 
-#[derive(Debug, Clone, PartialEq)]
+use crate::nodes::type_exprs::Primitive;
+
+/// The type of a value, as `typeck` records it for an expression with
+/// `Ast::set_expr_type`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Type {
-    Int16(i16),
-    Int32(i32),
-    Int64(i64),
-    Int128(i128),
-    Float32(f32),
-    Float64(f64),
-    String(String),
+    // int, f64, string, bool, ...
+    Primitive(Primitive),
+    // `()`, the value of a block without a tail and of an `if` without
+    // `else`
+    Unit,
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Primitive(p) => write!(f, "{}", p.name()),
+            Type::Unit => write!(f, "()"),
+        }
+    }
 }
@@ -1,7 +1,274 @@
-use crate::nodes::types::Type;
-use crate::nodes::exprs::{LiteralExpr, BinaryExpr};
+//! Read-only traversal of a program.
+//!
+//! Every `visit_*` method of `Visitor` defaults to the `walk_*` function of
+//! the same name, which visits the children of the node. A pass overrides
+//! the methods of the nodes it cares about and calls `walk_*` from them to
+//! keep descending.
 
-pub trait TypeVisitor {
-    fn visit_literal(&mut self, expr: &LiteralExpr) -> Type;
-    fn visit_binary(&mut self, expr: &BinaryExpr) -> Type;
+use crate::nodes::ast::Ast;
+use crate::nodes::chain;
+use crate::nodes::decls::{EnumDecl, FuncDecl, StructDecl};
+use crate::nodes::exprs::{ArmBody, ExprId, ExprKind, LambdaBody, MatchArm};
+use crate::nodes::patterns::{Pattern, PatternKind};
+use crate::nodes::stmts::{Block, Program, StmtId, StmtKind};
+use crate::nodes::type_exprs::{TypeExpr, TypeExprKind};
+
+pub trait Visitor: Sized {
+    fn visit_program(&mut self, program: &Program) {
+        walk_program(self, program);
+    }
+
+    fn visit_stmt(&mut self, ast: &Ast, stmt: StmtId) {
+        walk_stmt(self, ast, stmt);
+    }
+
+    fn visit_block(&mut self, ast: &Ast, block: &Block) {
+        walk_block(self, ast, block);
+    }
+
+    fn visit_expr(&mut self, ast: &Ast, expr: ExprId) {
+        walk_expr(self, ast, expr);
+    }
+
+    fn visit_match_arm(&mut self, ast: &Ast, arm: &MatchArm) {
+        walk_match_arm(self, ast, arm);
+    }
+
+    fn visit_func_decl(&mut self, ast: &Ast, decl: &FuncDecl) {
+        walk_func_decl(self, ast, decl);
+    }
+
+    fn visit_struct_decl(&mut self, decl: &StructDecl) {
+        walk_struct_decl(self, decl);
+    }
+
+    fn visit_enum_decl(&mut self, decl: &EnumDecl) {
+        walk_enum_decl(self, decl);
+    }
+
+    fn visit_pattern(&mut self, pattern: &Pattern) {
+        walk_pattern(self, pattern);
+    }
+
+    fn visit_type_expr(&mut self, ty: &TypeExpr) {
+        walk_type_expr(self, ty);
+    }
+}
+
+pub fn walk_program<V: Visitor>(v: &mut V, program: &Program) {
+    for stmt in &program.stmts {
+        v.visit_stmt(&program.ast, *stmt);
+    }
+}
+
+pub fn walk_stmt<V: Visitor>(v: &mut V, ast: &Ast, stmt: StmtId) {
+    match ast.stmt(stmt) {
+        StmtKind::Expr(expr) => v.visit_expr(ast, *expr),
+        StmtKind::Let { ty, value, .. } => {
+            if let Some(ty) = ty {
+                v.visit_type_expr(ty);
+            }
+            if let Some(value) = value {
+                v.visit_expr(ast, *value);
+            }
+        }
+        StmtKind::Assign { target, value } => {
+            v.visit_expr(ast, *target);
+            v.visit_expr(ast, *value);
+        }
+        StmtKind::Block(block) => v.visit_block(ast, block),
+        StmtKind::If {
+            cond,
+            then_branch,
+            else_branch,
+        } => {
+            v.visit_expr(ast, *cond);
+            v.visit_block(ast, then_branch);
+            if let Some(else_branch) = else_branch {
+                v.visit_stmt(ast, *else_branch);
+            }
+        }
+        StmtKind::While { cond, body } => {
+            v.visit_expr(ast, *cond);
+            v.visit_block(ast, body);
+        }
+        StmtKind::For { iterable, body, .. } => {
+            v.visit_expr(ast, *iterable);
+            v.visit_block(ast, body);
+        }
+        StmtKind::Return(value) => {
+            if let Some(value) = value {
+                v.visit_expr(ast, *value);
+            }
+        }
+        StmtKind::Func(decl) => v.visit_func_decl(ast, decl),
+        StmtKind::Struct(decl) => v.visit_struct_decl(decl),
+        StmtKind::Enum(decl) => v.visit_enum_decl(decl),
+        StmtKind::Break | StmtKind::Continue | StmtKind::Import(_) | StmtKind::Error => {}
+    }
+}
+
+pub fn walk_block<V: Visitor>(v: &mut V, ast: &Ast, block: &Block) {
+    for stmt in &block.stmts {
+        v.visit_stmt(ast, *stmt);
+    }
+}
+
+/// Visits the children of an expression. The operand of a link of a chain
+/// such as `a + b + c` is walked in a loop, see `nodes::chain`.
+pub fn walk_expr<V: Visitor>(v: &mut V, ast: &Ast, expr: ExprId) {
+    if ast.expr(expr).chained_operand().is_some() && chain::walked_operand(ast, expr).is_none() {
+        walk_chain(v, ast, expr);
+    }
+    match ast.expr(expr) {
+        ExprKind::Binary { right, .. } => v.visit_expr(ast, *right),
+        ExprKind::Unary { expr, .. } | ExprKind::Grouping(expr) => v.visit_expr(ast, *expr),
+        ExprKind::Call { args, .. } => {
+            for arg in args {
+                v.visit_expr(ast, *arg);
+            }
+        }
+        ExprKind::Index { index, .. } => v.visit_expr(ast, *index),
+        ExprKind::Member { .. } => {}
+        ExprKind::Cast { ty, .. } => v.visit_type_expr(ty),
+        ExprKind::Array(elements) | ExprKind::Tuple(elements) => {
+            for element in elements {
+                v.visit_expr(ast, *element);
+            }
+        }
+        ExprKind::Map(entries) => {
+            for (key, value) in entries {
+                v.visit_expr(ast, *key);
+                v.visit_expr(ast, *value);
+            }
+        }
+        ExprKind::Struct { fields, .. } => {
+            for field in fields {
+                v.visit_expr(ast, field.value);
+            }
+        }
+        ExprKind::Match { scrutinee, arms } => {
+            v.visit_expr(ast, *scrutinee);
+            for arm in arms {
+                v.visit_match_arm(ast, arm);
+            }
+        }
+        ExprKind::If {
+            cond,
+            then_branch,
+            else_branch,
+        } => {
+            v.visit_expr(ast, *cond);
+            v.visit_block(ast, then_branch);
+            if let Some(else_branch) = else_branch {
+                v.visit_expr(ast, *else_branch);
+            }
+        }
+        ExprKind::Block(block) => v.visit_block(ast, block),
+        ExprKind::Lambda(lambda) => {
+            for param in &lambda.params {
+                if let Some(ty) = &param.type_annotation {
+                    v.visit_type_expr(ty);
+                }
+            }
+            if let Some(ty) = &lambda.return_type {
+                v.visit_type_expr(ty);
+            }
+            match &lambda.body {
+                LambdaBody::Expr(expr) => v.visit_expr(ast, *expr),
+                LambdaBody::Block(block) => v.visit_block(ast, block),
+            }
+        }
+        ExprKind::Literal(_) | ExprKind::Ident(_) | ExprKind::Error => {}
+    }
+}
+
+/// Visits the operand a chain starts with, then every link below `expr`
+/// from the innermost out.
+fn walk_chain<V: Visitor>(v: &mut V, ast: &Ast, expr: ExprId) {
+    let chain = ast.chain(expr);
+    let (first, links) = chain.split_last().expect("a chain has an operand");
+    v.visit_expr(ast, *first);
+    for link in links[1..].iter().rev() {
+        chain::visit_link(ast, *link, *link, || v.visit_expr(ast, *link));
+    }
+}
+
+pub fn walk_match_arm<V: Visitor>(v: &mut V, ast: &Ast, arm: &MatchArm) {
+    v.visit_pattern(&arm.pattern);
+    if let Some(guard) = arm.guard {
+        v.visit_expr(ast, guard);
+    }
+    match &arm.body {
+        ArmBody::Expr(expr) => v.visit_expr(ast, *expr),
+        ArmBody::Block(block) => v.visit_block(ast, block),
+    }
+}
+
+pub fn walk_func_decl<V: Visitor>(v: &mut V, ast: &Ast, decl: &FuncDecl) {
+    for param in &decl.params {
+        if let Some(ty) = &param.type_annotation {
+            v.visit_type_expr(ty);
+        }
+    }
+    if let Some(ty) = &decl.return_type {
+        v.visit_type_expr(ty);
+    }
+    v.visit_block(ast, &decl.body);
+}
+
+pub fn walk_struct_decl<V: Visitor>(v: &mut V, decl: &StructDecl) {
+    for field in &decl.fields {
+        v.visit_type_expr(&field.ty);
+    }
+}
+
+pub fn walk_enum_decl<V: Visitor>(v: &mut V, decl: &EnumDecl) {
+    for variant in &decl.variants {
+        for field in &variant.fields {
+            v.visit_type_expr(field);
+        }
+    }
+}
+
+pub fn walk_pattern<V: Visitor>(v: &mut V, pattern: &Pattern) {
+    match &pattern.kind {
+        PatternKind::Tuple(items)
+        | PatternKind::Variant {
+            fields: Some(items),
+            ..
+        } => {
+            for item in items {
+                v.visit_pattern(item);
+            }
+        }
+        PatternKind::Wildcard
+        | PatternKind::Literal(_)
+        | PatternKind::Binding(_)
+        | PatternKind::Variant { fields: None, .. } => {}
+    }
+}
+
+pub fn walk_type_expr<V: Visitor>(v: &mut V, ty: &TypeExpr) {
+    match &ty.kind {
+        TypeExprKind::Array(inner) | TypeExprKind::Optional(inner) => v.visit_type_expr(inner),
+        TypeExprKind::Map(key, value) => {
+            v.visit_type_expr(key);
+            v.visit_type_expr(value);
+        }
+        TypeExprKind::Tuple(items) => {
+            for item in items {
+                v.visit_type_expr(item);
+            }
+        }
+        TypeExprKind::Function { params, ret } => {
+            for param in params {
+                v.visit_type_expr(param);
+            }
+            if let Some(ret) = ret {
+                v.visit_type_expr(ret);
+            }
+        }
+        TypeExprKind::Primitive(_) | TypeExprKind::Named(_) => {}
+    }
 }
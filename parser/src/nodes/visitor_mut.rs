@@ -0,0 +1,276 @@
+//! In-place traversal of a program, the mutable twin of `visitor`.
+//!
+//! While the children of a node are visited, the node itself is taken out
+//! of the arena and reads as `Error`, so that both it and the arena can be
+//! borrowed mutably. It is put back once its children are done.
+
+use crate::nodes::ast::Ast;
+use crate::nodes::chain;
+use crate::nodes::decls::{EnumDecl, FuncDecl, StructDecl};
+use crate::nodes::exprs::{ArmBody, ExprId, ExprKind, LambdaBody, MatchArm};
+use crate::nodes::patterns::{Pattern, PatternKind};
+use crate::nodes::stmts::{Block, Program, StmtId, StmtKind};
+use crate::nodes::type_exprs::{TypeExpr, TypeExprKind};
+
+pub trait VisitorMut: Sized {
+    fn visit_program_mut(&mut self, program: &mut Program) {
+        walk_program_mut(self, program);
+    }
+
+    fn visit_stmt_mut(&mut self, ast: &mut Ast, stmt: StmtId) {
+        walk_stmt_mut(self, ast, stmt);
+    }
+
+    fn visit_block_mut(&mut self, ast: &mut Ast, block: &mut Block) {
+        walk_block_mut(self, ast, block);
+    }
+
+    fn visit_expr_mut(&mut self, ast: &mut Ast, expr: ExprId) {
+        walk_expr_mut(self, ast, expr);
+    }
+
+    fn visit_match_arm_mut(&mut self, ast: &mut Ast, arm: &mut MatchArm) {
+        walk_match_arm_mut(self, ast, arm);
+    }
+
+    fn visit_func_decl_mut(&mut self, ast: &mut Ast, decl: &mut FuncDecl) {
+        walk_func_decl_mut(self, ast, decl);
+    }
+
+    fn visit_struct_decl_mut(&mut self, decl: &mut StructDecl) {
+        walk_struct_decl_mut(self, decl);
+    }
+
+    fn visit_enum_decl_mut(&mut self, decl: &mut EnumDecl) {
+        walk_enum_decl_mut(self, decl);
+    }
+
+    fn visit_pattern_mut(&mut self, pattern: &mut Pattern) {
+        walk_pattern_mut(self, pattern);
+    }
+
+    fn visit_type_expr_mut(&mut self, ty: &mut TypeExpr) {
+        walk_type_expr_mut(self, ty);
+    }
+}
+
+pub fn walk_program_mut<V: VisitorMut>(v: &mut V, program: &mut Program) {
+    for stmt in &program.stmts {
+        v.visit_stmt_mut(&mut program.ast, *stmt);
+    }
+}
+
+pub fn walk_stmt_mut<V: VisitorMut>(v: &mut V, ast: &mut Ast, stmt: StmtId) {
+    let mut kind = std::mem::replace(ast.stmt_mut(stmt), StmtKind::Error);
+    match &mut kind {
+        StmtKind::Expr(expr) => v.visit_expr_mut(ast, *expr),
+        StmtKind::Let { ty, value, .. } => {
+            if let Some(ty) = ty {
+                v.visit_type_expr_mut(ty);
+            }
+            if let Some(value) = value {
+                v.visit_expr_mut(ast, *value);
+            }
+        }
+        StmtKind::Assign { target, value } => {
+            v.visit_expr_mut(ast, *target);
+            v.visit_expr_mut(ast, *value);
+        }
+        StmtKind::Block(block) => v.visit_block_mut(ast, block),
+        StmtKind::If {
+            cond,
+            then_branch,
+            else_branch,
+        } => {
+            v.visit_expr_mut(ast, *cond);
+            v.visit_block_mut(ast, then_branch);
+            if let Some(else_branch) = else_branch {
+                v.visit_stmt_mut(ast, *else_branch);
+            }
+        }
+        StmtKind::While { cond, body } => {
+            v.visit_expr_mut(ast, *cond);
+            v.visit_block_mut(ast, body);
+        }
+        StmtKind::For { iterable, body, .. } => {
+            v.visit_expr_mut(ast, *iterable);
+            v.visit_block_mut(ast, body);
+        }
+        StmtKind::Return(value) => {
+            if let Some(value) = value {
+                v.visit_expr_mut(ast, *value);
+            }
+        }
+        StmtKind::Func(decl) => v.visit_func_decl_mut(ast, decl),
+        StmtKind::Struct(decl) => v.visit_struct_decl_mut(decl),
+        StmtKind::Enum(decl) => v.visit_enum_decl_mut(decl),
+        StmtKind::Break | StmtKind::Continue | StmtKind::Import(_) | StmtKind::Error => {}
+    }
+    *ast.stmt_mut(stmt) = kind;
+}
+
+pub fn walk_block_mut<V: VisitorMut>(v: &mut V, ast: &mut Ast, block: &mut Block) {
+    for stmt in &block.stmts {
+        v.visit_stmt_mut(ast, *stmt);
+    }
+}
+
+/// Visits the children of an expression. The operand of a link of a chain
+/// such as `a + b + c` is walked in a loop, see `nodes::chain`.
+pub fn walk_expr_mut<V: VisitorMut>(v: &mut V, ast: &mut Ast, expr: ExprId) {
+    let chain = if ast.expr(expr).chained_operand().is_some()
+        && chain::walked_operand(ast, expr).is_none()
+    {
+        ast.chain(expr)
+    } else {
+        Vec::new()
+    };
+    let mut kind = std::mem::replace(ast.expr_mut(expr), ExprKind::Error);
+    if let Some((first, links)) = chain.split_last() {
+        v.visit_expr_mut(ast, *first);
+        for link in links[1..].iter().rev() {
+            chain::visit_link(ast, *link, *link, || v.visit_expr_mut(ast, *link));
+        }
+    }
+    match &mut kind {
+        ExprKind::Binary { right, .. } => v.visit_expr_mut(ast, *right),
+        ExprKind::Unary { expr, .. } | ExprKind::Grouping(expr) => v.visit_expr_mut(ast, *expr),
+        ExprKind::Call { args, .. } => {
+            for arg in args {
+                v.visit_expr_mut(ast, *arg);
+            }
+        }
+        ExprKind::Index { index, .. } => v.visit_expr_mut(ast, *index),
+        ExprKind::Member { .. } => {}
+        ExprKind::Cast { ty, .. } => v.visit_type_expr_mut(ty),
+        ExprKind::Array(elements) | ExprKind::Tuple(elements) => {
+            for element in elements {
+                v.visit_expr_mut(ast, *element);
+            }
+        }
+        ExprKind::Map(entries) => {
+            for (key, value) in entries {
+                v.visit_expr_mut(ast, *key);
+                v.visit_expr_mut(ast, *value);
+            }
+        }
+        ExprKind::Struct { fields, .. } => {
+            for field in fields {
+                v.visit_expr_mut(ast, field.value);
+            }
+        }
+        ExprKind::Match { scrutinee, arms } => {
+            v.visit_expr_mut(ast, *scrutinee);
+            for arm in arms {
+                v.visit_match_arm_mut(ast, arm);
+            }
+        }
+        ExprKind::If {
+            cond,
+            then_branch,
+            else_branch,
+        } => {
+            v.visit_expr_mut(ast, *cond);
+            v.visit_block_mut(ast, then_branch);
+            if let Some(else_branch) = else_branch {
+                v.visit_expr_mut(ast, *else_branch);
+            }
+        }
+        ExprKind::Block(block) => v.visit_block_mut(ast, block),
+        ExprKind::Lambda(lambda) => {
+            for param in &mut lambda.params {
+                if let Some(ty) = &mut param.type_annotation {
+                    v.visit_type_expr_mut(ty);
+                }
+            }
+            if let Some(ty) = &mut lambda.return_type {
+                v.visit_type_expr_mut(ty);
+            }
+            match &mut lambda.body {
+                LambdaBody::Expr(expr) => v.visit_expr_mut(ast, *expr),
+                LambdaBody::Block(block) => v.visit_block_mut(ast, block),
+            }
+        }
+        ExprKind::Literal(_) | ExprKind::Ident(_) | ExprKind::Error => {}
+    }
+    *ast.expr_mut(expr) = kind;
+}
+
+pub fn walk_match_arm_mut<V: VisitorMut>(v: &mut V, ast: &mut Ast, arm: &mut MatchArm) {
+    v.visit_pattern_mut(&mut arm.pattern);
+    if let Some(guard) = arm.guard {
+        v.visit_expr_mut(ast, guard);
+    }
+    match &mut arm.body {
+        ArmBody::Expr(expr) => v.visit_expr_mut(ast, *expr),
+        ArmBody::Block(block) => v.visit_block_mut(ast, block),
+    }
+}
+
+pub fn walk_func_decl_mut<V: VisitorMut>(v: &mut V, ast: &mut Ast, decl: &mut FuncDecl) {
+    for param in &mut decl.params {
+        if let Some(ty) = &mut param.type_annotation {
+            v.visit_type_expr_mut(ty);
+        }
+    }
+    if let Some(ty) = &mut decl.return_type {
+        v.visit_type_expr_mut(ty);
+    }
+    v.visit_block_mut(ast, &mut decl.body);
+}
+
+pub fn walk_struct_decl_mut<V: VisitorMut>(v: &mut V, decl: &mut StructDecl) {
+    for field in &mut decl.fields {
+        v.visit_type_expr_mut(&mut field.ty);
+    }
+}
+
+pub fn walk_enum_decl_mut<V: VisitorMut>(v: &mut V, decl: &mut EnumDecl) {
+    for variant in &mut decl.variants {
+        for field in &mut variant.fields {
+            v.visit_type_expr_mut(field);
+        }
+    }
+}
+
+pub fn walk_pattern_mut<V: VisitorMut>(v: &mut V, pattern: &mut Pattern) {
+    match &mut pattern.kind {
+        PatternKind::Tuple(items)
+        | PatternKind::Variant {
+            fields: Some(items),
+            ..
+        } => {
+            for item in items {
+                v.visit_pattern_mut(item);
+            }
+        }
+        PatternKind::Wildcard
+        | PatternKind::Literal(_)
+        | PatternKind::Binding(_)
+        | PatternKind::Variant { fields: None, .. } => {}
+    }
+}
+
+pub fn walk_type_expr_mut<V: VisitorMut>(v: &mut V, ty: &mut TypeExpr) {
+    match &mut ty.kind {
+        TypeExprKind::Array(inner) | TypeExprKind::Optional(inner) => v.visit_type_expr_mut(inner),
+        TypeExprKind::Map(key, value) => {
+            v.visit_type_expr_mut(key);
+            v.visit_type_expr_mut(value);
+        }
+        TypeExprKind::Tuple(items) => {
+            for item in items {
+                v.visit_type_expr_mut(item);
+            }
+        }
+        TypeExprKind::Function { params, ret } => {
+            for param in params {
+                v.visit_type_expr_mut(param);
+            }
+            if let Some(ret) = ret {
+                v.visit_type_expr_mut(ret);
+            }
+        }
+        TypeExprKind::Primitive(_) | TypeExprKind::Named(_) => {}
+    }
+}
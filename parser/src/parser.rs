@@ -1,6 +1,12 @@
 pub mod engine;
 pub mod lookups;
 pub mod errors;
+pub mod events;
+pub mod trace;
+pub mod decls;
 pub mod exprs;
+pub mod patterns;
+pub mod stmts;
+pub mod types;
 
 
@@ -0,0 +1,283 @@
+use crate::nodes::decls::{EnumDecl, FieldDecl, FuncDecl, ImportDecl, ImportItem, ImportSource};
+use crate::nodes::decls::{Param, StructDecl, VariantDecl};
+use crate::nodes::stmts::{StmtId, StmtKind};
+use crate::parser::engine::Parser;
+use crate::parser::errors::ParserError;
+use lexer::token::Token;
+use lexer::token_type::TokenKind;
+
+/// Declaration parsing. Declarations are statements, so they are reached
+/// through the statement table in `lookups`.
+impl<'a> Parser<'a> {
+    /// Parses `func name(a: int, b) -> int { ... }`.
+    pub fn parse_func_decl(p: &mut Parser<'a>) -> Result<StmtId, ParserError<'a>> {
+        Self::func_decl(p, None)
+    }
+
+    /// Parses a function declaration, the `pub` before it already consumed
+    /// when there is one.
+    fn func_decl(p: &mut Parser<'a>, public: Option<Token<'a>>) -> Result<StmtId, ParserError<'a>> {
+        let keyword = p.expect(TokenKind::FUNCTION)?;
+        let keyword = public.unwrap_or(keyword);
+        let name = p.expect(TokenKind::IDENTIFIER)?;
+
+        p.expect(TokenKind::LPAREN)?;
+        let (params, close) = Self::parse_params(p, TokenKind::RPAREN)?;
+
+        let return_type = if p.at(TokenKind::ARROW) {
+            p.advance();
+            Some(Self::parse_type_expr(p)?)
+        } else {
+            None
+        };
+        let signature_end = return_type.as_ref().map_or(close.span, |ty| ty.span);
+        let signature_span = keyword.span.combine(&signature_end);
+
+        let body = Self::parse_block(p)?;
+        let span = keyword.span.combine(&body.span);
+        let decl = FuncDecl {
+            public: public.is_some(),
+            name: name.value.to_string(),
+            name_span: name.span,
+            params,
+            return_type,
+            body,
+            signature_span,
+            span,
+        };
+        Ok(p.alloc_stmt(StmtKind::Func(Box::new(decl)), span))
+    }
+
+    /// Parses the parameters after the opening `(` or `|` up to and
+    /// including `close`, which is returned with them.
+    pub fn parse_params(
+        p: &mut Parser<'a>,
+        close: TokenKind,
+    ) -> Result<(Vec<Param>, Token<'a>), ParserError<'a>> {
+        let mut params = Vec::new();
+        while !p.at(close) {
+            params.push(Self::parse_param(p)?);
+            match p.get_current_token() {
+                Some(t) if t.kind == TokenKind::COMMA => p.advance(),
+                Some(t) if t.kind == close => {}
+                _ => return Err(p.unexpected(&[TokenKind::COMMA, close])),
+            }
+        }
+        let close = p.expect(close)?;
+        Ok((params, close))
+    }
+
+    /// Parses a parameter, `name` or `name: type`.
+    pub fn parse_param(p: &mut Parser<'a>) -> Result<Param, ParserError<'a>> {
+        let name = p.expect(TokenKind::IDENTIFIER)?;
+        let type_annotation = if p.at(TokenKind::COLON) {
+            p.advance();
+            Some(Self::parse_type_expr(p)?)
+        } else {
+            None
+        };
+        let end = type_annotation.as_ref().map_or(name.span, |ty| ty.span);
+        Ok(Param {
+            name: name.value.to_string(),
+            name_span: name.span,
+            type_annotation,
+            span: name.span.combine(&end),
+        })
+    }
+
+
+    /// Parses `struct Name { field: type, ... }`, a trailing comma is allowed.
+    pub fn parse_struct_decl(p: &mut Parser<'a>) -> Result<StmtId, ParserError<'a>> {
+        Self::struct_decl(p, None)
+    }
+
+    fn struct_decl(p: &mut Parser<'a>, public: Option<Token<'a>>) -> Result<StmtId, ParserError<'a>> {
+        let keyword = p.expect(TokenKind::STRUCT)?;
+        let keyword = public.unwrap_or(keyword);
+        let name = p.expect(TokenKind::IDENTIFIER)?;
+        let open = p.expect(TokenKind::LBRACE)?;
+        let mut fields: Vec<FieldDecl> = Vec::new();
+        while !p.at(TokenKind::RBRACE) {
+            if p.at_end() {
+                return Err(ParserError::UnclosedDelimiter {
+                    open,
+                    close: TokenKind::RBRACE,
+                });
+            }
+            let field = p.expect(TokenKind::IDENTIFIER)?;
+            if fields.iter().any(|f| f.name == field.value) {
+                return Err(ParserError::DuplicateField {
+                    name: field.value.to_string(),
+                    span: field.span,
+                });
+            }
+            p.expect(TokenKind::COLON)?;
+            let ty = Self::parse_type_expr(p)?;
+            fields.push(FieldDecl {
+                name: field.value.to_string(),
+                name_span: field.span,
+                span: field.span.combine(&ty.span),
+                ty,
+            });
+            match p.get_current_token() {
+                Some(t) if t.kind == TokenKind::COMMA => p.advance(),
+                Some(t) if t.kind == TokenKind::RBRACE => {}
+                _ => return Err(p.unexpected(&[TokenKind::COMMA, TokenKind::RBRACE])),
+            }
+        }
+        let close = p.expect(TokenKind::RBRACE)?;
+        if let Some(scope) = p.struct_scopes.last_mut() {
+            let names = fields.iter().map(|f| f.name.clone()).collect();
+            scope
+                .fields
+                .entry(name.value.to_string())
+                .and_modify(|declared| *declared = None)
+                .or_insert(Some(names));
+        }
+        let span = keyword.span.combine(&close.span);
+        let decl = StructDecl {
+            public: public.is_some(),
+            name: name.value.to_string(),
+            name_span: name.span,
+            fields,
+            span,
+        };
+        Ok(p.alloc_stmt(StmtKind::Struct(Box::new(decl)), span))
+    }
+
+    /// Parses `enum Name { Variant, Variant(type, ...), ... }`, a trailing
+    /// comma is allowed.
+    pub fn parse_enum_decl(p: &mut Parser<'a>) -> Result<StmtId, ParserError<'a>> {
+        Self::enum_decl(p, None)
+    }
+
+    fn enum_decl(p: &mut Parser<'a>, public: Option<Token<'a>>) -> Result<StmtId, ParserError<'a>> {
+        let keyword = p.expect(TokenKind::ENUM)?;
+        let keyword = public.unwrap_or(keyword);
+        let name = p.expect(TokenKind::IDENTIFIER)?;
+        let open = p.expect(TokenKind::LBRACE)?;
+        let mut variants = Vec::new();
+        while !p.at(TokenKind::RBRACE) {
+            if p.at_end() {
+                return Err(ParserError::UnclosedDelimiter {
+                    open,
+                    close: TokenKind::RBRACE,
+                });
+            }
+            let variant = p.expect(TokenKind::IDENTIFIER)?;
+            let (fields, span) = if p.at(TokenKind::LPAREN) {
+                let (fields, _, span) = Self::parse_type_list(p)?;
+                (fields, variant.span.combine(&span))
+            } else {
+                (Vec::new(), variant.span)
+            };
+            variants.push(VariantDecl {
+                name: variant.value.to_string(),
+                name_span: variant.span,
+                fields,
+                span,
+            });
+            match p.get_current_token() {
+                Some(t) if t.kind == TokenKind::COMMA => p.advance(),
+                Some(t) if t.kind == TokenKind::RBRACE => {}
+                _ => return Err(p.unexpected(&[TokenKind::COMMA, TokenKind::RBRACE])),
+            }
+        }
+        let close = p.expect(TokenKind::RBRACE)?;
+        let span = keyword.span.combine(&close.span);
+        let decl = EnumDecl {
+            public: public.is_some(),
+            name: name.value.to_string(),
+            name_span: name.span,
+            variants,
+            span,
+        };
+        Ok(p.alloc_stmt(StmtKind::Enum(Box::new(decl)), span))
+    }
+
+    /// Parses `pub` before a function, struct or enum declaration, which
+    /// makes it visible to the files importing this one.
+    pub fn parse_pub_decl(p: &mut Parser<'a>) -> Result<StmtId, ParserError<'a>> {
+        let keyword = p.expect(TokenKind::PUB)?;
+        Self::expect_top_level(p, keyword)?;
+        match p.get_current_token().map(|t| t.kind) {
+            Some(TokenKind::FUNCTION) => Self::func_decl(p, Some(keyword)),
+            Some(TokenKind::STRUCT) => Self::struct_decl(p, Some(keyword)),
+            Some(TokenKind::ENUM) => Self::enum_decl(p, Some(keyword)),
+            _ => Err(p.unexpected(&[TokenKind::FUNCTION, TokenKind::STRUCT, TokenKind::ENUM])),
+        }
+    }
+
+    /// Parses `import "path/to/file";` or `import geometry.{Point, area};`,
+    /// the list of items is optional after either.
+    pub fn parse_import_decl(p: &mut Parser<'a>) -> Result<StmtId, ParserError<'a>> {
+        let keyword = p.expect(TokenKind::IMPORT)?;
+        Self::expect_top_level(p, keyword)?;
+        let (source, source_span) = if p.at(TokenKind::LITERALSTRING) {
+            let token = p.expect(TokenKind::LITERALSTRING)?;
+            (ImportSource::File(Self::string_value(token)?), token.span)
+        } else {
+            let first = p.expect(TokenKind::IDENTIFIER)?;
+            let mut segments = vec![first.value.to_string()];
+            let mut span = first.span;
+            // a `.` followed by `{` starts the items instead
+            while p.at(TokenKind::DOT)
+                && p.get_k_token(1).is_some_and(|t| t.kind == TokenKind::IDENTIFIER)
+            {
+                p.advance();
+                let segment = p.expect(TokenKind::IDENTIFIER)?;
+                segments.push(segment.value.to_string());
+                span = span.combine(&segment.span);
+            }
+            (ImportSource::Module(segments), span)
+        };
+
+        let items = if p.at(TokenKind::DOT) {
+            p.advance();
+            let open = p.expect(TokenKind::LBRACE)?;
+            let mut items = Vec::new();
+            while !p.at(TokenKind::RBRACE) {
+                if p.at_end() {
+                    return Err(ParserError::UnclosedDelimiter {
+                        open,
+                        close: TokenKind::RBRACE,
+                    });
+                }
+                let name = p.expect(TokenKind::IDENTIFIER)?;
+                items.push(ImportItem {
+                    name: name.value.to_string(),
+                    span: name.span,
+                });
+                match p.get_current_token() {
+                    Some(t) if t.kind == TokenKind::COMMA => p.advance(),
+                    Some(t) if t.kind == TokenKind::RBRACE => {}
+                    _ => return Err(p.unexpected(&[TokenKind::COMMA, TokenKind::RBRACE])),
+                }
+            }
+            p.expect(TokenKind::RBRACE)?;
+            Some(items)
+        } else {
+            None
+        };
+
+        let semi = p.expect(TokenKind::SEMICOLON)?;
+        let span = keyword.span.combine(&semi.span);
+        let decl = ImportDecl {
+            source,
+            source_span,
+            items,
+            span,
+        };
+        Ok(p.alloc_stmt(StmtKind::Import(Box::new(decl)), span))
+    }
+
+    /// Fails unless the statement starting at `keyword` is at the top level
+    /// of the file, where imports and public declarations belong.
+    fn expect_top_level(p: &Parser<'a>, keyword: Token<'a>) -> Result<(), ParserError<'a>> {
+        // `parse_stmt` counts the statement itself
+        if p.nesting > 1 {
+            return Err(ParserError::NotTopLevel { keyword });
+        }
+        Ok(())
+    }
+}
@@ -1,34 +1,114 @@
-use lexer::token::Token;
+use std::collections::HashMap;
+
+use lexer::token::{Position, SourceSpan, Token};
+use lexer::token_type::TokenKind;
+
+use crate::lints::Lint;
+use crate::nodes::ast::Ast;
+use crate::nodes::exprs::{ExprId, ExprKind};
+use crate::nodes::stmts::{StmtId, StmtKind};
+use crate::parser::errors::ParserError;
+use crate::parser::events::{Event, SyntaxKind};
+use crate::parser::trace::{TraceEvent, TraceStep};
+
+/// Default of `Parser::max_depth`. A level takes up to about 7 KiB of stack
+/// in a debug build, so the default fits in half of the 2 MiB a spawned
+/// thread gets.
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// The structs declared in one block and the struct literals in it not yet
+/// matched to a declaration.
+#[derive(Debug, Default)]
+pub(crate) struct StructScope {
+    /// Field names of each struct declared in the block, `None` for a name
+    /// declared more than once.
+    pub(crate) fields: HashMap<String, Option<Vec<String>>>,
+    /// Struct literals with their span and field names.
+    pub(crate) inits: Vec<(String, SourceSpan, Vec<String>)>,
+}
 
 /// A parser for a sequence of tokens, allowing navigation and retrieval of tokens.
 /// The parser maintains a position within the token sequence and provides methods to access
 /// the current token, advance to the next token, and retrieve tokens at specific offsets.
 /// The `Parser` struct is generic over a lifetime `'a`, which allows it to hold references to
 /// tokens that have the same lifetime as the parser itself.
-/// #Example
+/// # Example
 /// ```
-/// use lexer::token::Token;
-/// use parser::engine::Parser;
-/// let tokens = vec![Token::new("token1"), Token::new("token2")];
-/// let mut parser = Parser::new(tokens, 0);
-/// assert_eq!(parser.get_current_token().unwrap().value, "token1");
+/// use parser::parser::engine::Parser;
+/// let tokens = lexer::tokenize("4 + 5", "example").unwrap();
+/// let mut parser = Parser::new(&tokens, 0);
+/// assert_eq!(parser.get_current_token().unwrap().value, "4");
 /// parser.advance();
-/// assert_eq!(parser.get_current_token().unwrap().value, "token2");
+/// assert_eq!(parser.get_current_token().unwrap().value, "+");
 /// ```
 pub struct Parser<'a> {
     pub tokens: &'a [Token<'a>],
     pub pos: usize,
+    /// Arena the parsed nodes are allocated in.
+    pub ast: Ast,
+    /// Lints that fired while parsing, to be fed to a `Linter`.
+    pub lints: Vec<(Lint, SourceSpan)>,
+    /// Syntax errors recovered from so far, in source order.
+    pub errors: Vec<ParserError<'a>>,
+    /// Whether a `{` after a name starts a struct literal. Turned off in
+    /// conditions, where the `{` opens the body instead.
+    pub(crate) struct_literals: bool,
+    /// Position of the token the expression statement being read starts
+    /// at. An `if`, `match` or `{` there would start a statement instead.
+    pub(crate) stmt_start: usize,
+    /// Structs declared and struct literals seen in each block being read,
+    /// the program's outermost. A literal is checked against the struct of
+    /// its name in the innermost block that declares one once that block is
+    /// read, so it may come before the declaration.
+    pub(crate) struct_scopes: Vec<StructScope>,
+    /// Events of the parse, only recorded when building a syntax tree.
+    pub(crate) events: Option<Vec<Event>>,
+    /// Decisions of the expression parser, only recorded when set to
+    /// `Some` before parsing.
+    pub trace: Option<Vec<TraceEvent>>,
+    /// Nesting of the current token in `parse_expr` calls and the nud and
+    /// led functions they dispatch to.
+    pub(crate) depth: usize,
+    /// Deepest nesting of expressions, statements, types and patterns that
+    /// is parsed. Going deeper is an error and skips the rest of the input,
+    /// instead of overflowing the stack. The operators of a chain such as
+    /// `a + b + c` or `f(x).y` do not count: the chain is read in a loop,
+    /// and whatever walks the tree follows its left operands in a loop too.
+    pub max_depth: usize,
+    /// Current nesting, counted the same way.
+    pub(crate) nesting: usize,
+    /// Whether `max_depth` was exceeded, after which the enclosing
+    /// constructs fail to close without any news worth reporting.
+    pub(crate) nesting_exceeded: bool,
 }
 
 /// Implementation of the `Parser` struct, providing methods to create a new parser,
 /// retrieve the current token, get a token at a specific offset, and advance the parser's
 /// position.
-//// The `Parser` struct is designed to work with a sequence of tokens, allowing for parsing
-///operations and navigation through the tokens. It provides a simple interface to access tokens
-///and manage the parser's position within the token stream.
+/// The `Parser` struct is designed to work with a sequence of tokens, allowing for parsing
+/// operations and navigation through the tokens. It provides a simple interface to access tokens
+/// and manage the parser's position within the token stream.
 impl<'a> Parser<'a> {
     pub fn new(tokens: &'a [Token<'a>], pos: usize) -> Parser<'a> {
-        Parser { tokens, pos }
+        Parser {
+            tokens,
+            pos,
+            // typical code has about one expression per two or three tokens
+            // and one statement per ten, reserving a bit more than that
+            // avoids growing the arena while parsing
+            ast: Ast::with_capacity(tokens.len() / 2, tokens.len() / 8),
+            lints: Vec::new(),
+            errors: Vec::new(),
+            struct_literals: true,
+            stmt_start: usize::MAX,
+            struct_scopes: Vec::new(),
+            events: None,
+            trace: None,
+            depth: 0,
+            max_depth: DEFAULT_MAX_DEPTH,
+            nesting: 0,
+            nesting_exceeded: false,
+        }
     }
 
     /// Creates a new `Parser` instance with the given tokens and initial position.
@@ -44,7 +124,120 @@ impl<'a> Parser<'a> {
     /// Advances the parser's position by one token, moving to the next token in the sequence.
     pub fn advance(&mut self) {
         if self.pos < self.tokens.len() {
+            if let Some(events) = &mut self.events {
+                events.push(Event::Token);
+            }
             self.pos += 1;
         }
     }
+
+    /// Allocates an expression in the arena, recording it as a node when
+    /// events are on.
+    pub(crate) fn alloc_expr(&mut self, kind: ExprKind, span: SourceSpan) -> ExprId {
+        let id = self.ast.alloc_expr(kind, span);
+        self.record_node(SyntaxKind::Expr(id), span);
+        id
+    }
+
+    /// Allocates a statement in the arena, recording it as a node when
+    /// events are on.
+    pub(crate) fn alloc_stmt(&mut self, kind: StmtKind, span: SourceSpan) -> StmtId {
+        let id = self.ast.alloc_stmt(kind, span);
+        self.record_node(SyntaxKind::Stmt(id), span);
+        id
+    }
+
+    /// Records a node ending at the current position. Its first token is
+    /// the first consumed one at or after the start of `span`.
+    fn record_node(&mut self, kind: SyntaxKind, span: SourceSpan) {
+        if let Some(events) = &mut self.events {
+            let start = self.tokens[..self.pos].partition_point(|t| t.span.start < span.start);
+            events.push(Event::Node { kind, start });
+        }
+    }
+
+    /// Records a step of the expression parser when tracing.
+    pub(crate) fn record_trace(&mut self, step: TraceStep) {
+        if let Some(trace) = &mut self.trace {
+            trace.push(TraceEvent {
+                depth: self.depth,
+                pos: self.pos,
+                step,
+            });
+        }
+    }
+
+    /// Runs `parse` one nesting level deeper. Past `max_depth` it reports
+    /// the error and skips to the end of input instead, which stops the
+    /// enclosing constructs from going any deeper either.
+    pub(crate) fn nested<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Parser<'a>) -> Result<T, ParserError<'a>>,
+    ) -> Result<T, ParserError<'a>> {
+        if self.nesting >= self.max_depth {
+            let span = self.get_current_token().map_or_else(|| self.eof_span(), |t| t.span);
+            self.nesting_exceeded = true;
+            while !self.at_end() {
+                self.advance();
+            }
+            return Err(ParserError::NestingTooDeep {
+                span,
+                limit: self.max_depth,
+            });
+        }
+        self.nesting += 1;
+        let result = parse(self);
+        self.nesting -= 1;
+        result
+    }
+
+    /// Whether the current token has the given kind.
+    pub fn at(&self, kind: TokenKind) -> bool {
+        self.get_current_token().is_some_and(|t| t.kind == kind)
+    }
+
+    /// Consumes the current token if it has the given kind, otherwise reports
+    /// what was found instead.
+    pub fn expect(&mut self, kind: TokenKind) -> Result<Token<'a>, ParserError<'a>> {
+        match self.get_current_token() {
+            Some(token) if token.kind == kind => {
+                let token = *token;
+                self.advance();
+                Ok(token)
+            }
+            _ => Err(self.unexpected(&[kind])),
+        }
+    }
+
+    /// Returns an empty span just past the last token, pointing at the end of input.
+    pub fn eof_span(&self) -> SourceSpan {
+        let end = self
+            .tokens
+            .iter()
+            .rev()
+            .find(|t| t.kind != TokenKind::EOF)
+            .map_or(Position::new(1, 1), |t| t.span.end);
+        SourceSpan::new(end, end)
+    }
+
+    /// Whether every token but the end of input has been consumed.
+    pub fn at_end(&self) -> bool {
+        self.get_current_token()
+            .is_none_or(|t| t.kind == TokenKind::EOF)
+    }
+
+    /// Builds the error for the current position when none of the `expected`
+    /// token kinds is there.
+    pub fn unexpected(&self, expected: &[TokenKind]) -> ParserError<'a> {
+        match self.get_current_token() {
+            Some(found) if found.kind != TokenKind::EOF => ParserError::UnexpectedToken {
+                found: *found,
+                expected: expected.to_vec(),
+            },
+            _ => ParserError::UnexpectedEof {
+                span: self.eof_span(),
+                expected: expected.to_vec(),
+            },
+        }
+    }
 }
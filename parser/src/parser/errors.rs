@@ -1,43 +1,246 @@
-
+use lexer::errors::{Severity, write_snippet};
+use lexer::messages::{Message, format, tr};
+use lexer::token::{SourceSpan, Token};
+use lexer::token_type::TokenKind;
+use std::fmt;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum LookUpError {
-    NotFound(String),
+    NudNotFound(TokenKind),
+    LedNotFound(TokenKind),
 }
 
 impl std::fmt::Display for LookUpError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            LookUpError::NotFound(msg) => write!(f, "Lookup error: {}", msg),
+            LookUpError::NudNotFound(kind) => {
+                write!(f, "{}", format(Message::NudNotFound, &[kind]))
+            }
+            LookUpError::LedNotFound(kind) => {
+                write!(f, "{}", format(Message::LedNotFound, &[kind]))
+            }
         }
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum ParserError {
-    UnexpectedToken(String),
-    MissingToken(String),
-    InvalidExpression(String),
-    TypeError(String),
-    LookupError(LookUpError),
+pub enum ParserError<'a> {
+    // a token was found where one of `expected` should have been
+    UnexpectedToken {
+        found: Token<'a>,
+        expected: Vec<TokenKind>,
+    },
+
+    // the input ended where one of `expected` should have been,
+    // `span` points just past the last token
+    UnexpectedEof {
+        span: SourceSpan,
+        expected: Vec<TokenKind>,
+    },
+
+    // the token has the right kind but its text is not a valid value
+    InvalidLiteral {
+        token: Token<'a>,
+    },
+
+    // the left side of `=` is not a variable, field or index
+    InvalidAssignTarget {
+        span: SourceSpan,
+    },
+
+    // two elements of a list follow each other without a `,`, `span`
+    // points at the second one
+    MissingComma {
+        span: SourceSpan,
+    },
+
+    // a list opened by `open` ended before its closing `close`
+    UnclosedDelimiter {
+        open: Token<'a>,
+        close: TokenKind,
+    },
+
+    // a field appears twice in a struct declaration or literal, `span`
+    // points at the second one
+    DuplicateField {
+        name: String,
+        span: SourceSpan,
+    },
+
+    // a literal of the struct `name` leaves out some of its declared fields
+    MissingFields {
+        name: String,
+        fields: Vec<String>,
+        span: SourceSpan,
+    },
+
+    // expressions, blocks, types or patterns are nested more than `limit`
+    // levels deep, `span` points at the token that would go one deeper
+    NestingTooDeep {
+        span: SourceSpan,
+        limit: usize,
+    },
+
+    // an `import` or `pub` inside a block or expression
+    NotTopLevel {
+        keyword: Token<'a>,
+    },
+
+    // the token has no parse function registered in the lookup tables
+    LookupError {
+        token: Token<'a>,
+        error: LookUpError,
+    },
 }
 
-impl From<LookUpError> for ParserError {
-    fn from(err: LookUpError) -> Self {
-        ParserError::LookupError(err)
+impl<'a> From<(Token<'a>, LookUpError)> for ParserError<'a> {
+    fn from((token, error): (Token<'a>, LookUpError)) -> ParserError<'a> {
+        ParserError::LookupError { token, error }
     }
 }
 
-impl std::fmt::Display for ParserError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+/// Formats a set of expected token kinds as `x` or `one of x, y, z`.
+fn describe_expected(expected: &[TokenKind]) -> String {
+    let kinds: Vec<String> = expected.iter().map(|k| k.to_string()).collect();
+    match kinds.len() {
+        1 => kinds[0].clone(),
+        _ => format(Message::OneOf, &[&kinds.join(", ")]),
+    }
+}
+
+impl<'a> ParserError<'a> {
+    /// Source span the error points at.
+    pub fn span(&self) -> SourceSpan {
+        match self {
+            ParserError::UnexpectedToken { found, .. } => found.span,
+            ParserError::UnexpectedEof { span, .. } => *span,
+            ParserError::InvalidLiteral { token } => token.span,
+            ParserError::InvalidAssignTarget { span } => *span,
+            ParserError::MissingComma { span } => *span,
+            ParserError::UnclosedDelimiter { open, .. } => open.span,
+            ParserError::DuplicateField { span, .. } => *span,
+            ParserError::MissingFields { span, .. } => *span,
+            ParserError::NestingTooDeep { span, .. } => *span,
+            ParserError::NotTopLevel { keyword } => keyword.span,
+            ParserError::LookupError { token, .. } => token.span,
+        }
+    }
+
+    /// Token kinds that would have been accepted at the error position.
+    pub fn expected(&self) -> &[TokenKind] {
+        match self {
+            ParserError::UnexpectedToken { expected, .. }
+            | ParserError::UnexpectedEof { expected, .. } => expected,
+            _ => &[],
+        }
+    }
+
+    /// Token found at the error position, `None` at the end of input.
+    pub fn found(&self) -> Option<&Token<'a>> {
+        match self {
+            ParserError::UnexpectedToken { found, .. } => Some(found),
+            ParserError::UnexpectedEof { .. } => None,
+            ParserError::InvalidLiteral { token } => Some(token),
+            ParserError::InvalidAssignTarget { .. } => None,
+            ParserError::MissingComma { .. } => None,
+            ParserError::UnclosedDelimiter { .. } => None,
+            ParserError::DuplicateField { .. } => None,
+            ParserError::MissingFields { .. } => None,
+            ParserError::NestingTooDeep { .. } => None,
+            ParserError::NotTopLevel { keyword } => Some(keyword),
+            ParserError::LookupError { token, .. } => Some(token),
+        }
+    }
+
+    fn title(&self) -> String {
         match self {
-            ParserError::UnexpectedToken(msg) => write!(f, "Unexpected token: {}", msg),
-            ParserError::MissingToken(msg) => write!(f, "Missing token: {}", msg),
-            ParserError::InvalidExpression(msg) => write!(f, "Invalid expression: {}", msg),
-            ParserError::TypeError(msg) => write!(f, "Type error: {}", msg),
-            ParserError::LookupError(look_up_error) => {
-                write!(f, "Lookup error: {}", look_up_error)
+            ParserError::UnexpectedToken { found, expected } => format(
+                Message::ExpectedFound,
+                &[&describe_expected(expected), &format_args!("`{}`", found.value)],
+            ),
+            ParserError::UnexpectedEof { expected, .. } => {
+                format(Message::ExpectedEndOfInput, &[&describe_expected(expected)])
+            }
+            ParserError::InvalidLiteral { token } => {
+                format(Message::InvalidIntegerLiteral, &[&token.value])
+            }
+            ParserError::InvalidAssignTarget { .. } => tr(Message::InvalidAssignTarget).to_string(),
+            ParserError::MissingComma { .. } => tr(Message::MissingComma).to_string(),
+            ParserError::UnclosedDelimiter { open, .. } => {
+                format(Message::UnclosedDelimiter, &[&open.kind])
+            }
+            ParserError::DuplicateField { name, .. } => format(Message::DuplicateField, &[name]),
+            ParserError::MissingFields { name, fields, .. } => {
+                let fields: Vec<String> = fields.iter().map(|f| format!("`{}`", f)).collect();
+                format(Message::MissingFields, &[name, &fields.join(", ")])
+            }
+            ParserError::NestingTooDeep { limit, .. } => format(Message::NestingTooDeep, &[limit]),
+            ParserError::NotTopLevel { keyword } => format(Message::NotTopLevel, &[&keyword.kind]),
+            ParserError::LookupError { error, .. } => {
+                format!("{}: {}", tr(Message::LookupError), error)
+            }
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            ParserError::UnexpectedToken { expected, .. }
+            | ParserError::UnexpectedEof { expected, .. } => {
+                format(Message::ExpectedLabel, &[&describe_expected(expected)])
+            }
+            ParserError::InvalidLiteral { .. } => tr(Message::InvalidLiteralLabel).to_string(),
+            ParserError::InvalidAssignTarget { .. } => {
+                tr(Message::InvalidAssignTargetLabel).to_string()
             }
+            ParserError::MissingComma { .. } => tr(Message::MissingCommaLabel).to_string(),
+            ParserError::UnclosedDelimiter { close, .. } => {
+                format(Message::UnclosedDelimiterLabel, &[close])
+            }
+            ParserError::DuplicateField { name, .. } => {
+                format(Message::DuplicateFieldLabel, &[name])
+            }
+            ParserError::MissingFields { .. } => tr(Message::MissingFieldsLabel).to_string(),
+            ParserError::NestingTooDeep { .. } => tr(Message::NestingTooDeepLabel).to_string(),
+            ParserError::NotTopLevel { .. } => tr(Message::NotTopLevelLabel).to_string(),
+            ParserError::LookupError { error, .. } => error.to_string(),
         }
     }
+
+    /// Pairs the error with the source it came from so it can be displayed
+    /// with a snippet of the offending line.
+    pub fn report<'r>(&'r self, source: &'r str, filename: &'r str) -> Report<'r, 'a> {
+        Report {
+            error: self,
+            source,
+            filename,
+        }
+    }
+}
+
+impl<'a> fmt::Display for ParserError<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let span = self.span();
+        write!(f, "{} ({}:{})", self.title(), span.start.line, span.start.column)
+    }
+}
+
+/// A `ParserError` rendered with the source snippet and caret underline.
+pub struct Report<'r, 'a> {
+    error: &'r ParserError<'a>,
+    source: &'r str,
+    filename: &'r str,
+}
+
+impl fmt::Display for Report<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_snippet(
+            f,
+            Severity::Error,
+            &self.error.title(),
+            &self.error.label(),
+            self.filename,
+            self.source,
+            self.error.span(),
+        )
+    }
 }
@@ -0,0 +1,23 @@
+use crate::nodes::exprs::ExprId;
+use crate::nodes::stmts::StmtId;
+
+/// What a node of the concrete syntax tree stands for. Every node but the
+/// root is an AST node, so the typed AST can be read back from the tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyntaxKind {
+    Program,
+    Expr(ExprId),
+    Stmt(StmtId),
+}
+
+/// One step of a parse, recorded when the parser is asked to. Replaying the
+/// events in order rebuilds the shape of the parse as a tree of tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    // the current token was consumed
+    Token,
+    // a node was completed, spanning every token consumed from `start`, the
+    // index of its first token, up to now. Nodes complete after their
+    // children, so the events list the tree in post-order
+    Node { kind: SyntaxKind, start: usize },
+}
@@ -1,10 +1,14 @@
-use crate::nodes::exprs::{BinaryExpr, BinaryOp};
-use crate::nodes::exprs::{LiteralExpr, LiteralValue};
+use crate::nodes::exprs::{ArmBody, BinaryOp, ExprId, ExprKind, FieldInit, MatchArm};
+use crate::nodes::exprs::{LambdaBody, LambdaExpr, LiteralValue, UnaryOp};
+use crate::lints::Lint;
 use crate::parser::errors::ParserError;
 use crate::parser::lookups::BindingPower;
-use crate::parser::lookups::{get_led_fn, get_nud_fn, LedFn, NudFn};
-use crate::{nodes::exprs::Expr, parser::engine::Parser};
-use lexer::token::Token;
+use crate::parser::lookups::{get_led_fn, get_nud_fn, infix_binding, LedFn, NudFn, BINARY_OPS, EXPR_START};
+use crate::parser::lookups::STMT_KEYWORDS;
+use crate::parser::lookups::{led_fn_name, nud_fn_name};
+use crate::parser::trace::TraceStep;
+use crate::parser::engine::Parser;
+use lexer::token::{SourceSpan, Token};
 use lexer::token_type::TokenKind;
 
 /// This module defines the expression parsing logic for the parser.
@@ -14,56 +18,249 @@ use lexer::token_type::TokenKind;
 /// token position and the sequence of tokens to be parsed.
 impl<'a> Parser<'a> {
     /// Parses a primary expression, which is the most basic unit of an expression.
-    pub fn parse_primary_expr(p: &mut Parser<'a>) -> Result<Box<dyn Expr + 'a>, ParserError> {
-        let token = p
+    pub fn parse_primary_expr(p: &mut Parser<'a>) -> Result<ExprId, ParserError<'a>> {
+        let token = *p
             .get_current_token()
-            .ok_or_else(|| {
-                ParserError::UnexpectedToken("Expected a primary expression".to_string())
-            })?
-            .clone();
+            .ok_or_else(|| p.unexpected(EXPR_START))?;
         match token.kind {
             TokenKind::INTEGER => {
                 // Parse an integer literal
                 p.advance();
-                let value = token.value.parse::<i128>().map_err(|_|{
-                    ParserError::InvalidExpression(format!(
-                        "Failed to parse integer literal: {}",
-                        token.value
-                    ))
-                });
-                let span = token.span.clone();
-                Ok(Box::new(LiteralExpr::new(LiteralValue::Int(value?), span)))
+                let value = token
+                    .value
+                    .parse::<i128>()
+                    .map_err(|_| ParserError::InvalidLiteral { token })?;
+                Ok(p.alloc_expr(ExprKind::Literal(LiteralValue::Int(value)), token.span))
+            }
+            TokenKind::FLOAT => {
+                p.advance();
+                let value = token
+                    .value
+                    .parse::<f64>()
+                    .map_err(|_| ParserError::InvalidLiteral { token })?;
+                Ok(p.alloc_expr(ExprKind::Literal(LiteralValue::Float(value)), token.span))
+            }
+            TokenKind::LITERALSTRING => {
+                p.advance();
+                let value = Self::string_value(token)?;
+                Ok(p.alloc_expr(ExprKind::Literal(LiteralValue::Str(value)), token.span))
+            }
+            TokenKind::TRUE | TokenKind::FALSE => {
+                p.advance();
+                let value = LiteralValue::Bool(token.kind == TokenKind::TRUE);
+                Ok(p.alloc_expr(ExprKind::Literal(value), token.span))
+            }
+            TokenKind::IDENTIFIER => {
+                p.advance();
+                if p.struct_literals && p.at(TokenKind::LBRACE) {
+                    return Self::parse_struct_expr(p, token);
+                }
+                Ok(p.alloc_expr(ExprKind::Ident(token.value.to_string()), token.span))
             }
             _ => {
                 // Handle other primary expression types, such as identifiers, strings, etc.
                 // For now, we will just return an error for unsupported token kinds
-                todo!("Handle other primary expression types");
-                Err(ParserError::UnexpectedToken(format!(
-                    "Unsupported primary expression token: {:?}",
-                    token.kind
-                )))
+                Err(p.unexpected(EXPR_START))
             }
         }
     }
 
-    pub fn parse_expr(p: &mut Parser<'a>, bp: BindingPower) -> Result<Box<dyn Expr + 'a>, ParserError> {
-        // Start with a primary expression
-        let mut left: Box<dyn Expr + 'a> = Self::parse_primary_expr(p)?;
+    /// Contents of a string literal token. The lexeme keeps its quotes, a
+    /// token without them can only come from a broken token stream.
+    pub(crate) fn string_value(token: Token<'a>) -> Result<String, ParserError<'a>> {
+        token
+            .value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .map(str::to_string)
+            .ok_or(ParserError::InvalidLiteral { token })
+    }
 
-        // Check for the next token to determine if we have a binary expression
-        while let Some(current) = p.get_current_token() {
-        let binding_power = BindingPower::from_token(current.kind);
-        if (bp as u8) >= (binding_power as u8) {
-            break;
+    /// Parses a prefix operator applied to an operand, such as `-x` or `!x`.
+    /// The operand binds tighter than any binary operator, so `-a * b` is
+    /// `(-a) * b`.
+    pub fn parse_unary_expr(p: &mut Parser<'a>) -> Result<ExprId, ParserError<'a>> {
+        let token = *p.get_current_token().ok_or_else(|| p.unexpected(EXPR_START))?;
+        let op = UnaryOp::from_token(token.kind).ok_or_else(|| p.unexpected(EXPR_START))?;
+        p.advance();
+        let expr = Self::parse_expr(p, BindingPower::Unary)?;
+        // From the operator to the end of the operand
+        let span = token.span.combine(&p.ast.expr_span(expr));
+        Ok(p.alloc_expr(
+            ExprKind::Unary {
+                op,
+                op_span: token.span,
+                expr,
+            },
+            span,
+        ))
+    }
+
+    /// Parses a parenthesized expression, or a tuple when the parentheses
+    /// hold a `,` or nothing. Parentheses around an atomic operand fire the
+    /// `redundant_parens` lint, unless the operand needs them where it
+    /// stands, see `parens_needed`.
+    pub fn parse_grouping_expr(p: &mut Parser<'a>) -> Result<ExprId, ParserError<'a>> {
+        let starts_stmt = p.pos == p.stmt_start;
+        let open = p.expect(TokenKind::LPAREN)?;
+        let mut elements = Vec::new();
+        let mut is_tuple = p.at(TokenKind::RPAREN);
+        while !p.at(TokenKind::RPAREN) {
+            if p.at_end() {
+                return Err(ParserError::UnclosedDelimiter {
+                    open,
+                    close: TokenKind::RPAREN,
+                });
+            }
+            elements.push(Self::parse_nested_expr(p)?);
+            if elements.len() == 1 && !p.at(TokenKind::COMMA) {
+                break;
+            }
+            is_tuple = true;
+            Self::expect_list_separator(p, open, TokenKind::RPAREN)?;
         }
+        let close = p.expect(TokenKind::RPAREN)?;
+        let span = open.span.combine(&close.span);
 
-        let led_fn: LedFn<'a> = get_led_fn(current.kind)?;
-        left = led_fn(p, binding_power, left)?;
+        if is_tuple {
+            return Ok(p.alloc_expr(ExprKind::Tuple(elements), span));
         }
-        Ok(left)
-}
+        let expr = elements[0];
+        if p.ast.expr(expr).is_atomic() && !Self::parens_needed(p, expr, starts_stmt) {
+            p.lints.push((Lint::RedundantParens, span));
+        }
+        Ok(p.alloc_expr(ExprKind::Grouping(expr), span))
+    }
+
+    /// Whether the parentheses around the atomic `expr` are needed all the
+    /// same: a struct literal in a condition would end the condition at its
+    /// `{`, as in `if (P { x: 1 }).x == p.x { ... }`, and an `if`, `match`,
+    /// block or map at the start of a statement would be read as a
+    /// statement of its own, as in `(if a { b } else { c }).d;`. Of two
+    /// pairs around such an operand, the inner one is redundant.
+    fn parens_needed(p: &Parser<'a>, expr: ExprId, starts_stmt: bool) -> bool {
+        match p.ast.expr(expr) {
+            ExprKind::Grouping(inner) => Self::parens_needed(p, *inner, starts_stmt),
+            ExprKind::Struct { .. } => !p.struct_literals,
+            ExprKind::If { .. } | ExprKind::Match { .. } | ExprKind::Block(_) | ExprKind::Map(_) => {
+                starts_stmt
+            }
+            _ => false,
+        }
+    }
 
+    /// Parses the condition of an `if` or `while`, or the iterable of a
+    /// `for`. The `{` that follows opens the body, so `if p == Point { ... }`
+    /// compares with `Point` instead of starting a struct literal.
+    pub fn parse_cond_expr(p: &mut Parser<'a>) -> Result<ExprId, ParserError<'a>> {
+        let open = p.get_current_token().copied().filter(|t| t.kind == TokenKind::LBRACE);
+        let saved = std::mem::replace(&mut p.struct_literals, false);
+        let expr = Self::parse_expr(p, BindingPower::Primary);
+        p.struct_literals = saved;
+        match open {
+            // in `while { }` the block read as the condition was the body,
+            // reported there rather than as a missing `{` on the next line
+            Some(open) if expr.is_ok() && !p.at(TokenKind::LBRACE) => {
+                Err(ParserError::UnexpectedToken {
+                    found: open,
+                    expected: EXPR_START
+                        .iter()
+                        .copied()
+                        .filter(|kind| *kind != TokenKind::LBRACE)
+                        .collect(),
+                })
+            }
+            _ => expr,
+        }
+    }
+
+    /// Parses an expression inside delimiters, where struct literals are
+    /// allowed again even within a condition: `if f(Point { x }) { ... }`.
+    pub fn parse_nested_expr(p: &mut Parser<'a>) -> Result<ExprId, ParserError<'a>> {
+        let saved = std::mem::replace(&mut p.struct_literals, true);
+        let expr = Self::parse_expr(p, BindingPower::Primary);
+        p.struct_literals = saved;
+        expr
+    }
 
+    /// Parses an expression, or records an error and returns an `ErrorExpr`
+    /// placeholder when the current token cannot start one, as in
+    /// `let x = ;`. The rest of the statement then parses as usual.
+    pub fn parse_expr_or_error(p: &mut Parser<'a>) -> Result<ExprId, ParserError<'a>> {
+        let starts_expr = p
+            .get_current_token()
+            .is_some_and(|t| get_nud_fn(t.kind).is_ok());
+        if starts_expr {
+            return Self::parse_expr(p, BindingPower::Primary);
+        }
+        let error = p.unexpected(EXPR_START);
+        let at = error.span().start;
+        p.errors.push(error);
+        Ok(p.alloc_expr(ExprKind::Error, SourceSpan::new(at, at)))
+    }
+
+    pub fn parse_expr(p: &mut Parser<'a>, bp: BindingPower) -> Result<ExprId, ParserError<'a>> {
+        p.record_trace(TraceStep::Enter { bp });
+        p.depth += 1;
+        let expr = p.nested(|p| Self::parse_operators(p, bp));
+        p.depth -= 1;
+        p.record_trace(TraceStep::Exit);
+        expr
+    }
+
+    /// The loop of `parse_expr`, one level deeper than its caller however
+    /// many operators it applies.
+    fn parse_operators(p: &mut Parser<'a>, bp: BindingPower) -> Result<ExprId, ParserError<'a>> {
+        // Start with the prefix (nud) function of the first token
+        let first = *p.get_current_token().ok_or_else(|| p.unexpected(EXPR_START))?;
+        let nud_fn: NudFn<'a> = get_nud_fn(first.kind).map_err(|_| p.unexpected(EXPR_START))?;
+        if p.trace.is_some() {
+            let handler = nud_fn_name(first.kind).unwrap_or_default();
+            p.record_trace(TraceStep::Nud {
+                token: first.kind,
+                handler,
+            });
+        }
+        let mut left: ExprId = Self::dispatch(p, nud_fn)?;
+
+        // Keep applying operators while they bind tighter than `bp`
+        while let Some(current) = p.get_current_token() {
+            let current = *current;
+            let binding_power = BindingPower::from_token(current.kind);
+            p.record_trace(TraceStep::Compare {
+                token: current.kind,
+                token_bp: binding_power,
+                min_bp: bp,
+                binds: binding_power > bp,
+            });
+            if binding_power <= bp {
+                break;
+            }
+
+            let led_fn: LedFn<'a> = get_led_fn(current.kind).map_err(|e| (current, e))?;
+            if p.trace.is_some() {
+                let handler = led_fn_name(current.kind).unwrap_or_default();
+                p.record_trace(TraceStep::Led {
+                    token: current.kind,
+                    handler,
+                });
+            }
+            left = Self::dispatch(p, |p| led_fn(p, binding_power, left))?;
+        }
+        Ok(left)
+    }
+
+    /// Calls a nud or led function one level deeper, so in a trace the
+    /// expressions it parses nest under its dispatch.
+    fn dispatch(
+        p: &mut Parser<'a>,
+        parse: impl FnOnce(&mut Parser<'a>) -> Result<ExprId, ParserError<'a>>,
+    ) -> Result<ExprId, ParserError<'a>> {
+        p.depth += 1;
+        let expr = parse(p);
+        p.depth -= 1;
+        expr
+    }
 
     /// Parses an expression, starting with a primary expression and then applying
     /// operators based on their binding power.
@@ -74,23 +271,436 @@ impl<'a> Parser<'a> {
     pub fn parse_binary_expr(
         p: &mut Parser<'a>,
         bp: BindingPower,
-        left: Box<dyn Expr + 'a>,
-    ) -> Result<Box<dyn Expr + 'a>, ParserError> {
+        left: ExprId,
+    ) -> Result<ExprId, ParserError<'a>> {
         // Here we would typically look for the next token and determine if it is a binary operator
-        let op: Token<'a> = p
+        let op: Token<'a> = *p
             .get_current_token()
-            .ok_or_else(|| ParserError::UnexpectedToken("Expected a binary operator".to_string()))?
-            .clone();
+            .ok_or_else(|| p.unexpected(BINARY_OPS))?;
+        let (_, assoc) = infix_binding(op.kind).ok_or_else(|| p.unexpected(BINARY_OPS))?;
+        let binary_op = BinaryOp::from_token(op.kind).ok_or_else(|| p.unexpected(BINARY_OPS))?;
         // Advance the parser to the next tokens
         p.advance();
-        let right_bp = BindingPower::from_token(op.kind);
-        let right = Self::parse_expr(p, right_bp)?;
+        let right = Self::parse_expr(p, bp.right_operand(assoc))?;
         // Create a binary expression with the left operand, operator, and right operand
-        Ok(Box::new(BinaryExpr {
-            left,
-            op: BinaryOp::from_token(op.kind),
-            right,
-            type_info: None, // Type information can be set later
-        }))
+        let span = p.ast.expr_span(left).combine(&p.ast.expr_span(right));
+        Ok(p.alloc_expr(
+            ExprKind::Binary {
+                left,
+                op: binary_op,
+                right,
+            },
+            span,
+        ))
+    }
+
+    /// Parses a cast, `expr as T`. The type is not an expression, so it is
+    /// parsed with `parse_type_expr` rather than at a binding power.
+    pub fn parse_cast_expr(
+        p: &mut Parser<'a>,
+        _bp: BindingPower,
+        expr: ExprId,
+    ) -> Result<ExprId, ParserError<'a>> {
+        p.expect(TokenKind::AS)?;
+        let ty = Self::parse_type_expr(p)?;
+        // From the start of the expression to the end of the type
+        let span = p.ast.expr_span(expr).combine(&ty.span);
+        Ok(p.alloc_expr(ExprKind::Cast { expr, ty }, span))
+    }
+}
+
+/// Postfix operators. They are registered in the led table at the
+/// `Postfix` level, so they chain: `a.b(c)[d]` is `((a.b)(c))[d]`.
+impl<'a> Parser<'a> {
+    /// Parses the argument list of a call, `callee(a, b)`, reporting a
+    /// missing `,` or `)` like every other list.
+    pub fn parse_call_expr(
+        p: &mut Parser<'a>,
+        _bp: BindingPower,
+        callee: ExprId,
+    ) -> Result<ExprId, ParserError<'a>> {
+        let open = p.expect(TokenKind::LPAREN)?;
+        let mut args = Vec::new();
+        while !p.at(TokenKind::RPAREN) {
+            if p.at_end() {
+                return Err(ParserError::UnclosedDelimiter {
+                    open,
+                    close: TokenKind::RPAREN,
+                });
+            }
+            args.push(Self::parse_nested_expr(p)?);
+            Self::expect_list_separator(p, open, TokenKind::RPAREN)?;
+        }
+        let close = p.expect(TokenKind::RPAREN)?;
+        let span = p.ast.expr_span(callee).combine(&close.span);
+        Ok(p.alloc_expr(ExprKind::Call { callee, args }, span))
+    }
+
+    /// Parses an index, `object[index]`.
+    pub fn parse_index_expr(
+        p: &mut Parser<'a>,
+        _bp: BindingPower,
+        object: ExprId,
+    ) -> Result<ExprId, ParserError<'a>> {
+        p.expect(TokenKind::LBRACKET)?;
+        let index = Self::parse_nested_expr(p)?;
+        let close = p.expect(TokenKind::RBRACKET)?;
+        let span = p.ast.expr_span(object).combine(&close.span);
+        Ok(p.alloc_expr(ExprKind::Index { object, index }, span))
+    }
+
+    /// Parses a member access, `object.field`.
+    pub fn parse_member_expr(
+        p: &mut Parser<'a>,
+        _bp: BindingPower,
+        object: ExprId,
+    ) -> Result<ExprId, ParserError<'a>> {
+        p.expect(TokenKind::DOT)?;
+        let field = p.expect(TokenKind::IDENTIFIER)?;
+        // From the start of the object to the end of the field name
+        let span = p.ast.expr_span(object).combine(&field.span);
+        Ok(p.alloc_expr(
+            ExprKind::Member {
+                object,
+                field: field.value.to_string(),
+                field_span: field.span,
+            },
+            span,
+        ))
+    }
+}
+
+/// Collection literals. Both accept a trailing comma, and report a missing
+/// `,` between two elements apart from a list that is never closed.
+impl<'a> Parser<'a> {
+    /// Parses an array literal, `[a, b, c]`.
+    pub fn parse_array_expr(p: &mut Parser<'a>) -> Result<ExprId, ParserError<'a>> {
+        let open = p.expect(TokenKind::LBRACKET)?;
+        let mut elements = Vec::new();
+        while !p.at(TokenKind::RBRACKET) {
+            if p.at_end() {
+                return Err(ParserError::UnclosedDelimiter {
+                    open,
+                    close: TokenKind::RBRACKET,
+                });
+            }
+            elements.push(Self::parse_nested_expr(p)?);
+            Self::expect_list_separator(p, open, TokenKind::RBRACKET)?;
+        }
+        let close = p.expect(TokenKind::RBRACKET)?;
+        Ok(p.alloc_expr(ExprKind::Array(elements), open.span.combine(&close.span)))
+    }
+
+    /// Parses a map literal, `{key: value, ...}`. A `{` that starts a
+    /// statement is a block, so maps only appear where an expression is
+    /// expected.
+    pub fn parse_map_expr(p: &mut Parser<'a>) -> Result<ExprId, ParserError<'a>> {
+        let open = p.expect(TokenKind::LBRACE)?;
+        let mut entries = Vec::new();
+        while !p.at(TokenKind::RBRACE) {
+            if p.at_end() {
+                return Err(ParserError::UnclosedDelimiter {
+                    open,
+                    close: TokenKind::RBRACE,
+                });
+            }
+            let key = Self::parse_nested_expr(p)?;
+            p.expect(TokenKind::COLON)?;
+            let value = Self::parse_nested_expr(p)?;
+            entries.push((key, value));
+            Self::expect_list_separator(p, open, TokenKind::RBRACE)?;
+        }
+        let close = p.expect(TokenKind::RBRACE)?;
+        Ok(p.alloc_expr(ExprKind::Map(entries), open.span.combine(&close.span)))
+    }
+
+    /// Consumes the `,` after a list element, or stops before `close`.
+    /// Another element right away means the `,` was forgotten, anything
+    /// else means the list opened by `open` was never closed.
+    fn expect_list_separator(
+        p: &mut Parser<'a>,
+        open: Token<'a>,
+        close: TokenKind,
+    ) -> Result<(), ParserError<'a>> {
+        match p.get_current_token() {
+            Some(t) if t.kind == TokenKind::COMMA => {
+                p.advance();
+                Ok(())
+            }
+            Some(t) if t.kind == close => Ok(()),
+            Some(t) if EXPR_START.contains(&t.kind) => {
+                Err(ParserError::MissingComma { span: t.span })
+            }
+            _ => Err(ParserError::UnclosedDelimiter { open, close }),
+        }
+    }
+}
+
+impl<'a> Parser<'a> {
+    /// Parses the fields of a struct literal after its name,
+    /// `Point { x: 1, y }`. Fields missing from the declaration are checked
+    /// at the end of the enclosing block, repeated ones right away.
+    pub fn parse_struct_expr(
+        p: &mut Parser<'a>,
+        name: Token<'a>,
+    ) -> Result<ExprId, ParserError<'a>> {
+        let open = p.expect(TokenKind::LBRACE)?;
+        let mut fields: Vec<FieldInit> = Vec::new();
+        while !p.at(TokenKind::RBRACE) {
+            if p.at_end() {
+                return Err(ParserError::UnclosedDelimiter {
+                    open,
+                    close: TokenKind::RBRACE,
+                });
+            }
+            let field = p.expect(TokenKind::IDENTIFIER)?;
+            if fields.iter().any(|f| f.name == field.value) {
+                return Err(ParserError::DuplicateField {
+                    name: field.value.to_string(),
+                    span: field.span,
+                });
+            }
+            let (value, shorthand) = if p.at(TokenKind::COLON) {
+                p.advance();
+                (Self::parse_nested_expr(p)?, false)
+            } else {
+                let name = ExprKind::Ident(field.value.to_string());
+                (p.alloc_expr(name, field.span), true)
+            };
+            fields.push(FieldInit {
+                name: field.value.to_string(),
+                name_span: field.span,
+                value,
+                shorthand,
+            });
+            Self::expect_list_separator(p, open, TokenKind::RBRACE)?;
+        }
+        let close = p.expect(TokenKind::RBRACE)?;
+        let span = name.span.combine(&close.span);
+        if let Some(scope) = p.struct_scopes.last_mut() {
+            scope.inits.push((
+                name.value.to_string(),
+                span,
+                fields.iter().map(|f| f.name.clone()).collect(),
+            ));
+        }
+        Ok(p.alloc_expr(
+            ExprKind::Struct {
+                name: name.value.to_string(),
+                name_span: name.span,
+                fields,
+            },
+            span,
+        ))
+    }
+}
+
+impl<'a> Parser<'a> {
+    /// Parses `match scrutinee { pattern if guard => body, ... }`. An arm
+    /// whose body is a block may leave out the `,` after it.
+    pub fn parse_match_expr(p: &mut Parser<'a>) -> Result<ExprId, ParserError<'a>> {
+        let keyword = p.expect(TokenKind::MATCH)?;
+        let scrutinee = Self::parse_cond_expr(p)?;
+        let open = p.expect(TokenKind::LBRACE)?;
+        let mut arms = Vec::new();
+        while !p.at(TokenKind::RBRACE) {
+            if p.at_end() {
+                return Err(ParserError::UnclosedDelimiter {
+                    open,
+                    close: TokenKind::RBRACE,
+                });
+            }
+            let pattern = Self::parse_pattern(p)?;
+            let guard = if p.at(TokenKind::IF) {
+                p.advance();
+                Some(Self::parse_nested_expr(p)?)
+            } else {
+                None
+            };
+            p.expect(TokenKind::FAT_ARROW)?;
+            let body = if p.at(TokenKind::LBRACE) {
+                ArmBody::Block(Self::parse_block(p)?)
+            } else {
+                ArmBody::Expr(Self::parse_nested_expr(p)?)
+            };
+            let body_span = match &body {
+                ArmBody::Expr(expr) => p.ast.expr_span(*expr),
+                ArmBody::Block(block) => block.span,
+            };
+            let span = pattern.span.combine(&body_span);
+            let is_block = matches!(body, ArmBody::Block(_));
+            arms.push(MatchArm {
+                pattern,
+                guard,
+                body,
+                span,
+            });
+            if is_block && !p.at(TokenKind::COMMA) {
+                continue;
+            }
+            Self::expect_list_separator(p, open, TokenKind::RBRACE)?;
+        }
+        let close = p.expect(TokenKind::RBRACE)?;
+        Ok(p.alloc_expr(
+            ExprKind::Match { scrutinee, arms },
+            keyword.span.combine(&close.span),
+        ))
+    }
+}
+
+impl<'a> Parser<'a> {
+    /// Parses a lambda, `|a, b| a + b`, `|| f()` or `func(a) -> int { ... }`.
+    /// An expression body takes in every operator that follows it, so
+    /// `|x| x + 1` returns `x + 1`. A body starting with `{` is a block, as
+    /// after the `=>` of an arm, and so is any body after a return type.
+    pub fn parse_lambda_expr(p: &mut Parser<'a>) -> Result<ExprId, ParserError<'a>> {
+        let start = *p.get_current_token().ok_or_else(|| p.unexpected(EXPR_START))?;
+        let params = match start.kind {
+            TokenKind::FUNCTION => {
+                p.advance();
+                p.expect(TokenKind::LPAREN)?;
+                Self::parse_params(p, TokenKind::RPAREN)?.0
+            }
+            TokenKind::VERTICAL_BAR => {
+                p.advance();
+                Self::parse_params(p, TokenKind::VERTICAL_BAR)?.0
+            }
+            TokenKind::DOUBLE_VERTICAL_BAR => {
+                p.advance();
+                Vec::new()
+            }
+            _ => return Err(p.unexpected(EXPR_START)),
+        };
+        let return_type = if p.at(TokenKind::ARROW) {
+            p.advance();
+            Some(Self::parse_type_expr(p)?)
+        } else {
+            None
+        };
+        let body = if start.kind == TokenKind::FUNCTION
+            || return_type.is_some()
+            || p.at(TokenKind::LBRACE)
+        {
+            LambdaBody::Block(Self::parse_block(p)?)
+        } else {
+            LambdaBody::Expr(Self::parse_expr(p, BindingPower::Primary)?)
+        };
+        let body_span = match &body {
+            LambdaBody::Expr(expr) => p.ast.expr_span(*expr),
+            LambdaBody::Block(block) => block.span,
+        };
+        Ok(p.alloc_expr(
+            ExprKind::Lambda(Box::new(LambdaExpr {
+                params,
+                return_type,
+                body,
+            })),
+            start.span.combine(&body_span),
+        ))
+    }
+}
+
+impl<'a> Parser<'a> {
+    /// Parses `if cond { ... } else { ... }` where a value is expected. It
+    /// evaluates to the tail of the branch taken, `else if` chains nest as
+    /// the `else` branch.
+    pub fn parse_if_expr(p: &mut Parser<'a>) -> Result<ExprId, ParserError<'a>> {
+        let keyword = p.expect(TokenKind::IF)?;
+        let cond = Self::parse_cond_expr(p)?;
+        let then_branch = Self::parse_block(p)?;
+        let mut span = keyword.span.combine(&then_branch.span);
+
+        let else_branch = if p.at(TokenKind::ELSE) {
+            p.advance();
+            let branch = if p.at(TokenKind::IF) {
+                Self::parse_if_expr(p)?
+            } else {
+                let block = Self::parse_block(p)?;
+                let span = block.span;
+                p.alloc_expr(ExprKind::Block(Box::new(block)), span)
+            };
+            span = span.combine(&p.ast.expr_span(branch));
+            Some(branch)
+        } else {
+            None
+        };
+
+        Ok(p.alloc_expr(
+            ExprKind::If {
+                cond,
+                then_branch: Box::new(then_branch),
+                else_branch,
+            },
+            span,
+        ))
+    }
+
+    /// Parses a `{` where a value is expected, either a block that
+    /// evaluates to its tail, `{ let y = 2; y * y }`, or a map literal,
+    /// `{"a": 1}`.
+    pub fn parse_brace_expr(p: &mut Parser<'a>) -> Result<ExprId, ParserError<'a>> {
+        if !Self::at_block_expr(p) {
+            return Self::parse_map_expr(p);
+        }
+        let block = Self::parse_block(p)?;
+        let span = block.span;
+        Ok(p.alloc_expr(ExprKind::Block(Box::new(block)), span))
+    }
+
+    /// Whether the `{` at the current token opens a block rather than a
+    /// map. A map has a `:` after its first key, a block a statement
+    /// keyword, a `;` or the closing `}` before any such `:`. `{}` is the
+    /// empty map. A block also starts with an `if`, `match` or `{`, which
+    /// may end a statement at their `}`, so a map key starting with one
+    /// must be parenthesized.
+    fn at_block_expr(p: &Parser<'a>) -> bool {
+        let tokens = &p.tokens[p.pos + 1..];
+        match tokens.first().map(|t| t.kind) {
+            None | Some(TokenKind::RBRACE) => return false,
+            Some(TokenKind::IF | TokenKind::MATCH | TokenKind::LBRACE) => return true,
+            _ => {}
+        }
+        let mut depth = 0usize;
+        let mut after_operand = false;
+        let mut i = 0;
+        while let Some(token) = tokens.get(i) {
+            match token.kind {
+                TokenKind::LPAREN | TokenKind::LBRACKET | TokenKind::LBRACE => depth += 1,
+                TokenKind::RPAREN | TokenKind::RBRACKET | TokenKind::RBRACE if depth == 0 => {
+                    return true;
+                }
+                TokenKind::RPAREN | TokenKind::RBRACKET | TokenKind::RBRACE => depth -= 1,
+                TokenKind::COLON if depth == 0 => return false,
+                TokenKind::SEMICOLON if depth == 0 => return true,
+                TokenKind::IF => {}
+                TokenKind::FUNCTION
+                    if tokens.get(i + 1).is_some_and(|t| t.kind == TokenKind::LPAREN) => {}
+                kind if depth == 0 && STMT_KEYWORDS.contains(&kind) => return true,
+                // the parameters of a lambda may have `:` in them
+                TokenKind::VERTICAL_BAR if !after_operand => {
+                    i += tokens[i + 1..]
+                        .iter()
+                        .position(|t| t.kind == TokenKind::VERTICAL_BAR)
+                        .map_or(0, |n| n + 1);
+                }
+                TokenKind::EOF => return false,
+                _ => {}
+            }
+            after_operand = matches!(
+                token.kind,
+                TokenKind::IDENTIFIER
+                    | TokenKind::INTEGER
+                    | TokenKind::FLOAT
+                    | TokenKind::LITERALSTRING
+                    | TokenKind::TRUE
+                    | TokenKind::FALSE
+                    | TokenKind::RPAREN
+                    | TokenKind::RBRACKET
+                    | TokenKind::RBRACE
+                    | TokenKind::QUESTION
+            );
+            i += 1;
+        }
+        false
     }
 }
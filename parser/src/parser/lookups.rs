@@ -1,54 +1,337 @@
-
 use lexer::token_type::TokenKind;
-use crate::nodes::exprs::Expr;
+use crate::nodes::exprs::ExprId;
+use crate::nodes::stmts::StmtId;
 use crate::parser::engine::Parser;
 use crate::parser::errors::{LookUpError, ParserError};
 
+/// Precedence levels, from loosest to tightest. Each level has an explicit
+/// strength so the ordering never depends on the declaration order.
+///
+/// | strength | level            | operators                       | associativity |
+/// |----------|------------------|---------------------------------|---------------|
+/// | 0        | `Primary`        | (a whole expression)            |               |
+/// | 1        | `Range`          | `..` `..=`                      | left          |
+/// | 2        | `LogicalOr`      | `\|\|`                            | left          |
+/// | 3        | `LogicalAnd`     | `&&`                            | left          |
+/// | 4        | `Equality`       | `==` `!=`                       | left          |
+/// | 5        | `Comparison`     | `<` `>` `<=` `>=`               | left          |
+/// | 6        | `BitOr`          | `\|`                             | left          |
+/// | 7        | `BitXor`         | `^`                             | left          |
+/// | 8        | `BitAnd`         | `&`                             | left          |
+/// | 9        | `Shift`          | `<<` `>>`                       | left          |
+/// | 10       | `Additive`       | `+` `-`                         | left          |
+/// | 11       | `Multiplicative` | `*` `/` `%`                     | left          |
+/// | 12       | `Cast`           | `as`                            | left          |
+/// | 13       | `Unary`          | prefix `-` `!` `&` `*`          | right         |
+/// | 14       | `Exponent`       | `**`                            | right         |
+/// | 15       | `Postfix`        | calls, indexing, member access  | left          |
+///
+/// A range binds looser than everything else, so `0..n + 1` runs up to
+/// `n + 1` and `a..b == c` is a range ending in a comparison.
+///
+/// A cast binds looser than the prefix operators, so `-x as int` is
+/// `(-x) as int`, and tighter than the arithmetic, so `a * b as f64` casts
+/// only `b`.
+///
+/// Exponentiation binds tighter than the prefix operators, so `-2 ** 2` is
+/// `-(2 ** 2)`, while `2 ** -1` still parses because the right operand may
+/// start with a prefix operator.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BindingPower {
     Primary,
-    Mul,
-    Div,
-    Add,
-    Sub,
+    Range,
+    LogicalOr,
+    LogicalAnd,
+    Equality,
+    Comparison,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Shift,
+    Additive,
+    Multiplicative,
+    Cast,
+    Unary,
+    Exponent,
+    Postfix,
+}
 
-    Or,
-    And,
+/// Which side groups first when an operator meets another of the same level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Associativity {
+    // `a - b - c` is `(a - b) - c`
+    Left,
+    // `a ** b ** c` is `a ** (b ** c)`
+    Right,
 }
 
 impl BindingPower {
+    /// Numeric strength of the level, higher binds tighter.
+    pub fn strength(self) -> u8 {
+        match self {
+            BindingPower::Primary => 0,
+            BindingPower::Range => 1,
+            BindingPower::LogicalOr => 2,
+            BindingPower::LogicalAnd => 3,
+            BindingPower::Equality => 4,
+            BindingPower::Comparison => 5,
+            BindingPower::BitOr => 6,
+            BindingPower::BitXor => 7,
+            BindingPower::BitAnd => 8,
+            BindingPower::Shift => 9,
+            BindingPower::Additive => 10,
+            BindingPower::Multiplicative => 11,
+            BindingPower::Cast => 12,
+            BindingPower::Unary => 13,
+            BindingPower::Exponent => 14,
+            BindingPower::Postfix => 15,
+        }
+    }
+
+    /// Level of an infix or postfix operator token, `Primary` for any other token.
     pub fn from_token(kind: TokenKind) -> Self {
-        match kind {
-            TokenKind::PLUS | TokenKind::DASH => BindingPower::Add,
-            TokenKind::STAR | TokenKind::SLASH | TokenKind::MODULO => BindingPower::Mul,
-            TokenKind::VERTICAL_BAR => BindingPower::Or,
-            TokenKind::AMPER => BindingPower::And,
-            _ => BindingPower::Primary,
+        infix_binding(kind).map_or(BindingPower::Primary, |(bp, _)| bp)
+    }
+
+    /// Level the right operand of an operator at this level is parsed at.
+    /// Stopping at the same level makes the operator left-associative,
+    /// dropping one level below lets it recurse into itself.
+    pub fn right_operand(self, assoc: Associativity) -> Self {
+        match assoc {
+            Associativity::Left => self,
+            Associativity::Right => self.lower(),
         }
     }
+
+    /// The level just below this one.
+    fn lower(self) -> Self {
+        Self::LEVELS
+            .iter()
+            .copied()
+            .find(|bp| bp.strength() + 1 == self.strength())
+            .unwrap_or(BindingPower::Primary)
+    }
+
+    const LEVELS: [BindingPower; 16] = [
+        BindingPower::Primary,
+        BindingPower::Range,
+        BindingPower::LogicalOr,
+        BindingPower::LogicalAnd,
+        BindingPower::Equality,
+        BindingPower::Comparison,
+        BindingPower::BitOr,
+        BindingPower::BitXor,
+        BindingPower::BitAnd,
+        BindingPower::Shift,
+        BindingPower::Additive,
+        BindingPower::Multiplicative,
+        BindingPower::Cast,
+        BindingPower::Unary,
+        BindingPower::Exponent,
+        BindingPower::Postfix,
+    ];
 }
 
-pub type NudFn<'a> = fn(&mut Parser<'a>) -> Result<Box<dyn Expr + 'a>, ParserError>;
-pub type LedFn<'a> = fn(&mut Parser<'a>,BindingPower, Box<dyn Expr + 'a>) -> Result<Box<dyn Expr + 'a>, ParserError>;
+impl PartialOrd for BindingPower {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
-pub fn get_nud_fn(kind: TokenKind) -> Result<NudFn<'static>, LookUpError> {
-    match kind {
-        TokenKind::INTEGER => Ok(Parser::parse_primary_expr),
-        _ => Err(LookUpError::NotFound(format!("Nud function for token kind {:?} not found", kind))),
+impl Ord for BindingPower {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.strength().cmp(&other.strength())
     }
 }
 
+/// Level and associativity of every infix and postfix operator, `None` for
+/// tokens that cannot follow an operand.
+pub fn infix_binding(kind: TokenKind) -> Option<(BindingPower, Associativity)> {
+    let bp = match kind {
+        TokenKind::DOUBLE_DOT | TokenKind::DOUBLE_DOT_EQUAL => BindingPower::Range,
+        TokenKind::DOUBLE_VERTICAL_BAR => BindingPower::LogicalOr,
+        TokenKind::DOUBLE_AMPER => BindingPower::LogicalAnd,
+        TokenKind::EQUAL_EQUAL | TokenKind::NOT_EQUAL => BindingPower::Equality,
+        TokenKind::LESS | TokenKind::GREATER | TokenKind::LESS_EQUAL | TokenKind::GREATER_EQUAL => {
+            BindingPower::Comparison
+        }
+        TokenKind::VERTICAL_BAR => BindingPower::BitOr,
+        TokenKind::CARET => BindingPower::BitXor,
+        TokenKind::AMPER => BindingPower::BitAnd,
+        TokenKind::DOUBLE_LESS | TokenKind::DOUBLE_GREATER => BindingPower::Shift,
+        TokenKind::PLUS | TokenKind::DASH => BindingPower::Additive,
+        TokenKind::STAR | TokenKind::SLASH | TokenKind::MODULO => BindingPower::Multiplicative,
+        TokenKind::DOUBLE_STAR => return Some((BindingPower::Exponent, Associativity::Right)),
+        TokenKind::AS => BindingPower::Cast,
+        TokenKind::LPAREN | TokenKind::LBRACKET | TokenKind::DOT => BindingPower::Postfix,
+        _ => return None,
+    };
+    Some((bp, Associativity::Left))
+}
+
+pub type NudFn<'a> = fn(&mut Parser<'a>) -> Result<ExprId, ParserError<'a>>;
+pub type LedFn<'a> = fn(&mut Parser<'a>,BindingPower, ExprId) -> Result<ExprId, ParserError<'a>>;
+
+pub type StmtFn<'a> = fn(&mut Parser<'a>) -> Result<StmtId, ParserError<'a>>;
+
+/// Token kinds that can start a type expression.
+pub const TYPE_START: &[TokenKind] = &[
+    TokenKind::IDENTIFIER,
+    TokenKind::FUNCTION,
+    TokenKind::LBRACKET,
+    TokenKind::LBRACE,
+    TokenKind::LPAREN,
+];
+
+/// Token kinds that can start an expression, reported when one is missing.
+pub const EXPR_START: &[TokenKind] = &[
+    TokenKind::INTEGER,
+    TokenKind::FLOAT,
+    TokenKind::LITERALSTRING,
+    TokenKind::TRUE,
+    TokenKind::FALSE,
+    TokenKind::IDENTIFIER,
+    TokenKind::LPAREN,
+    TokenKind::LBRACKET,
+    TokenKind::LBRACE,
+    TokenKind::DASH,
+    TokenKind::NEGATION,
+    TokenKind::AMPER,
+    TokenKind::STAR,
+    TokenKind::MATCH,
+    TokenKind::IF,
+    TokenKind::FUNCTION,
+    TokenKind::VERTICAL_BAR,
+    TokenKind::DOUBLE_VERTICAL_BAR,
+];
+
+/// Keywords that start a statement, where error recovery resumes parsing.
+pub const STMT_KEYWORDS: &[TokenKind] = &[
+    TokenKind::LET,
+    TokenKind::VAR,
+    TokenKind::FUNCTION,
+    TokenKind::STRUCT,
+    TokenKind::ENUM,
+    TokenKind::IMPORT,
+    TokenKind::PUB,
+    TokenKind::IF,
+    TokenKind::WHILE,
+    TokenKind::FOR,
+    TokenKind::RETURN,
+    TokenKind::BREAK,
+    TokenKind::CONTINUE,
+];
+
+/// Token kinds that can start a pattern.
+pub const PATTERN_START: &[TokenKind] = &[
+    TokenKind::IDENTIFIER,
+    TokenKind::INTEGER,
+    TokenKind::FLOAT,
+    TokenKind::LITERALSTRING,
+    TokenKind::TRUE,
+    TokenKind::FALSE,
+    TokenKind::DASH,
+    TokenKind::LPAREN,
+];
+
+/// Token kinds with a binary led function registered.
+pub const BINARY_OPS: &[TokenKind] = &[
+    TokenKind::DOUBLE_DOT,
+    TokenKind::DOUBLE_DOT_EQUAL,
+    TokenKind::DOUBLE_VERTICAL_BAR,
+    TokenKind::DOUBLE_AMPER,
+    TokenKind::EQUAL_EQUAL,
+    TokenKind::NOT_EQUAL,
+    TokenKind::LESS,
+    TokenKind::GREATER,
+    TokenKind::LESS_EQUAL,
+    TokenKind::GREATER_EQUAL,
+    TokenKind::VERTICAL_BAR,
+    TokenKind::CARET,
+    TokenKind::AMPER,
+    TokenKind::DOUBLE_LESS,
+    TokenKind::DOUBLE_GREATER,
+    TokenKind::PLUS,
+    TokenKind::DASH,
+    TokenKind::STAR,
+    TokenKind::SLASH,
+    TokenKind::MODULO,
+    TokenKind::DOUBLE_STAR,
+];
+
+pub fn get_nud_fn<'a>(kind: TokenKind) -> Result<NudFn<'a>, LookUpError> {
+    nud_entry(kind)
+        .map(|(_, nud)| nud)
+        .ok_or(LookUpError::NudNotFound(kind))
+}
+
 pub fn get_led_fn<'a>(kind: TokenKind) -> Result<LedFn<'a>, LookUpError> {
-    match kind {
-        TokenKind::PLUS | TokenKind::DASH => Ok(Parser::parse_binary_expr as LedFn<'a>),
-        TokenKind::STAR | TokenKind::SLASH | TokenKind::MODULO => Ok(Parser::parse_binary_expr as LedFn<'a>),
-        TokenKind::VERTICAL_BAR => Ok(Parser::parse_binary_expr),
-        TokenKind::AMPER => Ok(Parser::parse_binary_expr),
-        _ => Err(LookUpError::NotFound(format!("Led function for token kind {:?} not found", kind))),
-    }
+    led_entry(kind)
+        .map(|(_, led)| led)
+        .ok_or(LookUpError::LedNotFound(kind))
 }
 
+/// Name of the prefix function `get_nud_fn` dispatches to, for traces.
+pub fn nud_fn_name(kind: TokenKind) -> Option<&'static str> {
+    nud_entry(kind).map(|(name, _)| name)
+}
 
+/// Name of the infix function `get_led_fn` dispatches to, for traces.
+pub fn led_fn_name(kind: TokenKind) -> Option<&'static str> {
+    led_entry(kind).map(|(name, _)| name)
+}
 
+fn nud_entry<'a>(kind: TokenKind) -> Option<(&'static str, NudFn<'a>)> {
+    match kind {
+        TokenKind::INTEGER
+        | TokenKind::FLOAT
+        | TokenKind::LITERALSTRING
+        | TokenKind::TRUE
+        | TokenKind::FALSE
+        | TokenKind::IDENTIFIER => Some(("parse_primary_expr", Parser::parse_primary_expr)),
+        TokenKind::LPAREN => Some(("parse_grouping_expr", Parser::parse_grouping_expr)),
+        TokenKind::LBRACKET => Some(("parse_array_expr", Parser::parse_array_expr)),
+        TokenKind::LBRACE => Some(("parse_brace_expr", Parser::parse_brace_expr)),
+        TokenKind::IF => Some(("parse_if_expr", Parser::parse_if_expr)),
+        TokenKind::MATCH => Some(("parse_match_expr", Parser::parse_match_expr)),
+        TokenKind::FUNCTION | TokenKind::VERTICAL_BAR | TokenKind::DOUBLE_VERTICAL_BAR => {
+            Some(("parse_lambda_expr", Parser::parse_lambda_expr))
+        }
+        TokenKind::DASH | TokenKind::NEGATION | TokenKind::AMPER | TokenKind::STAR => {
+            Some(("parse_unary_expr", Parser::parse_unary_expr))
+        }
+        _ => None,
+    }
+}
 
+fn led_entry<'a>(kind: TokenKind) -> Option<(&'static str, LedFn<'a>)> {
+    match kind {
+        k if BINARY_OPS.contains(&k) => Some(("parse_binary_expr", Parser::parse_binary_expr)),
+        TokenKind::LPAREN => Some(("parse_call_expr", Parser::parse_call_expr)),
+        TokenKind::LBRACKET => Some(("parse_index_expr", Parser::parse_index_expr)),
+        TokenKind::DOT => Some(("parse_member_expr", Parser::parse_member_expr)),
+        TokenKind::AS => Some(("parse_cast_expr", Parser::parse_cast_expr)),
+        _ => None,
+    }
+}
 
+/// Statement parse function for a keyword that starts a statement, `None`
+/// when the statement is an expression statement or an assignment.
+pub fn get_stmt_fn<'a>(kind: TokenKind) -> Option<StmtFn<'a>> {
+    match kind {
+        TokenKind::LET | TokenKind::VAR => Some(Parser::parse_let_stmt as StmtFn<'a>),
+        TokenKind::FUNCTION => Some(Parser::parse_func_decl),
+        TokenKind::STRUCT => Some(Parser::parse_struct_decl),
+        TokenKind::ENUM => Some(Parser::parse_enum_decl),
+        TokenKind::IMPORT => Some(Parser::parse_import_decl),
+        TokenKind::PUB => Some(Parser::parse_pub_decl),
+        TokenKind::LBRACE => Some(Parser::parse_block_stmt),
+        TokenKind::IF => Some(Parser::parse_if_stmt),
+        TokenKind::WHILE => Some(Parser::parse_while_stmt),
+        TokenKind::FOR => Some(Parser::parse_for_stmt),
+        TokenKind::RETURN => Some(Parser::parse_return_stmt),
+        TokenKind::BREAK => Some(Parser::parse_break_stmt),
+        TokenKind::CONTINUE => Some(Parser::parse_continue_stmt),
+        _ => None,
+    }
+}
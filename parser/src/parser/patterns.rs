@@ -0,0 +1,152 @@
+use crate::nodes::exprs::LiteralValue;
+use crate::nodes::patterns::{Pattern, PatternKind};
+use crate::parser::engine::Parser;
+use crate::parser::errors::ParserError;
+use crate::parser::lookups::PATTERN_START;
+use lexer::token::Token;
+use lexer::token_type::TokenKind;
+
+/// Pattern parsing. A bare name binds, a name followed by `(` or a dotted
+/// path names an enum variant, so `Color.Red` matches a variant while `red`
+/// binds whatever is there.
+impl<'a> Parser<'a> {
+    pub fn parse_pattern(p: &mut Parser<'a>) -> Result<Pattern, ParserError<'a>> {
+        p.nested(Self::parse_pattern_inner)
+    }
+
+    fn parse_pattern_inner(p: &mut Parser<'a>) -> Result<Pattern, ParserError<'a>> {
+        let token = *p
+            .get_current_token()
+            .ok_or_else(|| p.unexpected(PATTERN_START))?;
+        match token.kind {
+            TokenKind::IDENTIFIER if token.value == "_" => {
+                p.advance();
+                Ok(Pattern::new(PatternKind::Wildcard, token.span))
+            }
+            TokenKind::IDENTIFIER => Self::parse_variant_pattern(p),
+            TokenKind::INTEGER
+            | TokenKind::FLOAT
+            | TokenKind::LITERALSTRING
+            | TokenKind::TRUE
+            | TokenKind::FALSE => {
+                p.advance();
+                let value = Self::literal_pattern_value(token, false)?;
+                Ok(Pattern::new(PatternKind::Literal(value), token.span))
+            }
+            TokenKind::DASH => {
+                // only numbers can be negated
+                p.advance();
+                let number = *p
+                    .get_current_token()
+                    .filter(|t| matches!(t.kind, TokenKind::INTEGER | TokenKind::FLOAT))
+                    .ok_or_else(|| p.unexpected(&[TokenKind::INTEGER, TokenKind::FLOAT]))?;
+                p.advance();
+                let value = Self::literal_pattern_value(number, true)?;
+                Ok(Pattern::new(
+                    PatternKind::Literal(value),
+                    token.span.combine(&number.span),
+                ))
+            }
+            TokenKind::LPAREN => {
+                let open = p.expect(TokenKind::LPAREN)?;
+                let (mut items, trailing_comma) = Self::parse_pattern_list(p, open)?;
+                let close = p.expect(TokenKind::RPAREN)?;
+                let span = open.span.combine(&close.span);
+                // `(p)` only groups, a one element tuple is written `(p,)`
+                if items.len() == 1 && !trailing_comma {
+                    let mut inner = items.remove(0);
+                    inner.span = span;
+                    Ok(inner)
+                } else {
+                    Ok(Pattern::new(PatternKind::Tuple(items), span))
+                }
+            }
+            _ => Err(p.unexpected(PATTERN_START)),
+        }
+    }
+
+    /// Parses a binding `x`, or a variant `Name(p, ...)` or `Enum.Name(p, ...)`.
+    fn parse_variant_pattern(p: &mut Parser<'a>) -> Result<Pattern, ParserError<'a>> {
+        let first = p.expect(TokenKind::IDENTIFIER)?;
+        let mut path = vec![first.value.to_string()];
+        let mut span = first.span;
+        while p.at(TokenKind::DOT) {
+            p.advance();
+            let segment = p.expect(TokenKind::IDENTIFIER)?;
+            path.push(segment.value.to_string());
+            span = span.combine(&segment.span);
+        }
+
+        let fields = if p.at(TokenKind::LPAREN) {
+            let open = p.expect(TokenKind::LPAREN)?;
+            let (fields, _) = Self::parse_pattern_list(p, open)?;
+            let close = p.expect(TokenKind::RPAREN)?;
+            span = span.combine(&close.span);
+            Some(fields)
+        } else {
+            None
+        };
+
+        let kind = match (path.len(), fields) {
+            (1, None) => PatternKind::Binding(path.remove(0)),
+            (_, fields) => PatternKind::Variant { path, fields },
+        };
+        Ok(Pattern::new(kind, span))
+    }
+
+    /// Parses patterns up to, but not including, the `)` matching `open`.
+    /// Also returns whether the list ended with a comma.
+    fn parse_pattern_list(
+        p: &mut Parser<'a>,
+        open: Token<'a>,
+    ) -> Result<(Vec<Pattern>, bool), ParserError<'a>> {
+        let mut items = Vec::new();
+        let mut trailing_comma = false;
+        while !p.at(TokenKind::RPAREN) {
+            if p.at_end() {
+                return Err(ParserError::UnclosedDelimiter {
+                    open,
+                    close: TokenKind::RPAREN,
+                });
+            }
+            items.push(Self::parse_pattern(p)?);
+            trailing_comma = p.at(TokenKind::COMMA);
+            match p.get_current_token() {
+                Some(t) if t.kind == TokenKind::COMMA => p.advance(),
+                Some(t) if t.kind == TokenKind::RPAREN => {}
+                Some(t) if PATTERN_START.contains(&t.kind) => {
+                    return Err(ParserError::MissingComma { span: t.span });
+                }
+                _ => {
+                    return Err(ParserError::UnclosedDelimiter {
+                        open,
+                        close: TokenKind::RPAREN,
+                    });
+                }
+            }
+        }
+        Ok((items, trailing_comma))
+    }
+
+    fn literal_pattern_value(
+        token: Token<'a>,
+        negative: bool,
+    ) -> Result<LiteralValue, ParserError<'a>> {
+        let sign = if negative { -1 } else { 1 };
+        match token.kind {
+            TokenKind::INTEGER => token
+                .value
+                .parse::<i128>()
+                .map(|v| LiteralValue::Int(sign * v))
+                .map_err(|_| ParserError::InvalidLiteral { token }),
+            TokenKind::FLOAT => token
+                .value
+                .parse::<f64>()
+                .map(|v| LiteralValue::Float(sign as f64 * v))
+                .map_err(|_| ParserError::InvalidLiteral { token }),
+            TokenKind::TRUE => Ok(LiteralValue::Bool(true)),
+            TokenKind::FALSE => Ok(LiteralValue::Bool(false)),
+            _ => Self::string_value(token).map(LiteralValue::Str),
+        }
+    }
+}
@@ -0,0 +1,361 @@
+use crate::lints::Lint;
+use crate::nodes::stmts::{Block, Program, StmtId, StmtKind};
+use crate::parser::engine::{Parser, StructScope};
+use crate::parser::errors::ParserError;
+use crate::parser::lookups::{get_stmt_fn, BindingPower, STMT_KEYWORDS};
+use lexer::token::Token;
+use lexer::token_type::TokenKind;
+
+/// Statement parsing. Keywords that start a statement are dispatched through
+/// the statement table in `lookups`, anything else is an expression statement
+/// or an assignment.
+impl<'a> Parser<'a> {
+    /// Parses statements up to the end of input. A statement that fails to
+    /// parse is recorded in `p.errors` and replaced by a `StmtKind::Error`, so
+    /// the program is always complete and holds every error at once. The
+    /// arena is moved out of the parser into the program.
+    pub fn parse_program(p: &mut Parser<'a>) -> Program {
+        p.struct_scopes.push(StructScope::default());
+        let mut stmts = Vec::new();
+        while !p.at_end() {
+            if Self::skip_empty_stmt(p) {
+                continue;
+            }
+            stmts.push(Self::parse_stmt_or_recover(p));
+        }
+        Self::close_struct_scope(p);
+        Program {
+            ast: std::mem::take(&mut p.ast),
+            stmts,
+        }
+    }
+
+    /// Parses a statement, or records the error, skips to the next statement
+    /// boundary and returns a `StmtKind::Error` in its place.
+    fn parse_stmt_or_recover(p: &mut Parser<'a>) -> StmtId {
+        let start = p.pos;
+        match Self::parse_stmt(p) {
+            Ok(stmt) => stmt,
+            Err(error) => {
+                let error_span = error.span();
+                // past the nesting limit only the first error says anything
+                let first_too_deep = matches!(error, ParserError::NestingTooDeep { .. })
+                    && !p.errors.iter().any(|e| matches!(e, ParserError::NestingTooDeep { .. }));
+                // nor does a second error at the token of the last one, as in
+                // `let x = ) 1;`, which is also missing its `;` there
+                let same_token = p.errors.last().is_some_and(|e| e.span().start == error_span.start);
+                if (!p.nesting_exceeded || first_too_deep) && !same_token {
+                    p.errors.push(error);
+                }
+                Self::synchronize(p, start);
+                let span = match (p.tokens.get(start), p.pos.checked_sub(1)) {
+                    (Some(first), Some(last)) if p.pos > start => {
+                        first.span.combine(&p.tokens[last].span)
+                    }
+                    _ => error_span,
+                };
+                p.alloc_stmt(StmtKind::Error, span)
+            }
+        }
+    }
+
+    /// Skips tokens up to a statement boundary: just past a `;`, or before a
+    /// `}` or a keyword that starts a statement. A `}` closing a `{` the
+    /// statement opened, as in `{"a": 1 "b": 2}`, is skipped with it. At
+    /// least one token is skipped when the statement did not consume any, so
+    /// recovery always makes progress.
+    fn synchronize(p: &mut Parser<'a>, start: usize) {
+        if p.pos == start && !p.at_end() {
+            p.advance();
+        }
+        let mut open = p.tokens[start..p.pos].iter().fold(0usize, |open, t| match t.kind {
+            TokenKind::LBRACE => open + 1,
+            TokenKind::RBRACE => open.saturating_sub(1),
+            _ => open,
+        });
+        while let Some(token) = p.get_current_token() {
+            match token.kind {
+                TokenKind::LBRACE => {
+                    open += 1;
+                    p.advance();
+                }
+                TokenKind::RBRACE if open > 0 => {
+                    open -= 1;
+                    p.advance();
+                }
+                TokenKind::EOF | TokenKind::RBRACE => break,
+                TokenKind::SEMICOLON => {
+                    p.advance();
+                    break;
+                }
+                kind if STMT_KEYWORDS.contains(&kind) => break,
+                _ => p.advance(),
+            }
+        }
+    }
+
+    /// Leaves the innermost block, recording an error for every struct
+    /// literal in it that leaves out a field of the struct the block
+    /// declares under that name. Literals of structs the block does not
+    /// declare are handed to the enclosing block. Those of structs declared
+    /// nowhere, or more than once in one block, are left alone.
+    fn close_struct_scope(p: &mut Parser<'a>) {
+        let Some(scope) = p.struct_scopes.pop() else {
+            return;
+        };
+        let mut undeclared = Vec::new();
+        for (name, span, given) in scope.inits {
+            let declared = match scope.fields.get(&name) {
+                Some(Some(declared)) => declared,
+                Some(None) => continue,
+                None => {
+                    undeclared.push((name, span, given));
+                    continue;
+                }
+            };
+            let missing: Vec<String> = declared
+                .iter()
+                .filter(|field| !given.contains(field))
+                .cloned()
+                .collect();
+            if !missing.is_empty() {
+                // among the errors of the block, which come before it ends
+                let at = p.errors.partition_point(|e| e.span().start <= span.start);
+                p.errors.insert(
+                    at,
+                    ParserError::MissingFields {
+                        name,
+                        fields: missing,
+                        span,
+                    },
+                );
+            }
+        }
+        if let Some(outer) = p.struct_scopes.last_mut() {
+            outer.inits.extend(undeclared);
+        }
+    }
+
+    pub fn parse_stmt(p: &mut Parser<'a>) -> Result<StmtId, ParserError<'a>> {
+        let kind = p.get_current_token().map(|t| t.kind);
+        // `func(` starts a lambda, not a declaration
+        let lambda = p.at(TokenKind::FUNCTION)
+            && p.get_k_token(1).is_some_and(|t| t.kind == TokenKind::LPAREN);
+        p.nested(|p| match kind.and_then(get_stmt_fn) {
+            Some(stmt_fn) if !lambda => stmt_fn(p),
+            _ => Self::parse_expr_stmt(p),
+        })
+    }
+
+    /// Skips a lone `;`, an empty statement the `redundant_semicolons` lint
+    /// reports separately.
+    fn skip_empty_stmt(p: &mut Parser<'a>) -> bool {
+        let empty = p.at(TokenKind::SEMICOLON);
+        if empty {
+            p.advance();
+        }
+        empty
+    }
+
+    /// Parses `{ stmt* }`. The block has a tail when its last statement is
+    /// an expression, `if`, `match` or block not followed by a `;`.
+    pub fn parse_block(p: &mut Parser<'a>) -> Result<Block, ParserError<'a>> {
+        let open = p.expect(TokenKind::LBRACE)?;
+        // struct literals are allowed again inside a block of a condition,
+        // as in `if if a { P {} } else { q } { ... }`
+        let saved = std::mem::replace(&mut p.struct_literals, true);
+        p.struct_scopes.push(StructScope::default());
+        let block = Self::parse_block_stmts(p, open);
+        Self::close_struct_scope(p);
+        p.struct_literals = saved;
+        block
+    }
+
+    fn parse_block_stmts(p: &mut Parser<'a>, open: Token<'a>) -> Result<Block, ParserError<'a>> {
+        let mut stmts = Vec::new();
+        let mut tail = false;
+        while !p.at(TokenKind::RBRACE) && !p.at_end() {
+            if Self::skip_empty_stmt(p) {
+                tail = false;
+                continue;
+            }
+            let stmt = Self::parse_stmt_or_recover(p);
+            stmts.push(stmt);
+            let after_semi = p.tokens[p.pos - 1].kind == TokenKind::SEMICOLON;
+            tail = p.at(TokenKind::RBRACE)
+                && !after_semi
+                && matches!(
+                    p.ast.stmt(stmt),
+                    StmtKind::Expr(_) | StmtKind::If { .. } | StmtKind::Block(_)
+                );
+        }
+        let close = p.expect(TokenKind::RBRACE)?;
+        Ok(Block {
+            stmts,
+            span: open.span.combine(&close.span),
+            tail,
+        })
+    }
+
+    pub fn parse_block_stmt(p: &mut Parser<'a>) -> Result<StmtId, ParserError<'a>> {
+        let block = Self::parse_block(p)?;
+        let span = block.span;
+        Ok(p.alloc_stmt(StmtKind::Block(block), span))
+    }
+
+    /// Parses `expr;`, `place = expr;` or a `match` statement. The `;` may be
+    /// left out after the last expression of a block.
+    pub fn parse_expr_stmt(p: &mut Parser<'a>) -> Result<StmtId, ParserError<'a>> {
+        // a `match` statement ends at its `}` like a block, the `;` is optional
+        if p.at(TokenKind::MATCH) {
+            let expr = Self::parse_match_expr(p)?;
+            let mut span = p.ast.expr_span(expr);
+            if p.at(TokenKind::SEMICOLON) {
+                let semi = p.expect(TokenKind::SEMICOLON)?;
+                span = span.combine(&semi.span);
+            }
+            return Ok(p.alloc_stmt(StmtKind::Expr(expr), span));
+        }
+
+        p.stmt_start = p.pos;
+        let expr = Self::parse_expr(p, BindingPower::Primary)?;
+        let expr_span = p.ast.expr_span(expr);
+
+        if p.at(TokenKind::EQUAL) {
+            if !p.ast.expr(expr).is_place() {
+                return Err(ParserError::InvalidAssignTarget { span: expr_span });
+            }
+            p.advance();
+            let value = Self::parse_expr_or_error(p)?;
+            let semi = p.expect(TokenKind::SEMICOLON)?;
+            let span = expr_span.combine(&semi.span);
+            return Ok(p.alloc_stmt(StmtKind::Assign { target: expr, value }, span));
+        }
+
+        // the last expression of a block may leave out the `;`, the block
+        // then evaluates to it
+        if p.at(TokenKind::RBRACE) {
+            return Ok(p.alloc_stmt(StmtKind::Expr(expr), expr_span));
+        }
+        let semi = p.expect(TokenKind::SEMICOLON)?;
+        if !p.ast.expr(expr).has_side_effects() {
+            p.lints.push((Lint::Unused, expr_span));
+        }
+        let span = expr_span.combine(&semi.span);
+        Ok(p.alloc_stmt(StmtKind::Expr(expr), span))
+    }
+
+    /// Parses `let name: type = expr;` or `var name: type = expr;`, the type
+    /// and the value are optional.
+    pub fn parse_let_stmt(p: &mut Parser<'a>) -> Result<StmtId, ParserError<'a>> {
+        let keyword = *p
+            .get_current_token()
+            .ok_or_else(|| p.unexpected(&[TokenKind::LET, TokenKind::VAR]))?;
+        p.advance();
+        let name = p.expect(TokenKind::IDENTIFIER)?;
+        let ty = if p.at(TokenKind::COLON) {
+            p.advance();
+            Some(Self::parse_type_expr(p)?)
+        } else {
+            None
+        };
+        let value = if p.at(TokenKind::EQUAL) {
+            p.advance();
+            Some(Self::parse_expr_or_error(p)?)
+        } else {
+            None
+        };
+        let semi = p.expect(TokenKind::SEMICOLON)?;
+        Ok(p.alloc_stmt(
+            StmtKind::Let {
+                mutable: keyword.kind == TokenKind::VAR,
+                name: name.value.to_string(),
+                name_span: name.span,
+                ty,
+                value,
+            },
+            keyword.span.combine(&semi.span),
+        ))
+    }
+
+    /// Parses `if cond { ... }` with an optional `else { ... }` or `else if`.
+    pub fn parse_if_stmt(p: &mut Parser<'a>) -> Result<StmtId, ParserError<'a>> {
+        let keyword = p.expect(TokenKind::IF)?;
+        let cond = Self::parse_cond_expr(p)?;
+        let then_branch = Self::parse_block(p)?;
+        let mut span = keyword.span.combine(&then_branch.span);
+
+        let else_branch = if p.at(TokenKind::ELSE) {
+            p.advance();
+            let branch = if p.at(TokenKind::IF) {
+                Self::parse_if_stmt(p)?
+            } else {
+                Self::parse_block_stmt(p)?
+            };
+            span = span.combine(&p.ast.stmt_span(branch));
+            Some(branch)
+        } else {
+            None
+        };
+
+        Ok(p.alloc_stmt(
+            StmtKind::If {
+                cond,
+                then_branch,
+                else_branch,
+            },
+            span,
+        ))
+    }
+
+    pub fn parse_while_stmt(p: &mut Parser<'a>) -> Result<StmtId, ParserError<'a>> {
+        let keyword = p.expect(TokenKind::WHILE)?;
+        let cond = Self::parse_cond_expr(p)?;
+        let body = Self::parse_block(p)?;
+        let span = keyword.span.combine(&body.span);
+        Ok(p.alloc_stmt(StmtKind::While { cond, body }, span))
+    }
+
+    /// Parses `for name in iterable { ... }`.
+    pub fn parse_for_stmt(p: &mut Parser<'a>) -> Result<StmtId, ParserError<'a>> {
+        let keyword = p.expect(TokenKind::FOR)?;
+        let var = p.expect(TokenKind::IDENTIFIER)?;
+        p.expect(TokenKind::IN)?;
+        let iterable = Self::parse_cond_expr(p)?;
+        let body = Self::parse_block(p)?;
+        let span = keyword.span.combine(&body.span);
+        Ok(p.alloc_stmt(
+            StmtKind::For {
+                var: var.value.to_string(),
+                var_span: var.span,
+                iterable,
+                body,
+            },
+            span,
+        ))
+    }
+
+    /// Parses `return;` or `return expr;`.
+    pub fn parse_return_stmt(p: &mut Parser<'a>) -> Result<StmtId, ParserError<'a>> {
+        let keyword = p.expect(TokenKind::RETURN)?;
+        let value = if p.at(TokenKind::SEMICOLON) {
+            None
+        } else {
+            Some(Self::parse_expr(p, BindingPower::Primary)?)
+        };
+        let semi = p.expect(TokenKind::SEMICOLON)?;
+        Ok(p.alloc_stmt(StmtKind::Return(value), keyword.span.combine(&semi.span)))
+    }
+
+    pub fn parse_break_stmt(p: &mut Parser<'a>) -> Result<StmtId, ParserError<'a>> {
+        let keyword = p.expect(TokenKind::BREAK)?;
+        let semi = p.expect(TokenKind::SEMICOLON)?;
+        Ok(p.alloc_stmt(StmtKind::Break, keyword.span.combine(&semi.span)))
+    }
+
+    pub fn parse_continue_stmt(p: &mut Parser<'a>) -> Result<StmtId, ParserError<'a>> {
+        let keyword = p.expect(TokenKind::CONTINUE)?;
+        let semi = p.expect(TokenKind::SEMICOLON)?;
+        Ok(p.alloc_stmt(StmtKind::Continue, keyword.span.combine(&semi.span)))
+    }
+}
@@ -0,0 +1,78 @@
+use std::fmt::{self, Display};
+
+use lexer::token_type::TokenKind;
+
+use crate::parser::lookups::BindingPower;
+
+/// One decision of the expression parser, recorded when `Parser::trace` is
+/// set. `pos` is the index of the current token and `depth` how deep the
+/// decision is nested in `parse_expr` calls and the nud and led functions
+/// they dispatch to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceEvent {
+    pub depth: usize,
+    pub pos: usize,
+    pub step: TraceStep,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceStep {
+    // `parse_expr` was called to parse operators binding tighter than `bp`
+    Enter {
+        bp: BindingPower,
+    },
+    // the first token was dispatched to its prefix function
+    Nud {
+        token: TokenKind,
+        handler: &'static str,
+    },
+    // the binding power of the operator `token` was compared with the one
+    // `parse_expr` was called with, it is applied only if it `binds`
+    Compare {
+        token: TokenKind,
+        token_bp: BindingPower,
+        min_bp: BindingPower,
+        binds: bool,
+    },
+    // the operator was dispatched to its infix function
+    Led {
+        token: TokenKind,
+        handler: &'static str,
+    },
+    // `parse_expr` returned
+    Exit,
+}
+
+/// One line of the trace, indented by depth:
+/// `  led parse_binary_expr for `+` at 1`.
+impl Display for TraceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for _ in 0..self.depth {
+            write!(f, "  ")?;
+        }
+        match self.step {
+            TraceStep::Enter { bp } => write!(f, "parse_expr {:?}", bp)?,
+            TraceStep::Nud { token, handler } => write!(f, "nud {} for {}", handler, token)?,
+            TraceStep::Compare {
+                token,
+                token_bp,
+                min_bp,
+                binds,
+            } => {
+                let (cmp, action) = if binds {
+                    (">", "apply")
+                } else {
+                    ("<=", "stop")
+                };
+                write!(
+                    f,
+                    "{} {:?} {} {:?}, {}",
+                    token, token_bp, cmp, min_bp, action
+                )?
+            }
+            TraceStep::Led { token, handler } => write!(f, "led {} for {}", handler, token)?,
+            TraceStep::Exit => write!(f, "done")?,
+        }
+        write!(f, " at {}", self.pos)
+    }
+}
@@ -0,0 +1,122 @@
+use crate::nodes::type_exprs::{Primitive, TypeExpr, TypeExprKind};
+use crate::parser::engine::Parser;
+use crate::parser::errors::ParserError;
+use crate::parser::lookups::TYPE_START;
+use lexer::token::SourceSpan;
+use lexer::token_type::TokenKind;
+
+/// Type expression parsing. Types have no operators besides the postfix `?`,
+/// so they are parsed by plain recursive descent instead of the Pratt tables.
+impl<'a> Parser<'a> {
+    /// Parses a type, such as `int`, `[string]`, `{string: f64}`, `(int, bool)`,
+    /// `fn(int) -> bool` or `Point?`.
+    pub fn parse_type_expr(p: &mut Parser<'a>) -> Result<TypeExpr, ParserError<'a>> {
+        p.nested(Self::parse_type_expr_inner)
+    }
+
+    fn parse_type_expr_inner(p: &mut Parser<'a>) -> Result<TypeExpr, ParserError<'a>> {
+        let token = *p
+            .get_current_token()
+            .ok_or_else(|| p.unexpected(TYPE_START))?;
+
+        let mut ty = match token.kind {
+            // `fn` is not a keyword, it only means a function type here
+            TokenKind::IDENTIFIER if token.value == "fn" => Self::parse_function_type(p)?,
+            TokenKind::FUNCTION => Self::parse_function_type(p)?,
+            TokenKind::IDENTIFIER => {
+                p.advance();
+                let kind = match Primitive::from_name(token.value) {
+                    Some(primitive) => TypeExprKind::Primitive(primitive),
+                    None => TypeExprKind::Named(token.value.to_string()),
+                };
+                TypeExpr::new(kind, token.span)
+            }
+            TokenKind::LBRACKET => {
+                p.advance();
+                let elem = Self::parse_type_expr(p)?;
+                let close = p.expect(TokenKind::RBRACKET)?;
+                TypeExpr::new(
+                    TypeExprKind::Array(Box::new(elem)),
+                    token.span.combine(&close.span),
+                )
+            }
+            TokenKind::LBRACE => {
+                p.advance();
+                let key = Self::parse_type_expr(p)?;
+                p.expect(TokenKind::COLON)?;
+                let value = Self::parse_type_expr(p)?;
+                let close = p.expect(TokenKind::RBRACE)?;
+                TypeExpr::new(
+                    TypeExprKind::Map(Box::new(key), Box::new(value)),
+                    token.span.combine(&close.span),
+                )
+            }
+            TokenKind::LPAREN => {
+                let (mut items, trailing_comma, span) = Self::parse_type_list(p)?;
+                // `(T)` only groups, a one element tuple is written `(T,)`
+                if items.len() == 1 && !trailing_comma {
+                    let mut inner = items.remove(0);
+                    inner.span = span;
+                    inner
+                } else {
+                    TypeExpr::new(TypeExprKind::Tuple(items), span)
+                }
+            }
+            _ => return Err(p.unexpected(TYPE_START)),
+        };
+
+        while p.at(TokenKind::QUESTION) {
+            let question = *p
+                .get_current_token()
+                .ok_or_else(|| p.unexpected(&[TokenKind::QUESTION]))?;
+            p.advance();
+            let span = ty.span.combine(&question.span);
+            ty = TypeExpr::new(TypeExprKind::Optional(Box::new(ty)), span);
+        }
+        Ok(ty)
+    }
+
+    /// Parses `fn(A, B) -> C`, the return type is optional.
+    fn parse_function_type(p: &mut Parser<'a>) -> Result<TypeExpr, ParserError<'a>> {
+        let keyword = *p
+            .get_current_token()
+            .ok_or_else(|| p.unexpected(TYPE_START))?;
+        p.advance();
+        let (params, _, params_span) = Self::parse_type_list(p)?;
+        let ret = if p.at(TokenKind::ARROW) {
+            p.advance();
+            Some(Box::new(Self::parse_type_expr(p)?))
+        } else {
+            None
+        };
+        let end = ret.as_ref().map_or(params_span, |ty| ty.span);
+        Ok(TypeExpr::new(
+            TypeExprKind::Function { params, ret },
+            keyword.span.combine(&end),
+        ))
+    }
+
+    /// Parses a parenthesized, comma separated list of types. Also returns
+    /// whether the list ended with a comma and the span of the parentheses.
+    pub(crate) fn parse_type_list(
+        p: &mut Parser<'a>,
+    ) -> Result<(Vec<TypeExpr>, bool, SourceSpan), ParserError<'a>> {
+        let open = p.expect(TokenKind::LPAREN)?;
+        let mut items = Vec::new();
+        let mut trailing_comma = false;
+        while !p.at(TokenKind::RPAREN) {
+            items.push(Self::parse_type_expr(p)?);
+            trailing_comma = false;
+            match p.get_current_token() {
+                Some(t) if t.kind == TokenKind::COMMA => {
+                    p.advance();
+                    trailing_comma = true;
+                }
+                Some(t) if t.kind == TokenKind::RPAREN => {}
+                _ => return Err(p.unexpected(&[TokenKind::COMMA, TokenKind::RPAREN])),
+            }
+        }
+        let close = p.expect(TokenKind::RPAREN)?;
+        Ok((items, trailing_comma, open.span.combine(&close.span)))
+    }
+}
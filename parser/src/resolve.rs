@@ -0,0 +1,182 @@
+//! Name resolution, so far only what lambdas need: the variables each one
+//! captures from the scopes around it.
+//!
+//! A lambda captures a name it uses without binding it when the name is
+//! bound by an enclosing function, block, loop, arm or lambda, since that
+//! binding may be gone by the time the lambda runs. Names bound at the top
+//! level of the program live as long as the program and are not captured,
+//! and neither are names bound nowhere, which are left for a later pass to
+//! report.
+
+use std::collections::HashMap;
+
+use lexer::token::SourceSpan;
+
+use crate::nodes::ast::Ast;
+use crate::nodes::decls::{FuncDecl, Param};
+use crate::nodes::exprs::{ExprId, ExprKind, MatchArm};
+use crate::nodes::stmts::{Block, Program, StmtId, StmtKind};
+use crate::nodes::visitor::{self, Visitor};
+
+/// A variable captured by a lambda.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capture {
+    pub name: String,
+    // the first use of the name in the lambda
+    pub span: SourceSpan,
+}
+
+/// The captures of every lambda in the program, in order of first use. A
+/// lambda nested in another adds its captures to the outer one too, unless
+/// the outer one binds them.
+///
+/// # Example
+/// ```
+/// use parser::parser::engine::Parser;
+/// let src = "func counter(start) { var n = start; return || n + 1; }";
+/// let tokens = lexer::tokenize(src, "example").unwrap();
+/// let mut parser = Parser::new(&tokens, 0);
+/// let program = Parser::parse_program(&mut parser);
+/// let captures = parser::resolve::captures(&program);
+/// let names: Vec<_> = captures.values().flatten().map(|c| c.name.as_str()).collect();
+/// assert_eq!(names, ["n"]);
+/// ```
+pub fn captures(program: &Program) -> HashMap<ExprId, Vec<Capture>> {
+    let mut resolver = Resolver {
+        scopes: vec![Scope::default()],
+        lambdas: Vec::new(),
+        captures: HashMap::new(),
+    };
+    resolver.visit_program(program);
+    resolver.captures
+}
+
+#[derive(Default)]
+struct Scope {
+    names: Vec<String>,
+    // how many lambdas enclose the scope
+    lambdas: usize,
+}
+
+struct Resolver {
+    // innermost last, the first one is the top level
+    scopes: Vec<Scope>,
+    // the lambdas being visited, innermost last
+    lambdas: Vec<ExprId>,
+    captures: HashMap<ExprId, Vec<Capture>>,
+}
+
+impl Resolver {
+    fn push_scope(&mut self) {
+        self.scopes.push(Scope {
+            names: Vec::new(),
+            lambdas: self.lambdas.len(),
+        });
+    }
+
+    fn pop_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn bind(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.names.push(name.to_string());
+        }
+    }
+
+    fn bind_params(&mut self, params: &[Param]) {
+        for param in params {
+            self.bind(&param.name);
+        }
+    }
+
+    /// Records `name` as captured by every lambda between its binding and
+    /// the use.
+    fn use_name(&mut self, name: &str, span: SourceSpan) {
+        let Some(index) = self
+            .scopes
+            .iter()
+            .rposition(|scope| scope.names.iter().any(|n| n == name))
+        else {
+            return;
+        };
+        if index == 0 {
+            return;
+        }
+        for lambda in &self.lambdas[self.scopes[index].lambdas..] {
+            let captures = self.captures.entry(*lambda).or_default();
+            if !captures.iter().any(|c| c.name == name) {
+                captures.push(Capture {
+                    name: name.to_string(),
+                    span,
+                });
+            }
+        }
+    }
+}
+
+impl Visitor for Resolver {
+    fn visit_stmt(&mut self, ast: &Ast, stmt: StmtId) {
+        match ast.stmt(stmt) {
+            // the value is resolved before the name is bound, so in
+            // `let x = x + 1;` the `x` on the right is the outer one
+            StmtKind::Let { name, .. } => {
+                visitor::walk_stmt(self, ast, stmt);
+                self.bind(name);
+            }
+            StmtKind::For {
+                var,
+                iterable,
+                body,
+                ..
+            } => {
+                self.visit_expr(ast, *iterable);
+                self.push_scope();
+                self.bind(var);
+                self.visit_block(ast, body);
+                self.pop_scope();
+            }
+            _ => visitor::walk_stmt(self, ast, stmt),
+        }
+    }
+
+    fn visit_block(&mut self, ast: &Ast, block: &Block) {
+        self.push_scope();
+        visitor::walk_block(self, ast, block);
+        self.pop_scope();
+    }
+
+    fn visit_func_decl(&mut self, ast: &Ast, decl: &FuncDecl) {
+        // bound before the body, which may call itself
+        self.bind(&decl.name);
+        self.push_scope();
+        self.bind_params(&decl.params);
+        visitor::walk_func_decl(self, ast, decl);
+        self.pop_scope();
+    }
+
+    fn visit_match_arm(&mut self, ast: &Ast, arm: &MatchArm) {
+        self.push_scope();
+        for name in arm.pattern.bindings() {
+            self.bind(name);
+        }
+        visitor::walk_match_arm(self, ast, arm);
+        self.pop_scope();
+    }
+
+    fn visit_expr(&mut self, ast: &Ast, expr: ExprId) {
+        match ast.expr(expr) {
+            ExprKind::Ident(name) => self.use_name(name, ast.expr_span(expr)),
+            ExprKind::Lambda(lambda) => {
+                self.captures.entry(expr).or_default();
+                self.lambdas.push(expr);
+                self.push_scope();
+                self.bind_params(&lambda.params);
+                visitor::walk_expr(self, ast, expr);
+                self.pop_scope();
+                self.lambdas.pop();
+            }
+            _ => visitor::walk_expr(self, ast, expr),
+        }
+    }
+}
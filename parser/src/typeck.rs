@@ -0,0 +1,286 @@
+//! Type checking, so far only what `if` and block expressions need: the
+//! branches of an `if` must agree on the type of their value, and an `if`
+//! without `else` is `()`, so its branch must not yield a value.
+//!
+//! The types of literals, operators on them, casts, blocks and `if`s are
+//! inferred and recorded with `Ast::set_expr_type`. Everything else, such
+//! as names and calls, has no type yet and agrees with any other, so only
+//! values whose types are both known can clash.
+
+use std::fmt;
+
+use lexer::errors::{Severity, write_snippet};
+use lexer::messages::{Message, format, tr};
+use lexer::token::SourceSpan;
+
+use crate::nodes::ast::Ast;
+use crate::nodes::decls::FuncDecl;
+use crate::nodes::exprs::{BinaryOp, ExprId, ExprKind, LambdaBody, LiteralValue, UnaryOp};
+use crate::nodes::stmts::{Block, Program, StmtId, StmtKind};
+use crate::nodes::type_exprs::{Primitive, TypeExprKind};
+use crate::nodes::types::Type;
+use crate::nodes::visitor::{self, Visitor};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeError {
+    // the `else` branch of an `if` yields a value of another type than the
+    // first branch, `span` is the `else` branch
+    BranchMismatch {
+        expected: Type,
+        found: Type,
+        span: SourceSpan,
+    },
+    // the branch of an `if` without `else` yields a value, `span` is the
+    // branch
+    MissingElse {
+        found: Type,
+        span: SourceSpan,
+    },
+}
+
+impl TypeError {
+    pub fn span(&self) -> SourceSpan {
+        match self {
+            TypeError::BranchMismatch { span, .. } | TypeError::MissingElse { span, .. } => *span,
+        }
+    }
+
+    fn title(&self) -> String {
+        match self {
+            TypeError::BranchMismatch { .. } => tr(Message::BranchMismatch).to_string(),
+            TypeError::MissingElse { .. } => tr(Message::MissingElse).to_string(),
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            TypeError::BranchMismatch {
+                expected, found, ..
+            } => format(Message::BranchMismatchLabel, &[expected, found]),
+            TypeError::MissingElse { found, .. } => format(Message::MissingElseLabel, &[found]),
+        }
+    }
+
+    /// Pairs the error with the source it came from so it can be displayed
+    /// with a snippet of the offending line.
+    pub fn report<'r>(&'r self, source: &'r str, filename: &'r str) -> Report<'r> {
+        Report {
+            error: self,
+            source,
+            filename,
+        }
+    }
+}
+
+impl fmt::Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let span = self.span();
+        write!(
+            f,
+            "{} ({}:{})",
+            self.title(),
+            span.start.line,
+            span.start.column
+        )
+    }
+}
+
+/// A `TypeError` rendered with the source snippet and caret underline.
+pub struct Report<'r> {
+    error: &'r TypeError,
+    source: &'r str,
+    filename: &'r str,
+}
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let title = format!("{}: {}", tr(Message::ErrorPrefix), self.error.title());
+        write_snippet(
+            f,
+            Severity::Error,
+            &title,
+            &self.error.label(),
+            self.filename,
+            self.source,
+            self.error.span(),
+        )
+    }
+}
+
+/// Checks `program`, recording the type of every expression it can infer
+/// in its arena, and returns the errors it finds.
+///
+/// # Example
+/// ```
+/// use parser::parser::engine::Parser;
+/// let src = "let x = if a { 1 } else { \"one\" };";
+/// let tokens = lexer::tokenize(src, "example").unwrap();
+/// let mut parser = Parser::new(&tokens, 0);
+/// let mut program = Parser::parse_program(&mut parser);
+/// let errors = parser::typeck::check(&mut program);
+/// assert!(matches!(errors[0], parser::typeck::TypeError::BranchMismatch { .. }));
+/// ```
+pub fn check(program: &mut Program) -> Vec<TypeError> {
+    let mut checker = Checker {
+        types: vec![None; program.ast.expr_count()],
+        errors: Vec::new(),
+    };
+    checker.visit_program(program);
+    for (index, ty) in checker.types.into_iter().enumerate() {
+        if let Some(ty) = ty {
+            program.ast.set_expr_type(ExprId(index as u32), ty);
+        }
+    }
+    checker.errors
+}
+
+struct Checker {
+    // indexed by `ExprId`, `None` where the type is not known
+    types: Vec<Option<Type>>,
+    errors: Vec<TypeError>,
+}
+
+impl Checker {
+    fn type_of(&self, expr: ExprId) -> Option<Type> {
+        self.types[expr.index()]
+    }
+
+    /// The type of `expr`, whose children are checked already.
+    fn infer(&mut self, ast: &Ast, expr: ExprId) -> Option<Type> {
+        match ast.expr(expr) {
+            ExprKind::Literal(value) => Some(Type::Primitive(match value {
+                LiteralValue::Int(_) => Primitive::Int,
+                LiteralValue::Float(_) => Primitive::Float,
+                LiteralValue::Bool(_) => Primitive::Bool,
+                LiteralValue::Str(_) => Primitive::String,
+            })),
+            ExprKind::Grouping(inner) => self.type_of(*inner),
+            ExprKind::Unary {
+                op: UnaryOp::Not, ..
+            } => Some(Type::Primitive(Primitive::Bool)),
+            ExprKind::Unary {
+                op: UnaryOp::Neg,
+                expr,
+                ..
+            } => self.type_of(*expr),
+            ExprKind::Binary { left, op, right } => match op {
+                BinaryOp::Equal
+                | BinaryOp::NotEqual
+                | BinaryOp::Less
+                | BinaryOp::LessEqual
+                | BinaryOp::Greater
+                | BinaryOp::GreaterEqual
+                | BinaryOp::And
+                | BinaryOp::Or => Some(Type::Primitive(Primitive::Bool)),
+                BinaryOp::Range | BinaryOp::RangeInclusive => None,
+                // arithmetic keeps the type of its operands when they agree
+                _ => match (self.type_of(*left), self.type_of(*right)) {
+                    (Some(left), Some(right)) if left == right => Some(left),
+                    _ => None,
+                },
+            },
+            ExprKind::Cast { ty, .. } => match &ty.kind {
+                TypeExprKind::Primitive(p) => Some(Type::Primitive(*p)),
+                TypeExprKind::Tuple(elements) if elements.is_empty() => Some(Type::Unit),
+                _ => None,
+            },
+            ExprKind::Tuple(elements) if elements.is_empty() => Some(Type::Unit),
+            ExprKind::Block(block) => self.block_type(ast, block),
+            ExprKind::Lambda(lambda) => {
+                // what the body returns is not the type of the lambda, but
+                // a tail `if` in it is checked all the same
+                if let LambdaBody::Block(body) = &lambda.body {
+                    self.block_type(ast, body);
+                }
+                None
+            }
+            ExprKind::If {
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                let then_type = self.block_type(ast, then_branch);
+                let else_branch = else_branch.map(|e| (self.type_of(e), ast.expr_span(e)));
+                self.if_type(then_type, then_branch.span, else_branch)
+            }
+            _ => None,
+        }
+    }
+
+    /// The type of the value of `block`. A block without a tail is `()`,
+    /// unless it ends in a `return`, `break` or `continue`, which leave it
+    /// without a value of any type.
+    fn block_type(&mut self, ast: &Ast, block: &Block) -> Option<Type> {
+        let last = block.stmts.last().map(|stmt| ast.stmt(*stmt));
+        if !block.tail {
+            return match last {
+                Some(StmtKind::Return(_) | StmtKind::Break | StmtKind::Continue) => None,
+                _ => Some(Type::Unit),
+            };
+        }
+        self.stmt_type(ast, *block.stmts.last()?)
+    }
+
+    /// The type of the tail of a block, which may be an `if` or a block
+    /// statement, or of the `else` branch of such an `if`. They are only
+    /// checked here, as their value is unused anywhere else.
+    fn stmt_type(&mut self, ast: &Ast, stmt: StmtId) -> Option<Type> {
+        match ast.stmt(stmt) {
+            StmtKind::Expr(expr) => self.type_of(*expr),
+            StmtKind::Block(block) => self.block_type(ast, block),
+            StmtKind::If {
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                let then_type = self.block_type(ast, then_branch);
+                let else_branch = else_branch.map(|s| (self.stmt_type(ast, s), ast.stmt_span(s)));
+                self.if_type(then_type, then_branch.span, else_branch)
+            }
+            _ => None,
+        }
+    }
+
+    /// The type of an `if` whose first branch has `then_type`, reporting
+    /// branches that disagree. `else_branch` is the type and span of the
+    /// `else` branch.
+    fn if_type(
+        &mut self,
+        then_type: Option<Type>,
+        then_span: SourceSpan,
+        else_branch: Option<(Option<Type>, SourceSpan)>,
+    ) -> Option<Type> {
+        let Some((else_type, else_span)) = else_branch else {
+            if let Some(found) = then_type.filter(|ty| *ty != Type::Unit) {
+                self.errors.push(TypeError::MissingElse {
+                    found,
+                    span: then_span,
+                });
+            }
+            return Some(Type::Unit);
+        };
+        match (then_type, else_type) {
+            (Some(expected), Some(found)) if expected != found => {
+                self.errors.push(TypeError::BranchMismatch {
+                    expected,
+                    found,
+                    span: else_span,
+                });
+                None
+            }
+            (then_type, else_type) => then_type.or(else_type),
+        }
+    }
+}
+
+impl Visitor for Checker {
+    fn visit_func_decl(&mut self, ast: &Ast, decl: &FuncDecl) {
+        visitor::walk_func_decl(self, ast, decl);
+        self.block_type(ast, &decl.body);
+    }
+
+    fn visit_expr(&mut self, ast: &Ast, expr: ExprId) {
+        visitor::walk_expr(self, ast, expr);
+        self.types[expr.index()] = self.infer(ast, expr);
+    }
+}
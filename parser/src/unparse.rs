@@ -0,0 +1,687 @@
+//! Turns an AST back into source text.
+//!
+//! Unlike `Display`, which parenthesizes every operation, the unparser
+//! writes only the parentheses the grammar needs to read the tree back the
+//! same way, and lays statements out one per line with four space indents.
+//! Parsing the output gives the same tree, up to spans and the `Grouping`
+//! nodes of the parentheses it added.
+//!
+//! Some trees have no source form: literals are written as they are, so a
+//! negative or non-finite number, or a string holding `"` or a newline,
+//! does not read back as the same literal, a lambda with both a return
+//! type and an expression body reads back with a block body, an empty block
+//! expression reads back as an empty map, and `Error` nodes are written as
+//! `<error>`.
+
+use lexer::token_type::TokenKind;
+
+use crate::nodes::ast::Ast;
+use crate::nodes::decls::{EnumDecl, FuncDecl, Param, StructDecl};
+use crate::nodes::exprs::{ArmBody, BinaryOp, ExprId, ExprKind, LambdaBody, LiteralValue, UnaryOp};
+use crate::nodes::patterns::{Pattern, PatternKind};
+use crate::nodes::stmts::{Block, Program, StmtId, StmtKind};
+use crate::parser::lookups::{Associativity, BindingPower, infix_binding};
+
+/// Source text of a whole program, one statement per line.
+///
+/// # Example
+/// ```
+/// use parser::parser::engine::Parser;
+/// let tokens = lexer::tokenize("let x = ((1 + 2)) * (3 * 4);", "example").unwrap();
+/// let mut parser = Parser::new(&tokens, 0);
+/// let program = Parser::parse_program(&mut parser);
+/// assert_eq!(parser::unparse::unparse(&program), "let x = ((1 + 2)) * (3 * 4);\n");
+/// ```
+pub fn unparse(program: &Program) -> String {
+    let mut unparser = Unparser::new(&program.ast);
+    for stmt in &program.stmts {
+        unparser.stmt(*stmt);
+        unparser.out.push('\n');
+    }
+    unparser.out
+}
+
+/// Source text of a single expression.
+pub fn unparse_expr(ast: &Ast, expr: ExprId) -> String {
+    let mut unparser = Unparser::new(ast);
+    unparser.expr(expr, BindingPower::Primary);
+    unparser.out
+}
+
+struct Unparser<'t> {
+    ast: &'t Ast,
+    out: String,
+    indent: usize,
+    // whether a struct literal must be parenthesized, as the parser reads
+    // `Name {` in a condition as a name followed by the body
+    no_struct: bool,
+    // the operand that starts a statement, when it must be parenthesized
+    first: Option<ExprId>,
+}
+
+impl<'t> Unparser<'t> {
+    fn new(ast: &'t Ast) -> Unparser<'t> {
+        Unparser {
+            ast,
+            out: String::new(),
+            indent: 0,
+            no_struct: false,
+            first: None,
+        }
+    }
+
+    fn newline(&mut self) {
+        self.out.push('\n');
+        for _ in 0..self.indent {
+            self.out.push_str("    ");
+        }
+    }
+
+    fn stmt(&mut self, id: StmtId) {
+        let ast = self.ast;
+        match ast.stmt(id) {
+            // a statement `match` ends at its `}`
+            StmtKind::Expr(expr) if matches!(ast.expr(*expr), ExprKind::Match { .. }) => {
+                self.expr(*expr, BindingPower::Primary);
+            }
+            StmtKind::Expr(expr) => {
+                self.stmt_expr(*expr);
+                self.out.push(';');
+            }
+            StmtKind::Let {
+                mutable,
+                name,
+                ty,
+                value,
+                ..
+            } => {
+                self.out.push_str(if *mutable { "var " } else { "let " });
+                self.out.push_str(name);
+                if let Some(ty) = ty {
+                    self.out.push_str(&format!(": {}", ty));
+                }
+                if let Some(value) = value {
+                    self.out.push_str(" = ");
+                    self.expr(*value, BindingPower::Primary);
+                }
+                self.out.push(';');
+            }
+            StmtKind::Assign { target, value } => {
+                self.stmt_expr(*target);
+                self.out.push_str(" = ");
+                self.expr(*value, BindingPower::Primary);
+                self.out.push(';');
+            }
+            StmtKind::Block(block) => self.block(block),
+            StmtKind::If {
+                cond,
+                then_branch,
+                else_branch,
+            } => {
+                self.out.push_str("if ");
+                self.cond(*cond);
+                self.out.push(' ');
+                self.block(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.out.push_str(" else ");
+                    self.stmt(*else_branch);
+                }
+            }
+            StmtKind::While { cond, body } => {
+                self.out.push_str("while ");
+                self.cond(*cond);
+                self.out.push(' ');
+                self.block(body);
+            }
+            StmtKind::For {
+                var,
+                iterable,
+                body,
+                ..
+            } => {
+                self.out.push_str(&format!("for {} in ", var));
+                self.cond(*iterable);
+                self.out.push(' ');
+                self.block(body);
+            }
+            StmtKind::Return(value) => {
+                self.out.push_str("return");
+                if let Some(value) = value {
+                    self.out.push(' ');
+                    self.expr(*value, BindingPower::Primary);
+                }
+                self.out.push(';');
+            }
+            StmtKind::Break => self.out.push_str("break;"),
+            StmtKind::Continue => self.out.push_str("continue;"),
+            StmtKind::Func(decl) => self.func_decl(decl),
+            StmtKind::Struct(decl) => self.struct_decl(decl),
+            StmtKind::Enum(decl) => self.enum_decl(decl),
+            StmtKind::Import(decl) => self.out.push_str(&decl.to_string()),
+            StmtKind::Error => self.out.push_str("// <error>"),
+        }
+    }
+
+    /// Writes an expression at the start of a statement, where a `{` opens
+    /// a block and an `if` or `match` is a statement of its own, so a map,
+    /// block, `if` or `match` operand that comes first is parenthesized.
+    fn stmt_expr(&mut self, expr: ExprId) {
+        let first = self.leftmost(expr);
+        if matches!(
+            self.ast.expr(first),
+            ExprKind::Map(_) | ExprKind::Block(_) | ExprKind::If { .. } | ExprKind::Match { .. }
+        ) {
+            self.first = Some(first);
+        }
+        self.expr(expr, BindingPower::Primary);
+        self.first = None;
+    }
+
+    /// The operand whose tokens come first when `expr` is written.
+    fn leftmost(&self, mut expr: ExprId) -> ExprId {
+        loop {
+            expr = match self.ast.expr(expr) {
+                ExprKind::Binary { left: inner, .. }
+                | ExprKind::Cast { expr: inner, .. }
+                | ExprKind::Call { callee: inner, .. }
+                | ExprKind::Index { object: inner, .. }
+                | ExprKind::Member { object: inner, .. } => *inner,
+                _ => return expr,
+            };
+        }
+    }
+
+    /// Writes a block. Its last statement reads as the tail when it has no
+    /// `;`, so the tail expression leaves it out and a last `if`, `match`
+    /// or block that is not the tail gets one.
+    fn block(&mut self, block: &Block) {
+        if block.stmts.is_empty() {
+            self.out.push_str("{}");
+            return;
+        }
+        let saved = std::mem::replace(&mut self.no_struct, false);
+        self.out.push('{');
+        self.indent += 1;
+        for (i, stmt) in block.stmts.iter().enumerate() {
+            self.newline();
+            if i + 1 < block.stmts.len() {
+                self.stmt(*stmt);
+                continue;
+            }
+            match self.ast.stmt(*stmt) {
+                StmtKind::Expr(expr) if block.tail => {
+                    if matches!(self.ast.expr(*expr), ExprKind::Match { .. }) {
+                        self.stmt(*stmt);
+                    } else {
+                        self.stmt_expr(*expr);
+                    }
+                }
+                StmtKind::Expr(expr) if matches!(self.ast.expr(*expr), ExprKind::Match { .. }) => {
+                    self.stmt(*stmt);
+                    self.out.push(';');
+                }
+                StmtKind::If { .. } | StmtKind::Block(_) if !block.tail => {
+                    self.stmt(*stmt);
+                    self.out.push(';');
+                }
+                _ => self.stmt(*stmt),
+            }
+        }
+        self.indent -= 1;
+        self.newline();
+        self.out.push('}');
+        self.no_struct = saved;
+    }
+
+    fn func_decl(&mut self, decl: &FuncDecl) {
+        if decl.public {
+            self.out.push_str("pub ");
+        }
+        self.out.push_str(&format!("func {}(", decl.name));
+        self.params(&decl.params);
+        self.out.push(')');
+        if let Some(ty) = &decl.return_type {
+            self.out.push_str(&format!(" -> {}", ty));
+        }
+        self.out.push(' ');
+        self.block(&decl.body);
+    }
+
+    fn params(&mut self, params: &[Param]) {
+        for (i, param) in params.iter().enumerate() {
+            if i > 0 {
+                self.out.push_str(", ");
+            }
+            self.out.push_str(&param.to_string());
+        }
+    }
+
+    fn struct_decl(&mut self, decl: &StructDecl) {
+        if decl.public {
+            self.out.push_str("pub ");
+        }
+        self.out.push_str(&format!("struct {} {{", decl.name));
+        let fields = decl.fields.iter().map(ToString::to_string);
+        self.lines(fields.collect());
+    }
+
+    fn enum_decl(&mut self, decl: &EnumDecl) {
+        if decl.public {
+            self.out.push_str("pub ");
+        }
+        self.out.push_str(&format!("enum {} {{", decl.name));
+        let variants = decl.variants.iter().map(ToString::to_string);
+        self.lines(variants.collect());
+    }
+
+    /// Writes one item per line, each followed by a comma, and the `}`.
+    fn lines(&mut self, items: Vec<String>) {
+        if !items.is_empty() {
+            self.indent += 1;
+            for item in items {
+                self.newline();
+                self.out.push_str(&item);
+                self.out.push(',');
+            }
+            self.indent -= 1;
+            self.newline();
+        }
+        self.out.push('}');
+    }
+
+    /// Writes the condition of an `if` or `while`, the iterable of a `for`
+    /// or the scrutinee of a `match`.
+    fn cond(&mut self, expr: ExprId) {
+        let saved = std::mem::replace(&mut self.no_struct, true);
+        self.expr(expr, BindingPower::Primary);
+        self.no_struct = saved;
+    }
+
+    /// Writes an expression inside delimiters, where struct literals need
+    /// no parentheses even within a condition.
+    fn nested(&mut self, expr: ExprId) {
+        let saved = std::mem::replace(&mut self.no_struct, false);
+        self.expr(expr, BindingPower::Primary);
+        self.no_struct = saved;
+    }
+
+    fn parens(&mut self, expr: ExprId) {
+        self.out.push('(');
+        self.nested(expr);
+        self.out.push(')');
+    }
+
+    /// Opens parentheses around what follows, until the `Close` it returns.
+    fn open_parens(&mut self) -> Close {
+        self.out.push('(');
+        Close::Parens(std::mem::replace(&mut self.no_struct, false))
+    }
+
+    fn list(&mut self, items: &[ExprId]) {
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                self.out.push_str(", ");
+            }
+            self.nested(*item);
+        }
+    }
+
+    /// The level an expression is read at: the level of its operator, or
+    /// `Postfix` for anything that is a single operand. A lambda with an
+    /// expression body is at `Primary`, as its body takes in any operator.
+    fn level(&self, expr: ExprId) -> BindingPower {
+        match self.ast.expr(expr) {
+            ExprKind::Binary { op, .. } => binding(op.token_kind()).0,
+            ExprKind::Cast { .. } => BindingPower::Cast,
+            ExprKind::Unary { .. } => BindingPower::Unary,
+            ExprKind::Lambda(lambda) if lambda.has_expr_body() => BindingPower::Primary,
+            _ => BindingPower::Postfix,
+        }
+    }
+
+    /// Writes an operand that the parser reads with `parse_expr(p, bp)`, so
+    /// one that starts the expression or follows a prefix or infix operator.
+    /// Only operations at or below `bp` need parentheses, prefix operators
+    /// and single operands never do.
+    fn expr(&mut self, expr: ExprId, bp: BindingPower) {
+        if self.needs_parens(expr, bp) {
+            self.parens(expr);
+        } else {
+            self.write_expr(expr);
+        }
+    }
+
+    /// Whether `expr` writes an operand at `bp` with parentheses.
+    fn needs_parens(&mut self, expr: ExprId, bp: BindingPower) -> bool {
+        match self.ast.expr(expr) {
+            ExprKind::Binary { .. } | ExprKind::Cast { .. } => self.level(expr) <= bp,
+            ExprKind::Struct { .. } => self.no_struct,
+            ExprKind::Lambda(lambda) if lambda.has_expr_body() => {
+                bp > BindingPower::Primary || self.take_first(expr)
+            }
+            _ => self.take_first(expr),
+        }
+    }
+
+    /// Whether the left operand of an operator at level `bp` needs
+    /// parentheses. It is parsed before the operator is seen, so it does
+    /// when it binds looser than the operator, or as loose for a
+    /// right-associative one.
+    fn left_needs_parens(&mut self, expr: ExprId, bp: BindingPower, assoc: Associativity) -> bool {
+        let level = self.level(expr);
+        level < bp || (level == bp && assoc == Associativity::Right) || self.take_first(expr)
+    }
+
+    /// Whether `expr` is the operand starting the statement that must be
+    /// parenthesized, which only happens once.
+    fn take_first(&mut self, expr: ExprId) -> bool {
+        let first = self.first == Some(expr);
+        if first {
+            self.first = None;
+        }
+        first
+    }
+
+    fn write_expr(&mut self, expr: ExprId) {
+        let ast = self.ast;
+        match ast.expr(expr) {
+            ExprKind::Literal(value) => self.literal(value),
+            ExprKind::Ident(name) => self.out.push_str(name),
+            ExprKind::Binary { .. }
+            | ExprKind::Call { .. }
+            | ExprKind::Index { .. }
+            | ExprKind::Member { .. }
+            | ExprKind::Cast { .. } => self.write_chain(expr),
+            ExprKind::Unary { op, expr, .. } => {
+                self.out.push_str(op.symbol());
+                // `& &x` and `* *x` would lex as `&&` and `**`
+                if matches!(ast.expr(*expr), ExprKind::Unary { op: inner, .. }
+                    if *inner == *op && matches!(op, UnaryOp::Ref | UnaryOp::Deref))
+                {
+                    self.out.push(' ');
+                }
+                self.expr(*expr, BindingPower::Unary);
+            }
+            ExprKind::Grouping(inner) => self.parens(*inner),
+            ExprKind::Array(elements) => {
+                self.out.push('[');
+                self.list(elements);
+                self.out.push(']');
+            }
+            ExprKind::Map(entries) => {
+                self.out.push('{');
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push_str(", ");
+                        self.nested(*key);
+                    } else {
+                        self.first_key(*key);
+                    }
+                    self.out.push_str(": ");
+                    self.nested(*value);
+                }
+                self.out.push('}');
+            }
+            ExprKind::Tuple(elements) => {
+                self.out.push('(');
+                self.list(elements);
+                if elements.len() == 1 {
+                    self.out.push(',');
+                }
+                self.out.push(')');
+            }
+            ExprKind::Struct { name, fields, .. } => {
+                self.out.push_str(name);
+                if fields.is_empty() {
+                    self.out.push_str(" {}");
+                    return;
+                }
+                self.out.push_str(" { ");
+                for (i, field) in fields.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push_str(", ");
+                    }
+                    self.out.push_str(&field.name);
+                    if !field.shorthand {
+                        self.out.push_str(": ");
+                        self.nested(field.value);
+                    }
+                }
+                self.out.push_str(" }");
+            }
+            ExprKind::Match { scrutinee, arms } => {
+                self.out.push_str("match ");
+                self.cond(*scrutinee);
+                self.out.push_str(" {");
+                self.indent += 1;
+                for arm in arms {
+                    self.newline();
+                    self.pattern(&arm.pattern);
+                    if let Some(guard) = arm.guard {
+                        self.out.push_str(" if ");
+                        self.nested(guard);
+                    }
+                    self.out.push_str(" => ");
+                    match &arm.body {
+                        ArmBody::Block(block) => self.block(block),
+                        ArmBody::Expr(body) => {
+                            let saved = std::mem::replace(&mut self.no_struct, false);
+                            self.expr_body(*body);
+                            self.no_struct = saved;
+                            self.out.push(',');
+                        }
+                    }
+                }
+                self.indent -= 1;
+                if !arms.is_empty() {
+                    self.newline();
+                }
+                self.out.push('}');
+            }
+            ExprKind::If {
+                cond,
+                then_branch,
+                else_branch,
+            } => {
+                self.out.push_str("if ");
+                self.cond(*cond);
+                self.out.push(' ');
+                self.block(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.out.push_str(" else ");
+                    self.write_expr(*else_branch);
+                }
+            }
+            ExprKind::Block(block) => self.block(block),
+            ExprKind::Lambda(lambda) => {
+                let (open, close) = match lambda.body {
+                    LambdaBody::Block(_) => ("func(", ')'),
+                    LambdaBody::Expr(_) => ("|", '|'),
+                };
+                self.out.push_str(open);
+                self.params(&lambda.params);
+                self.out.push(close);
+                if let Some(ty) = &lambda.return_type {
+                    self.out.push_str(&format!(" -> {}", ty));
+                }
+                self.out.push(' ');
+                match &lambda.body {
+                    LambdaBody::Block(block) => self.block(block),
+                    LambdaBody::Expr(body) => self.expr_body(*body),
+                }
+            }
+            ExprKind::Error => self.out.push_str("<error>"),
+        }
+    }
+
+    /// Writes a chain of operations in a loop, see `Ast::chain`. Going down
+    /// the chain, each link decides as `left` would whether its operand
+    /// needs parentheses, and the rest of each link is written on the way
+    /// back up.
+    fn write_chain(&mut self, expr: ExprId) {
+        let ast = self.ast;
+        let mut closes = Vec::new();
+        let mut link = expr;
+        while let Some(operand) = ast.expr(link).chained_operand() {
+            closes.push(Close::Link(link));
+            // `1.x` would lex as the number `1.` followed by `x`
+            let number = matches!(
+                ast.expr(operand),
+                ExprKind::Literal(LiteralValue::Int(_) | LiteralValue::Float(_))
+            );
+            let needs_parens = match ast.expr(link) {
+                ExprKind::Member { .. } if number => true,
+                ExprKind::Binary { op, .. } => {
+                    let (bp, assoc) = binding(op.token_kind());
+                    self.left_needs_parens(operand, bp, assoc)
+                }
+                ExprKind::Cast { .. } => {
+                    self.left_needs_parens(operand, BindingPower::Cast, Associativity::Left)
+                }
+                _ => self.left_needs_parens(operand, BindingPower::Postfix, Associativity::Left),
+            };
+            if needs_parens {
+                closes.push(self.open_parens());
+            }
+            if self.needs_parens(operand, BindingPower::Primary) {
+                closes.push(self.open_parens());
+            }
+            link = operand;
+        }
+        self.write_expr(link);
+        while let Some(close) = closes.pop() {
+            match close {
+                Close::Parens(no_struct) => {
+                    self.no_struct = no_struct;
+                    self.out.push(')');
+                }
+                Close::Link(link) => self.write_link(link),
+            }
+        }
+    }
+
+    /// Writes what follows the operand of a link of a chain.
+    fn write_link(&mut self, link: ExprId) {
+        match self.ast.expr(link) {
+            ExprKind::Binary { op, right, .. } => {
+                let (bp, assoc) = binding(op.token_kind());
+                // ranges are written tight, as in `0..n`
+                if matches!(op, BinaryOp::Range | BinaryOp::RangeInclusive) {
+                    self.out.push_str(op.symbol());
+                } else {
+                    self.out.push_str(&format!(" {} ", op.symbol()));
+                }
+                self.expr(*right, bp.right_operand(assoc));
+            }
+            ExprKind::Call { args, .. } => {
+                self.out.push('(');
+                self.list(args);
+                self.out.push(')');
+            }
+            ExprKind::Index { index, .. } => {
+                self.out.push('[');
+                self.nested(*index);
+                self.out.push(']');
+            }
+            ExprKind::Member { field, .. } => {
+                self.out.push('.');
+                self.out.push_str(field);
+            }
+            ExprKind::Cast { ty, .. } => self.out.push_str(&format!(" as {}", ty)),
+            _ => unreachable!("only the last of a chain is not a link"),
+        }
+    }
+
+    /// Writes the first key of a map. A map starting with `if`, `match` or
+    /// `{` reads as a block, so such an operand that comes first is
+    /// parenthesized.
+    fn first_key(&mut self, key: ExprId) {
+        let first = self.leftmost(key);
+        if matches!(
+            self.ast.expr(first),
+            ExprKind::If { .. } | ExprKind::Match { .. } | ExprKind::Block(_) | ExprKind::Map(_)
+        ) {
+            self.first = Some(first);
+        }
+        self.nested(key);
+        self.first = None;
+    }
+
+    /// Writes the expression body of an arm or a lambda, where a `{`
+    /// opens a block, so a map or block operand that comes first is
+    /// parenthesized.
+    fn expr_body(&mut self, body: ExprId) {
+        let first = self.leftmost(body);
+        if matches!(self.ast.expr(first), ExprKind::Map(_) | ExprKind::Block(_)) {
+            self.first = Some(first);
+        }
+        self.expr(body, BindingPower::Primary);
+        self.first = None;
+    }
+
+    fn literal(&mut self, value: &LiteralValue) {
+        match value {
+            LiteralValue::Int(v) => self.out.push_str(&v.to_string()),
+            LiteralValue::Float(v) => self.out.push_str(&float(*v)),
+            LiteralValue::Bool(v) => self.out.push_str(&v.to_string()),
+            LiteralValue::Str(v) => self.out.push_str(&format!("\"{}\"", v)),
+        }
+    }
+
+    fn pattern(&mut self, pattern: &Pattern) {
+        match &pattern.kind {
+            PatternKind::Wildcard => self.out.push('_'),
+            PatternKind::Literal(value) => self.literal(value),
+            PatternKind::Binding(name) => self.out.push_str(name),
+            PatternKind::Tuple(items) => {
+                self.out.push('(');
+                self.patterns(items);
+                if items.len() == 1 {
+                    self.out.push(',');
+                }
+                self.out.push(')');
+            }
+            PatternKind::Variant { path, fields } => {
+                self.out.push_str(&path.join("."));
+                if let Some(fields) = fields {
+                    self.out.push('(');
+                    self.patterns(fields);
+                    self.out.push(')');
+                }
+            }
+        }
+    }
+
+    fn patterns(&mut self, items: &[Pattern]) {
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                self.out.push_str(", ");
+            }
+            self.pattern(item);
+        }
+    }
+}
+
+/// What is left to write of a chain once the operand of a link is done.
+enum Close {
+    // a `)`, and whether struct literals needed parentheses outside it
+    Parens(bool),
+    // the rest of a link
+    Link(ExprId),
+}
+
+/// Level and associativity of a binary operator token.
+fn binding(kind: TokenKind) -> (BindingPower, Associativity) {
+    infix_binding(kind).unwrap_or((BindingPower::Primary, Associativity::Left))
+}
+
+/// Writes a float so it lexes back as one: always with a `.` and never in
+/// exponent notation, which `{:?}` uses for very large or small values.
+fn float(value: f64) -> String {
+    let text = value.to_string();
+    if text.contains('.') || !value.is_finite() {
+        text
+    } else {
+        format!("{}.0", text)
+    }
+}
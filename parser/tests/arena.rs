@@ -0,0 +1,12 @@
+use std::mem::size_of;
+
+use parser::nodes::exprs::ExprKind;
+
+/// Every slot of the expression arena takes the room of the largest kind,
+/// so a variant that grows `ExprKind` slows down every parse. Box what
+/// makes it grow instead, as `If`, `Block` and `Lambda` do.
+#[test]
+fn expression_nodes_stay_small() {
+    let size = size_of::<ExprKind>();
+    assert!(size <= 80, "`ExprKind` grew to {} bytes", size);
+}
@@ -0,0 +1,83 @@
+use lexer::token_type::TokenKind;
+use parser::cst::Cst;
+
+/// Parses `src` into a syntax tree and checks it gives back `src` exactly.
+fn round_trip(src: &str) {
+    let (tokens, trivia) = lexer::tokenize_lossless(src, "test");
+    let cst = Cst::parse(&tokens, &trivia);
+    assert_eq!(cst.root.text(), src);
+    assert_eq!(cst.root.text_range(), 0..src.len());
+}
+
+#[test]
+fn valid_programs_round_trip() {
+    round_trip("");
+    round_trip("let x = 1;");
+    round_trip("  let   x=1 ;\n\n// done\n");
+    round_trip("func add(a: int, b: int) -> int {\n    return a + b; // sum\n}\n");
+    round_trip("struct P { x: int }\nlet p = P { x: 1 };\nif p.x > 0 { f(p); } else { g(); }\n");
+    round_trip("enum O { S(int), N }\nmatch o { O.S(v) if v > 1 => v, _ => { 0 } }\n");
+    round_trip("for x in [1, 2, 3] { while x < 10 { x = x * 2; } }");
+}
+
+#[test]
+fn broken_programs_round_trip() {
+    round_trip("let x = ;");
+    round_trip("let = = = ;;; }}} {{{");
+    round_trip("f(1, 2\nlet y = 3;");
+    round_trip("\tlet x\r\n= 1;");
+    round_trip("let s = \"unterminated\nlet t = 2;");
+    round_trip("let π = ٣ + \"\"\"x");
+    round_trip("match { => , }");
+    round_trip("1 +");
+}
+
+#[test]
+fn trivia_is_kept_between_nodes() {
+    let src = "// first\nlet x = 1; // one\n";
+    let (tokens, trivia) = lexer::tokenize_lossless(src, "test");
+    let cst = Cst::parse(&tokens, &trivia);
+    let stmt = cst.root.children().next().unwrap();
+    assert_eq!(stmt.text(), "let x = 1;");
+    let comments: Vec<String> = cst
+        .root
+        .tokens()
+        .iter()
+        .filter(|t| t.kind() == TokenKind::COMMENT)
+        .map(|t| t.text().to_string())
+        .collect();
+    assert_eq!(comments, ["// first", "// one"]);
+}
+
+#[test]
+fn nodes_lead_to_the_ast() {
+    let src = "let x = (1 + 2) * 3;";
+    let (tokens, trivia) = lexer::tokenize_lossless(src, "test");
+    let cst = Cst::parse(&tokens, &trivia);
+    assert!(cst.errors.is_empty());
+
+    let stmt = cst.program.stmts[0];
+    let node = cst.stmt_node(stmt).unwrap();
+    assert!(cst.stmt(&node).is_some());
+    assert_eq!(node.text_range(), 0..src.len());
+
+    let exprs: Vec<String> = node
+        .descendants()
+        .filter(|n| cst.expr(n).is_some())
+        .map(|n| n.text())
+        .collect();
+    assert_eq!(exprs, ["(1 + 2) * 3", "(1 + 2)", "1 + 2", "1", "2", "3"]);
+    let inner = node.descendants().nth(3).unwrap();
+    assert_eq!(inner.parent().unwrap().text(), "(1 + 2)");
+}
+
+#[test]
+fn errors_are_reported_but_kept_in_the_tree() {
+    let src = "let x = 1 $ 2;\nlet y = 2;";
+    let (tokens, trivia) = lexer::tokenize_lossless(src, "test");
+    let cst = Cst::parse(&tokens, &trivia);
+    assert!(!cst.errors.is_empty());
+    assert_eq!(cst.root.text(), src);
+    let invalid = cst.root.tokens().into_iter().find(|t| t.kind() == TokenKind::ERROR);
+    assert_eq!(invalid.unwrap().text(), "$");
+}
@@ -0,0 +1,169 @@
+use parser::dump::{Format, dump};
+use parser::nodes::ast::Ast;
+use parser::nodes::exprs::ExprId;
+use parser::nodes::fold::Fold;
+use parser::nodes::stmts::Program;
+use parser::nodes::visitor::{self, Visitor};
+use parser::nodes::visitor_mut::VisitorMut;
+use parser::parser::engine::{DEFAULT_MAX_DEPTH, Parser};
+use parser::parser::errors::ParserError;
+use parser::resolve;
+use parser::unparse::unparse;
+
+fn parse_with_limit(src: &str, max_depth: usize) -> (Program, Vec<ParserError<'static>>) {
+    // leaked so the errors can outlive this function, fine in a test
+    let src: &'static str = Box::leak(src.to_string().into_boxed_str());
+    let tokens = Box::leak(lexer::tokenize(src, "test").unwrap().into_boxed_slice());
+    let mut parser = Parser::new(tokens, 0);
+    parser.max_depth = max_depth;
+    let program = Parser::parse_program(&mut parser);
+    (program, parser.errors)
+}
+
+fn errors(src: &str) -> Vec<ParserError<'static>> {
+    parse_with_limit(src, DEFAULT_MAX_DEPTH).1
+}
+
+fn nest(open: &str, inner: &str, close: &str, levels: usize) -> String {
+    format!("{}{}{}", open.repeat(levels), inner, close.repeat(levels))
+}
+
+fn is_too_deep(errors: &[ParserError]) -> bool {
+    matches!(
+        errors,
+        [ParserError::NestingTooDeep {
+            limit: DEFAULT_MAX_DEPTH,
+            ..
+        }]
+    )
+}
+
+#[test]
+fn deep_nesting_is_one_error_instead_of_a_stack_overflow() {
+    let cases = [
+        nest("(", "x", ")", 10_000) + ";",
+        nest("-", "x", "", 10_000) + ";",
+        nest("[", "1", "]", 10_000) + ";",
+        nest("f(", "1", ")", 10_000) + ";",
+        nest("2 ** ", "2", "", 10_000) + ";",
+        nest("{", "", "}", 10_000),
+        nest("if x {", "", "}", 10_000),
+        "let x: ".to_string() + &nest("[", "int", "]", 10_000) + ";",
+        "match x { ".to_string() + &nest("(", "y", ",)", 10_000) + " => 1 }",
+        nest("match x { _ => ", "1", " }", 10_000) + ";",
+    ];
+    for src in &cases {
+        let errors = errors(src);
+        assert!(is_too_deep(&errors), "{}...: {:?}", &src[..20], errors);
+    }
+}
+
+#[test]
+fn nesting_within_the_limit_parses() {
+    let levels = DEFAULT_MAX_DEPTH - 10;
+    let cases = [
+        nest("(", "x", ")", levels) + ";",
+        nest("-", "x", "", levels) + ";",
+        nest("{", "", "}", levels),
+        "let x: ".to_string() + &nest("[", "int", "]", levels) + ";",
+    ];
+    for src in &cases {
+        assert_eq!(errors(src), [], "{}...", &src[..20]);
+    }
+}
+
+#[test]
+fn the_limit_is_configurable() {
+    assert!(parse_with_limit("((x));", 4).1.is_empty());
+    let (program, errors) = parse_with_limit("(((x))); y;", 4);
+    assert!(matches!(
+        errors[..],
+        [ParserError::NestingTooDeep { limit: 4, .. }]
+    ));
+    // the rest of the input is skipped
+    assert_eq!(program.stmts.len(), 1);
+}
+
+#[test]
+fn long_operator_chains_are_not_nesting() {
+    // a chain is read in a loop, however long it is
+    let chain = |link: &str, links: usize| format!("x{};", link.repeat(links));
+    for link in [" + 1", ".f", "(1)", "[0]", " as int", ".f(1)[0] * y"] {
+        assert_eq!(errors(&chain(link, 5_000)), [], "{}", link);
+    }
+    // but what nests inside a link still counts
+    assert!(is_too_deep(&errors(&chain(&nest("(", "1", ")", 200), 2))));
+}
+
+/// Counts the expressions it visits.
+#[derive(Default)]
+struct Count(usize);
+
+impl Visitor for Count {
+    fn visit_expr(&mut self, ast: &Ast, expr: ExprId) {
+        self.0 += 1;
+        visitor::walk_expr(self, ast, expr);
+    }
+}
+
+struct Walk;
+
+impl Visitor for Walk {}
+
+impl VisitorMut for Walk {}
+
+impl Fold for Walk {}
+
+#[test]
+fn long_chains_are_walked_in_a_loop() {
+    let levels = DEFAULT_MAX_DEPTH - 10;
+    let cases = [
+        vec!["1"; 100_000].join(" + ") + ";",
+        "x".to_string() + &".f(y)[0]".repeat(30_000) + " as int;",
+        // the operand at the far left of a chain ends up below all of it
+        nest("(", "x", ")", levels) + &" + 1".repeat(100_000) + ";",
+    ];
+    for src in &cases {
+        let (mut program, errors) = parse_with_limit(src, DEFAULT_MAX_DEPTH);
+        assert_eq!(errors, [], "{}...", &src[..20]);
+        let text = program.to_string();
+        let (reparsed, errors) = parse_with_limit(&unparse(&program), DEFAULT_MAX_DEPTH);
+        assert_eq!(errors, []);
+        assert_eq!(reparsed.to_string(), text);
+        for format in [Format::Sexpr, Format::Dot] {
+            assert!(!dump(&program, format).is_empty());
+        }
+        // every link is visited once
+        let mut count = Count::default();
+        count.visit_program(&program);
+        assert_eq!(count.0, program.ast.expr_count());
+        resolve::captures(&program);
+        Walk.visit_program_mut(&mut program);
+        assert_eq!(Walk.fold_program(program).to_string(), text);
+
+        let (tokens, trivia) = lexer::tokenize_lossless(src, "test");
+        let cst = parser::cst::Cst::parse(&tokens, &trivia);
+        assert_eq!(cst.root.text(), *src);
+        let text: String = cst.root.tokens().iter().map(|t| t.text()).collect();
+        assert_eq!(text, *src);
+    }
+    // the indented dumps grow with the square of the depth, so they get a
+    // shorter chain
+    let (program, errors) = parse_with_limit(
+        &("x".to_string() + &" + 1".repeat(3_000) + ";"),
+        DEFAULT_MAX_DEPTH,
+    );
+    assert_eq!(errors, []);
+    for format in Format::ALL {
+        assert!(!dump(&program, *format).is_empty());
+    }
+}
+
+#[test]
+fn skipped_input_stays_in_the_syntax_tree() {
+    let src = nest("(", "x", ")", 1_000) + "; y;";
+    let (tokens, trivia) = lexer::tokenize_lossless(&src, "test");
+    let cst = parser::cst::Cst::parse(&tokens, &trivia);
+    assert_eq!(cst.root.text(), src);
+    assert!(is_too_deep(&cst.errors));
+}
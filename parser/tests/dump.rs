@@ -0,0 +1,86 @@
+use parser::dump::{Format, dump};
+use parser::nodes::stmts::Program;
+use parser::parser::engine::Parser;
+
+fn parse(src: &str) -> Program {
+    let tokens = lexer::tokenize(src, "test").unwrap();
+    let mut parser = Parser::new(&tokens, 0);
+    Parser::parse_program(&mut parser)
+}
+
+#[test]
+fn tree_shows_fields_and_spans() {
+    let tree = dump(&parse("let x: int = f(1, y);"), Format::Tree);
+    assert_eq!(
+        tree,
+        "Program 1:1-1:22
+└─ stmts[0]: Let 1:1-1:22 mutable=false name=\"x\"
+   ├─ ty: PrimitiveType 1:8-1:11 name=\"int\"
+   └─ value: Call 1:14-1:21
+      ├─ callee: Ident 1:14-1:15 name=\"f\"
+      ├─ args[0]: Int 1:16-1:17 value=1
+      └─ args[1]: Ident 1:19-1:20 name=\"y\"
+"
+    );
+}
+
+#[test]
+fn sexprs_are_one_line_per_statement() {
+    let src = "var v;
+let p = P { x: 1.5, y };
+match s { Shape.Circle(r) if r > 0 => r, _ => { return; } }
+func f(a: [int]) -> int? {}";
+    assert_eq!(
+        dump(&parse(src), Format::Sexpr),
+        r#"(Let :mutable true :name "v")
+(Let :mutable false :name "p" :value (Struct :name "P" (FieldInit :name "x" :shorthand false (Float :value 1.5)) (FieldInit :name "y" :shorthand true (Ident :name "y"))))
+(Expr (Match (Ident :name "s") (Arm (VariantPat :path "Shape.Circle" (BindingPat :name "r")) :guard (Binary :op ">" (Ident :name "r") (Int :value 0)) (Ident :name "r")) (Arm (WildcardPat) (Block (Return)))))
+(Func :name "f" (Param :name "a" :type_annotation (ArrayType (PrimitiveType :name "int"))) :return_type (OptionalType (PrimitiveType :name "int")) (Block))
+"#
+    );
+}
+
+#[test]
+fn json_follows_the_schema() {
+    let json = dump(&parse("return \"a\\b\";"), Format::Json);
+    assert_eq!(
+        json,
+        r#"{
+  "kind": "Program",
+  "span": {"start": {"line": 1, "column": 1}, "end": {"line": 1, "column": 14}},
+  "stmts": [
+    {
+      "kind": "Return",
+      "span": {"start": {"line": 1, "column": 1}, "end": {"line": 1, "column": 14}},
+      "value": {
+        "kind": "Str",
+        "span": {"start": {"line": 1, "column": 8}, "end": {"line": 1, "column": 13}},
+        "value": "a\\b"
+      }
+    }
+  ]
+}
+"#
+    );
+    assert!(dump(&parse("let x;"), Format::Json).contains("\"value\": null"));
+}
+
+#[test]
+fn dot_has_one_edge_into_every_node_but_the_root() {
+    let dot = dump(&parse("if a { b = -c[0]; } else if d {}"), Format::Dot);
+    assert!(dot.starts_with("digraph ast {\n") && dot.ends_with("}\n"));
+    let nodes = dot.lines().filter(|l| l.contains(" [label=") && !l.contains("->")).count();
+    let edges = dot.lines().filter(|l| l.contains("->")).count();
+    assert_eq!(nodes, 13);
+    assert_eq!(edges, nodes - 1);
+    assert!(dot.contains("n0 -> n1 [label=\"stmts[0]\"];"));
+}
+
+#[test]
+fn trees_with_errors_are_dumped() {
+    let sexpr = dump(&parse("let x = ; y;"), Format::Sexpr);
+    assert_eq!(
+        sexpr,
+        "(Let :mutable false :name \"x\" :value (Error))\n(Expr (Ident :name \"y\"))\n"
+    );
+}
@@ -0,0 +1,268 @@
+mod common;
+
+use common::Rng;
+use lexer::token::{Position, SourceSpan, Token};
+use lexer::token_type::TokenKind;
+use parser::cst::Cst;
+use parser::dump::{self, Format};
+use parser::nodes::fold::Fold;
+use parser::nodes::stmts::Program;
+use parser::nodes::visitor::Visitor;
+use parser::parser::engine::Parser;
+use parser::resolve;
+use parser::unparse::unparse;
+
+// pieces of source text, valid tokens and not, glued together at random
+#[rustfmt::skip]
+const PIECES: &[&str] = &[
+    "let", "var", "func", "struct", "enum", "match", "import", "pub", "if", "else", "while", "for", "in",
+    "return", "break", "continue", "as", "true", "false", "int", "x", "Point", "_", "0", "42",
+    "1.5", ".5", "1.", "1.2.3", "99999999999999999999999999999999999999999", "\"s\"", "\"",
+    "\"\"\"", "// c", "(", ")", "{", "}", "[", "]", ",", ";", ":", ".", "?", "=", "==", "!",
+    "!=", "<", "<=", "<<", ">", ">=", ">>", "+", "-", "*", "**", "/", "%", "&", "&&", "|",
+    "||", "^", "->", "=>", "..", "..=", " ", "\n", "\t", "\r", "é", "٣", "½", "💥", "#", "$", "\\", "\0",
+];
+
+const KINDS: &[TokenKind] = &[
+    TokenKind::FLOAT,
+    TokenKind::INTEGER,
+    TokenKind::PLUS,
+    TokenKind::STAR,
+    TokenKind::EQUAL,
+    TokenKind::MODULO,
+    TokenKind::SLASH,
+    TokenKind::DASH,
+    TokenKind::AMPER,
+    TokenKind::GREATER,
+    TokenKind::LESS,
+    TokenKind::NEGATION,
+    TokenKind::VERTICAL_BAR,
+    TokenKind::CARET,
+    TokenKind::QUESTION,
+    TokenKind::EQUAL_EQUAL,
+    TokenKind::NOT_EQUAL,
+    TokenKind::LESS_EQUAL,
+    TokenKind::GREATER_EQUAL,
+    TokenKind::DOUBLE_AMPER,
+    TokenKind::DOUBLE_VERTICAL_BAR,
+    TokenKind::DOUBLE_LESS,
+    TokenKind::DOUBLE_GREATER,
+    TokenKind::DOUBLE_STAR,
+    TokenKind::DOUBLE_DOT,
+    TokenKind::DOUBLE_DOT_EQUAL,
+    TokenKind::ARROW,
+    TokenKind::FAT_ARROW,
+    TokenKind::SEMICOLON,
+    TokenKind::IF,
+    TokenKind::ELSE,
+    TokenKind::FOR,
+    TokenKind::WHILE,
+    TokenKind::FUNCTION,
+    TokenKind::RETURN,
+    TokenKind::LET,
+    TokenKind::VAR,
+    TokenKind::IN,
+    TokenKind::BREAK,
+    TokenKind::CONTINUE,
+    TokenKind::AS,
+    TokenKind::STRUCT,
+    TokenKind::ENUM,
+    TokenKind::MATCH,
+    TokenKind::IMPORT,
+    TokenKind::PUB,
+    TokenKind::TRUE,
+    TokenKind::FALSE,
+    TokenKind::LPAREN,
+    TokenKind::RPAREN,
+    TokenKind::LBRACE,
+    TokenKind::RBRACE,
+    TokenKind::LBRACKET,
+    TokenKind::RBRACKET,
+    TokenKind::COMMA,
+    TokenKind::COLON,
+    TokenKind::DOT,
+    TokenKind::IDENTIFIER,
+    TokenKind::LITERALSTRING,
+    TokenKind::COMMENT,
+    TokenKind::WHITESPACE,
+    TokenKind::ERROR,
+    TokenKind::EOF,
+];
+
+// snippets repeated many times over by the long cases, each run of one
+// nesting one level deeper or making a chain one operator longer
+const RUNS: &[&str] = &[
+    "(", "[", "{", "-", "!", "f(", "if x {", "match x { _ => ", "|a| ", "let x: [", " + 1",
+    " ** 2", ".f", "(1)", "[0]", " as int", "x;", "}", ")",
+];
+
+fn random_source(rng: &mut Rng) -> String {
+    let len = rng.below(40);
+    let mut src = String::new();
+    for _ in 0..len {
+        let piece: &&str = rng.pick(PIECES);
+        src.push_str(piece);
+        if rng.below(3) == 0 {
+            src.push(' ');
+        }
+    }
+    src
+}
+
+/// Source of well over ten thousand tokens, long runs of one snippet
+/// between random pieces, so it reaches the nesting limit and makes long
+/// chains and lists. Its pieces are spaced out and lex on their own, so
+/// the whole of it lexes and gets to the parser.
+fn long_source(rng: &mut Rng) -> String {
+    let pieces: Vec<&str> = PIECES
+        .iter()
+        .copied()
+        .filter(|p| !p.contains("//") && p.matches('"').count() % 2 == 0)
+        .filter(|p| lexer::tokenize(p, "fuzz").is_ok())
+        .collect();
+    let mut src = String::new();
+    let mut count = 0;
+    while count < 20_000 {
+        let (piece, run) = if rng.below(3) == 0 {
+            (*rng.pick(RUNS), 1 + rng.below(5_000))
+        } else {
+            (*rng.pick(&pieces), 1)
+        };
+        for _ in 0..run {
+            src.push_str(piece);
+            src.push(' ');
+        }
+        count += run;
+    }
+    src
+}
+
+/// Uses the program every way a host might, none of which may panic.
+fn consume(program: &Program, parser: &Parser, source: &str) {
+    for error in &parser.errors {
+        let _ = error.report(source, "fuzz").to_string();
+        let _ = error.to_string();
+    }
+    let _ = program.to_string();
+    let _ = unparse(program);
+    for format in Format::ALL {
+        let _ = dump::dump(program, *format);
+    }
+    Walk.visit_program(program);
+    let _ = resolve::captures(program);
+    Walk.fold_program(program.clone());
+}
+
+/// Visits and folds with the default walks.
+struct Walk;
+
+impl Visitor for Walk {}
+
+impl Fold for Walk {}
+
+#[test]
+fn random_source_never_panics() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    for _ in 0..5000 {
+        let src = random_source(&mut rng);
+        match lexer::tokenize(&src, "fuzz") {
+            Ok(tokens) => {
+                let mut parser = Parser::new(&tokens, 0);
+                parser.trace = Some(Vec::new());
+                let program = Parser::parse_program(&mut parser);
+                consume(&program, &parser, &src);
+            }
+            Err(error) => {
+                let _ = error.to_string();
+            }
+        }
+        let (tokens, trivia) = lexer::tokenize_lossless(&src, "fuzz");
+        let cst = Cst::parse(&tokens, &trivia);
+        assert_eq!(cst.root.text(), src);
+    }
+}
+
+#[test]
+fn long_and_deep_sources_never_panic() {
+    let mut rng = Rng(0xd1b5_4a32_d192_ed03);
+    for _ in 0..20 {
+        let src = long_source(&mut rng);
+        let tokens = lexer::tokenize(&src, "fuzz").unwrap();
+        assert!(tokens.len() > 10_000);
+        let mut parser = Parser::new(&tokens, 0);
+        let program = Parser::parse_program(&mut parser);
+        consume(&program, &parser, &src);
+        let (tokens, trivia) = lexer::tokenize_lossless(&src, "fuzz");
+        assert_eq!(Cst::parse(&tokens, &trivia).root.text(), src);
+        // and the same line, too long to pad by format width, failing to lex
+        for end in ["#", "\"", "'"] {
+            let bad = format!("{src}{end}");
+            let _ = lexer::tokenize(&bad, "fuzz").unwrap_err().to_string();
+        }
+    }
+}
+
+/// `len` tokens of random kinds, in runs of one kind when `runs` is set,
+/// sometimes followed by the end of input.
+fn random_tokens(rng: &mut Rng, len: usize, runs: bool) -> Vec<Token<'static>> {
+    let at = |column| Position::new(1, column);
+    let mut tokens = Vec::with_capacity(len + 1);
+    while tokens.len() < len {
+        let kind = *rng.pick(KINDS);
+        let run = if runs { 1 + rng.below(2_000) } else { 1 };
+        for _ in 0..run.min(len - tokens.len()) {
+            let i = tokens.len();
+            // values need not match their kind, as from a buggy lexer
+            let value = *rng.pick(PIECES);
+            tokens.push(Token::new(
+                kind,
+                value,
+                SourceSpan::new(at(i + 1), at(i + 2)),
+            ));
+        }
+    }
+    if rng.below(2) == 0 {
+        tokens.push(Token::new(
+            TokenKind::EOF,
+            "",
+            SourceSpan::new(at(len + 1), at(len + 1)),
+        ));
+    }
+    tokens
+}
+
+#[test]
+fn random_token_sequences_never_panic() {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    for _ in 0..5000 {
+        let len = rng.below(30);
+        let tokens = random_tokens(&mut rng, len, false);
+        let mut parser = Parser::new(&tokens, 0);
+        let program = Parser::parse_program(&mut parser);
+        consume(&program, &parser, "");
+    }
+    for _ in 0..20 {
+        let tokens = random_tokens(&mut rng, 20_000, true);
+        let mut parser = Parser::new(&tokens, 0);
+        let program = Parser::parse_program(&mut parser);
+        consume(&program, &parser, "");
+    }
+}
+
+#[test]
+fn cases_that_used_to_hang_or_panic() {
+    // `٣` is numeric but not a digit the number rules accept
+    assert!(lexer::tokenize("let x = ٣;", "fuzz").is_err());
+    assert!(lexer::tokenize("½", "fuzz").is_err());
+
+    // a string token without its quotes, as a broken lexer could produce
+    let at = Position::new(1, 1);
+    let tokens = [Token::new(
+        TokenKind::LITERALSTRING,
+        ";",
+        SourceSpan::new(at, at),
+    )];
+    let mut parser = Parser::new(&tokens, 0);
+    Parser::parse_program(&mut parser);
+    assert_eq!(parser.errors.len(), 1);
+}
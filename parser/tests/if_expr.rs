@@ -0,0 +1,167 @@
+use parser::dump::{Format, dump};
+use parser::nodes::exprs::ExprKind;
+use parser::nodes::stmts::{Program, StmtKind};
+use parser::nodes::type_exprs::Primitive;
+use parser::nodes::types::Type;
+use parser::parser::engine::Parser;
+use parser::typeck::{self, TypeError};
+use parser::unparse::unparse;
+
+fn parse(src: &str) -> Program {
+    let tokens = lexer::tokenize(src, "test").unwrap();
+    let mut parser = Parser::new(&tokens, 0);
+    let program = Parser::parse_program(&mut parser);
+    assert!(parser.errors.is_empty(), "`{}` did not parse: {:?}", src, parser.errors);
+    program
+}
+
+fn display(src: &str) -> String {
+    parse(src).to_string()
+}
+
+/// The value of the first statement, which must be a `let`.
+fn let_value(src: &str) -> ExprKind {
+    let program = parse(src);
+    let StmtKind::Let { value: Some(value), .. } = program.ast.stmt(program.stmts[0]) else {
+        panic!("`{}` does not start with a `let` with a value", src);
+    };
+    program.ast.expr(*value).clone()
+}
+
+#[test]
+fn if_expressions_take_the_value_of_a_branch() {
+    assert_eq!(
+        display("let x = if a > b { a } else { b };"),
+        "let x = if (a > b) { a } else { b };"
+    );
+    assert_eq!(
+        display("let s = if n < 0 { -1 } else if n == 0 { 0 } else { 1 };"),
+        "let s = if (n < 0) { (-1) } else if (n == 0) { 0 } else { 1 };"
+    );
+    assert!(matches!(let_value("let x = if a { 1 };"), ExprKind::If { else_branch: None, .. }));
+}
+
+#[test]
+fn blocks_yield_their_tail() {
+    let ExprKind::Block(block) = let_value("let y = { let t = 2; t * t };") else {
+        panic!("not a block");
+    };
+    assert!(block.tail);
+    assert_eq!(block.stmts.len(), 2);
+
+    // a `;` after the last expression leaves the block without a value
+    let ExprKind::Block(block) = let_value("let y = { f(); };") else {
+        panic!("not a block");
+    };
+    assert!(!block.tail);
+}
+
+#[test]
+fn braces_are_a_map_only_with_a_key() {
+    assert!(matches!(let_value("let m = {};"), ExprKind::Map(entries) if entries.is_empty()));
+    assert!(matches!(let_value("let m = {a: 1, b: 2};"), ExprKind::Map(_)));
+    assert!(matches!(let_value("let m = {f(x): {y}};"), ExprKind::Map(_)));
+    assert!(matches!(let_value("let m = {|a: int| a: 1};"), ExprKind::Map(_)));
+    assert!(matches!(let_value("let b = {x};"), ExprKind::Block(_)));
+    // an `if` statement ends at its `}`, so what follows is a lambda
+    assert!(matches!(let_value("let b = {if c {} |a: int| a};"), ExprKind::Block(_)));
+    assert!(matches!(let_value("let b = {return;};"), ExprKind::Block(_)));
+    assert!(matches!(
+        let_value("let b = {if c {} let t: int = 1; t};"),
+        ExprKind::Block(_)
+    ));
+}
+
+#[test]
+fn function_and_lambda_bodies_can_end_in_a_tail() {
+    assert_eq!(
+        display("func max(a: int, b: int) -> int { if a > b { a } else { b } }"),
+        "func max(a: int, b: int) -> int { if (a > b) { a } else { b } }"
+    );
+    let sexpr = dump(&parse("let f = |x| { let y = x; y + 1 };"), Format::Sexpr);
+    assert!(sexpr.contains("(Block :tail true"), "{}", sexpr);
+}
+
+#[test]
+fn unparsing_keeps_tails_and_blocks_apart() {
+    let cases = [
+        (
+            "let x = if a { 1 } else { 2 };",
+            "let x = if a {\n    1\n} else {\n    2\n};\n",
+        ),
+        ("let y = { f(); g() };", "let y = {\n    f();\n    g()\n};\n"),
+        ("let m = {(if a { 1 } else { 2 }): 3};", "let m = {(if a {\n    1\n} else {\n    2\n}): 3};\n"),
+        ("func f() { { g(); } }", "func f() {\n    {\n        g();\n    }\n}\n"),
+        // an `if` that starts a statement would be an `if` statement
+        ("(if a { b } else { c }).d;", "(if a {\n    b\n} else {\n    c\n}).d;\n"),
+        // and a trailing `if` statement followed by a `;` is no tail
+        ("let z = { if a { b; }; };", "let z = {\n    if a {\n        b;\n    };\n};\n"),
+    ];
+    for (src, expected) in cases {
+        assert_eq!(unparse(&parse(src)), expected, "{}", src);
+    }
+}
+
+/// The errors of checking `src`, which must parse.
+fn type_errors(src: &str) -> Vec<TypeError> {
+    typeck::check(&mut parse(src))
+}
+
+/// The type recorded for the value of the first statement, a `let`.
+fn let_type(program: &Program) -> Option<Type> {
+    let StmtKind::Let { value: Some(value), .. } = program.ast.stmt(program.stmts[0]) else {
+        panic!("not a `let` with a value");
+    };
+    program.ast.expr_type(*value).copied()
+}
+
+#[test]
+fn branches_of_an_if_agree_on_their_type() {
+    let int = Type::Primitive(Primitive::Int);
+    let mut program = parse("let x = if a { 1 } else if b { 2 } else { -3 };");
+    assert!(typeck::check(&mut program).is_empty());
+    assert_eq!(let_type(&program), Some(int));
+
+    // a value of no known type yet, or a branch that leaves, agrees with any
+    assert!(type_errors("let x = if a { y } else { 1 };").is_empty());
+    assert!(type_errors("func f(a) { let x = if a { return; } else { 1 }; }").is_empty());
+
+    let errors = type_errors("let x = if a { 1 } else { \"one\" };");
+    let string = Type::Primitive(Primitive::String);
+    assert!(matches!(
+        errors[..],
+        [TypeError::BranchMismatch { expected, found, .. }] if expected == int && found == string
+    ));
+    let cases = [
+        "let x = if a { 1 } else if b { 2 } else { true };",
+        "let x = if a { f(); } else { 1.5 };",
+        // a tail `if` statement, in a block, a function or a lambda body
+        "let y = { if a { 1 } else { 2.0 } };",
+        "func max(a: int, b: int) -> int { if a > b { 1 } else { b > a } }",
+        "let f = |a| { if a { 1 } else { \"\" } };",
+    ];
+    for src in cases {
+        let errors = type_errors(src);
+        assert!(matches!(errors[..], [TypeError::BranchMismatch { .. }]), "{}: {:?}", src, errors);
+    }
+}
+
+#[test]
+fn an_if_without_else_is_unit() {
+    let mut program = parse("let x = if a { f(); };");
+    assert!(typeck::check(&mut program).is_empty());
+    assert_eq!(let_type(&program), Some(Type::Unit));
+    assert!(type_errors("let x = if a { () };").is_empty());
+    // an `if` statement whose value is unused yields nothing
+    assert!(type_errors("func f(a) { if a { 1 } g(); }").is_empty());
+
+    let errors = type_errors("let x = if a { 1 };");
+    let [TypeError::MissingElse { found, span }] = errors[..] else {
+        panic!("{:?}", errors);
+    };
+    assert_eq!(found, Type::Primitive(Primitive::Int));
+    assert_eq!((span.start.column, span.end.column), (14, 19));
+    assert_eq!(errors[0].to_string(), "`if` may be missing an `else` branch (1:14)");
+    let errors = type_errors("let y = { if a { \"s\" } };");
+    assert!(matches!(errors[..], [TypeError::MissingElse { .. }]), "{:?}", errors);
+}
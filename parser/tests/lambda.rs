@@ -0,0 +1,108 @@
+use parser::dump::{Format, dump};
+use parser::nodes::stmts::Program;
+use parser::parser::engine::Parser;
+use parser::resolve::captures;
+use parser::unparse::unparse;
+
+fn parse(src: &str) -> Program {
+    let tokens = lexer::tokenize(src, "test").unwrap();
+    let mut parser = Parser::new(&tokens, 0);
+    let program = Parser::parse_program(&mut parser);
+    assert!(parser.errors.is_empty(), "`{}` did not parse: {:?}", src, parser.errors);
+    program
+}
+
+fn display(src: &str) -> String {
+    parse(src).to_string()
+}
+
+/// Captured names of every lambda, in source order of the lambdas.
+fn captured(src: &str) -> Vec<Vec<String>> {
+    let program = parse(src);
+    let mut lambdas: Vec<_> = captures(&program).into_iter().collect();
+    lambdas.sort_by_key(|(id, _)| program.ast.expr_span(*id).start);
+    lambdas
+        .into_iter()
+        .map(|(_, captures)| captures.into_iter().map(|c| c.name).collect())
+        .collect()
+}
+
+#[test]
+fn both_forms_parse() {
+    assert_eq!(display("let f = |x| x + 1;"), "let f = |x| (x + 1);");
+    assert_eq!(display("let f = || g();"), "let f = || g();");
+    assert_eq!(display("let f = |a: int, b| a;"), "let f = |a: int, b| a;");
+    assert_eq!(
+        display("let f = func(x: int) -> int { return x; };"),
+        "let f = func(x: int) -> int { return x; };"
+    );
+    // a block body and a return type both give the `func` form
+    assert_eq!(display("let f = |x| { return x; };"), "let f = func(x) { return x; };");
+    assert_eq!(display("let f = |x| -> int { return x; };"), "let f = func(x) -> int { return x; };");
+}
+
+#[test]
+fn an_expression_body_takes_in_what_follows() {
+    assert_eq!(display("f(|a| a * 2, 1);"), "f(|a| (a * 2), 1);");
+    assert_eq!(display("x = a || |b| b || c;"), "x = (a || |b| (b || c));");
+    assert_eq!(display("sort(xs, |a, b| a.key < b.key);"), "sort(xs, |a, b| (a.key < b.key));");
+}
+
+#[test]
+fn func_followed_by_a_parenthesis_starts_a_lambda_statement() {
+    let program = parse("func(x) { print(x); }(1); func f() {}");
+    let sexpr = dump(&program, Format::Sexpr);
+    assert!(sexpr.starts_with("(Expr (Call (Lambda"), "{}", sexpr);
+    assert!(sexpr.lines().nth(1).unwrap().starts_with("(Func :name \"f\""));
+}
+
+#[test]
+fn unparsing_parenthesizes_lambdas_that_would_take_in_too_much() {
+    let cases = [
+        ("(|x| x)(1);", "(|x| x)(1);\n"),
+        ("let y = (|x| x) + 1;", "let y = (|x| x) + 1;\n"),
+        ("let y = |x| x + 1;", "let y = |x| x + 1;\n"),
+        ("let y = |x| ({1: x})[1];", "let y = |x| ({1: x})[1];\n"),
+        ("let y = -(|| 1);", "let y = -(|| 1);\n"),
+        ("func(x) { return x; }(2);", "func(x) {\n    return x;\n}(2);\n"),
+    ];
+    for (src, expected) in cases {
+        assert_eq!(unparse(&parse(src)), expected, "{}", src);
+    }
+}
+
+#[test]
+fn lambdas_capture_the_locals_they_use() {
+    let src = "func counter(start: int) {
+        var n = start;
+        let step = 2;
+        return || n + step + start + global + missing;
+    }
+    let global = 1;";
+    assert_eq!(captured(src), [["n", "step", "start"]]);
+}
+
+#[test]
+fn names_a_lambda_binds_itself_are_not_captured() {
+    let src = "func f(xs) {
+        let k = 1;
+        map(xs, |x| {
+            let y = x * k;
+            match y { (a, b) => a + b + k, _ => 0 };
+            for i in xs { print(i); }
+            return y;
+        });
+    }";
+    assert_eq!(captured(src), [["k", "xs"]]);
+}
+
+#[test]
+fn nested_lambdas_pass_captures_through_the_outer_one() {
+    let src = "func f(a) {
+        let outer = |b| |c| a + b + c;
+        let shadowed = |a| a;
+        let x = 1;
+        let later = |y| { let x = x + y; return x; };
+    }";
+    assert_eq!(captured(src), [vec!["a"], vec!["a", "b"], vec![], vec!["x"]]);
+}
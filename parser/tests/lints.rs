@@ -0,0 +1,250 @@
+use parser::lints::{self, Level, Lint, LintConfig, Linter, UnknownLint};
+use parser::parser::engine::Parser;
+
+/// Lints `src` as `language check` does, returning each warning as its lint,
+/// level and line.
+fn lint(src: &str, config: &LintConfig) -> Vec<(Lint, Level, usize)> {
+    let (tokens, comments) = lexer::tokenize_with_comments(src, "test").unwrap();
+    let mut linter = Linter::new(config, &tokens, &comments);
+    lints::check_tokens(&mut linter, &tokens);
+    let mut parser = Parser::new(&tokens, 0);
+    Parser::parse_program(&mut parser);
+    assert!(parser.errors.is_empty(), "`{}` did not parse", src);
+    for (lint, span) in &parser.lints {
+        linter.emit(*lint, *span);
+    }
+    linter
+        .finish()
+        .iter()
+        .map(|w| (w.lint, w.level, w.span.start.line))
+        .collect()
+}
+
+fn unknown_lints(src: &str) -> Vec<String> {
+    let (tokens, comments) = lexer::tokenize_with_comments(src, "test").unwrap();
+    let config = LintConfig::new();
+    let linter = Linter::new(&config, &tokens, &comments);
+    linter.unknown_lints().into_iter().map(|u| u.name).collect()
+}
+
+/// The configuration the given `-W`/`-D`/`-A` flags build, in order.
+fn config(flags: &[(Level, &str)]) -> LintConfig {
+    let mut config = LintConfig::new();
+    for (level, name) in flags {
+        assert!(config.set(name, *level), "no lint `{}`", name);
+    }
+    config
+}
+
+#[test]
+fn each_lint_fires_at_its_default_level() {
+    use Level::Warn;
+    use Lint::{RedundantParens, RedundantSemicolons, Unused};
+    let none = LintConfig::new();
+    assert_eq!(lint("let x = 1;;", &none), [(RedundantSemicolons, Warn, 1)]);
+    assert_eq!(lint("let x = (y);", &none), [(RedundantParens, Warn, 1)]);
+    assert_eq!(lint("1 + 2;", &none), [(Unused, Warn, 1)]);
+    assert_eq!(
+        lint("let x = (1);\n\nx == 2;;\n", &none),
+        [
+            (RedundantSemicolons, Warn, 3),
+            (RedundantParens, Warn, 1),
+            (Unused, Warn, 3)
+        ]
+    );
+    // parentheses that group an operation, values that are used and calls,
+    // which may have side effects, are all fine
+    assert_eq!(
+        lint("let x = (1 + 2) * 3;\nf(x);\nx = 4;\nlet y = { 5 };", &none),
+        []
+    );
+}
+
+#[test]
+fn levels_and_lints_go_by_their_names() {
+    assert_eq!(Level::from_name("allow"), Some(Level::Allow));
+    assert_eq!(Level::from_name("warn"), Some(Level::Warn));
+    assert_eq!(Level::from_name("deny"), Some(Level::Deny));
+    assert_eq!(Level::from_name("forbid"), None);
+    assert!(Level::Allow < Level::Warn && Level::Warn < Level::Deny);
+    for lint in Lint::ALL {
+        assert_eq!(Lint::from_name(lint.name()), Some(*lint));
+        assert_eq!(LintConfig::new().level(*lint), lint.default_level());
+    }
+    assert_eq!(Lint::from_name(lints::ALL_LINTS), None);
+    assert!(!LintConfig::new().set("nonexistent", Level::Allow));
+}
+
+#[test]
+fn later_flags_override_earlier_ones() {
+    let src = "let x = (y);\n1;";
+    assert_eq!(
+        lint(
+            src,
+            &config(&[(Level::Allow, "warnings"), (Level::Deny, "unused")])
+        ),
+        [(Lint::Unused, Level::Deny, 2)]
+    );
+    assert_eq!(
+        lint(
+            src,
+            &config(&[(Level::Deny, "unused"), (Level::Allow, "warnings")])
+        ),
+        []
+    );
+    assert_eq!(
+        lint(
+            src,
+            &config(&[(Level::Deny, "warnings"), (Level::Warn, "redundant_parens")])
+        ),
+        [
+            (Lint::RedundantParens, Level::Warn, 1),
+            (Lint::Unused, Level::Deny, 2)
+        ]
+    );
+}
+
+#[test]
+fn deny_warnings_escalates_only_what_warns() {
+    let mut config = config(&[(Level::Allow, "redundant_parens")]);
+    config.deny_warnings();
+    assert_eq!(config.level(Lint::Unused), Level::Deny);
+    assert_eq!(config.level(Lint::RedundantParens), Level::Allow);
+    // a directive that sets a lint back to `warn` is escalated too
+    let src = "let x = (y); // warn(redundant_parens)\n1;\n2; // allow(unused)";
+    let warnings = lint(src, &config);
+    assert_eq!(
+        warnings,
+        [
+            (Lint::RedundantParens, Level::Deny, 1),
+            (Lint::Unused, Level::Deny, 2)
+        ]
+    );
+}
+
+#[test]
+fn a_trailing_directive_covers_only_its_own_line() {
+    use Lint::Unused;
+    let none = LintConfig::new();
+    assert_eq!(
+        lint("let a = 1; // allow(unused)\n4 + 5;", &none),
+        [(Unused, Level::Warn, 2)]
+    );
+    assert_eq!(
+        lint("4 + 5; // allow(unused)\n6;", &none),
+        [(Unused, Level::Warn, 2)]
+    );
+    assert_eq!(
+        lint("4 + 5; // deny(unused)\n6;", &none),
+        [(Unused, Level::Deny, 1), (Unused, Level::Warn, 2)]
+    );
+}
+
+#[test]
+fn parentheses_the_grammar_needs_are_not_redundant() {
+    let none = LintConfig::new();
+    // a struct literal would end a condition at its `{`
+    let src = "struct P { x: int }\nfunc f(p: P) {\n    if (P { x: 1 }).x == p.x { g(); }\n    \
+               while (P { x: 2 }).x > p.x { g(); }\n    match (P { x: 3 }) { _ => g(), }\n}";
+    assert_eq!(lint(src, &none), []);
+    // an `if`, `match`, block or map would start a statement of its own
+    let src = "(if a { b } else { c }).d();\n(match x { _ => y }).f();\n\
+               ({ g() }).h();\n({\"k\": 1}).len();";
+    assert_eq!(lint(src, &none), []);
+    // anywhere else the same parentheses are redundant
+    let src = "struct P { x: int }\nlet v = (P { x: 1 }).x;\nlet w = (if a { b } else { c }).d;\n\
+               if f((P { x: 1 })) { g(); }\n((match x { _ => y })).f();";
+    assert_eq!(
+        lint(src, &none),
+        [
+            (Lint::RedundantParens, Level::Warn, 2),
+            (Lint::RedundantParens, Level::Warn, 3),
+            (Lint::RedundantParens, Level::Warn, 4),
+            (Lint::RedundantParens, Level::Warn, 5)
+        ]
+    );
+}
+
+#[test]
+fn a_directive_on_its_own_line_covers_the_next_line_with_code() {
+    use Lint::{RedundantParens, Unused};
+    let none = LintConfig::new();
+    assert_eq!(
+        lint("// allow(unused)\n4 + 5;\n6;", &none),
+        [(Unused, Level::Warn, 3)]
+    );
+    assert_eq!(
+        lint("// allow(unused)\n\n// just a comment\n4 + 5;\n6;", &none),
+        [(Unused, Level::Warn, 5)]
+    );
+    assert_eq!(
+        lint("// allow(redundant_parens, unused)\n(x);\n(y);", &none),
+        [(RedundantParens, Level::Warn, 3), (Unused, Level::Warn, 3)]
+    );
+    assert_eq!(lint("// allow(warnings)\n(x);;", &none), []);
+    // the directive wins over the flags, and of two directives the later one
+    let deny = config(&[(Level::Deny, "warnings")]);
+    assert_eq!(lint("// allow(unused)\n1;", &deny), []);
+    assert_eq!(lint("// deny(unused)\n1; // allow(unused)", &none), []);
+    assert_eq!(
+        lint("// allow(unused)\n1; // deny(unused)", &none),
+        [(Unused, Level::Deny, 2)]
+    );
+    // comments that are not directives are left alone
+    assert_eq!(
+        lint("// allow unused\n1;\n// forbid(unused)\n2;", &none),
+        [(Unused, Level::Warn, 2), (Unused, Level::Warn, 4)]
+    );
+}
+
+#[test]
+fn directives_warn_about_unknown_lints() {
+    assert_eq!(unknown_lints("// allow(nonexistent)\n1;"), ["nonexistent"]);
+    assert_eq!(
+        unknown_lints("1; // deny(unused, nope)\n// warn(warnings, other)"),
+        ["nope", "other"]
+    );
+    assert!(unknown_lints("// allow(unused, warnings)\n// not(a, directive)").is_empty());
+    // the known names of the same directive still apply
+    let none = LintConfig::new();
+    assert_eq!(lint("// allow(nonexistent, unused)\n1;", &none), []);
+
+    let src = "1; // allow(nonexistent)";
+    let (tokens, comments) = lexer::tokenize_with_comments(src, "test").unwrap();
+    let linter = Linter::new(&none, &tokens, &comments);
+    let unknown: Vec<UnknownLint> = linter.unknown_lints();
+    let report = unknown[0].report(src, "test").to_string();
+    assert!(report.contains("unknown lint `nonexistent`"), "{}", report);
+    assert!(
+        report.contains("| 1; // allow(nonexistent)\n"),
+        "{}",
+        report
+    );
+    // the caret underlines the whole comment
+    assert!(report.contains(&"^".repeat("// allow(nonexistent)".len())));
+}
+
+#[test]
+fn reports_name_the_lint_and_its_level() {
+    let src = "1;";
+    let (tokens, comments) = lexer::tokenize_with_comments(src, "test").unwrap();
+    for (config, prefix) in [
+        (config(&[]), "warning"),
+        (config(&[(Level::Deny, "unused")]), "error"),
+    ] {
+        let mut linter = Linter::new(&config, &tokens, &comments);
+        let mut parser = Parser::new(&tokens, 0);
+        Parser::parse_program(&mut parser);
+        for (lint, span) in &parser.lints {
+            linter.emit(*lint, *span);
+        }
+        let warnings = linter.finish();
+        assert_eq!(warnings[0].is_denied(), prefix == "error");
+        let report = warnings[0].report(src, "test").to_string();
+        assert!(
+            report.contains(&format!("{}: unused value [unused]", prefix)),
+            "{}",
+            report
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use parser::parser::engine::Parser;
+
+/// Parses `src`, returning the program as text and its errors, each as
+/// its title with the line and column it points at.
+fn parse(src: &str) -> (String, Vec<String>) {
+    let tokens = lexer::tokenize(src, "test").unwrap();
+    let mut parser = Parser::new(&tokens, 0);
+    let program = Parser::parse_program(&mut parser);
+    let errors = parser.errors.iter().map(|e| e.to_string()).collect();
+    (program.to_string(), errors)
+}
+
+/// The snippet of each error, with the colors left out.
+fn reports(src: &str) -> Vec<String> {
+    let tokens = lexer::tokenize(src, "test").unwrap();
+    let mut parser = Parser::new(&tokens, 0);
+    Parser::parse_program(&mut parser);
+    parser
+        .errors
+        .iter()
+        .map(|e| strip_colors(&e.report(src, "test").to_string()))
+        .collect()
+}
+
+fn strip_colors(text: &str) -> String {
+    let mut plain = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            chars.by_ref().find(|c| *c == 'm');
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+#[test]
+fn a_missing_comma_points_at_the_second_element() {
+    for (src, at) in [
+        ("[1 2];", "1:4"),
+        ("let m = {\"a\": 1 \"b\": 2};", "1:17"),
+        ("let t = (1, 2 3);", "1:15"),
+        ("let p = P { x: 1 y: 2 };", "1:18"),
+        ("match t { (a b) => 1 }", "1:14"),
+        ("f(a b);", "1:5"),
+        ("x.g(1, 2 y);", "1:10"),
+    ] {
+        let (_, errors) = parse(src);
+        assert_eq!(
+            errors,
+            [format!("missing `,` between elements ({})", at)],
+            "`{}`",
+            src
+        );
+    }
+    let report = &reports("[1 2];")[0];
+    assert!(
+        report.contains("  1 | [1 2];\n    |    ^ expected `,` before this"),
+        "{}",
+        report
+    );
+}
+
+#[test]
+fn an_unclosed_list_points_at_its_opening_delimiter() {
+    for (src, error, close) in [
+        ("[1, 2;", "unclosed `[` (1:1)", "`]`"),
+        ("[1, 2", "unclosed `[` (1:1)", "`]`"),
+        ("let m = {\"a\": 1, \"b\": 2;", "unclosed `{` (1:9)", "`}`"),
+        ("let t = (1, 2;", "unclosed `(` (1:9)", "`)`"),
+        ("let p = P { x: 1, y: 2;", "unclosed `{` (1:11)", "`}`"),
+        ("match t { (a, b => 1 }", "unclosed `(` (1:11)", "`)`"),
+        ("f(a, b;", "unclosed `(` (1:2)", "`)`"),
+    ] {
+        let (_, errors) = parse(src);
+        assert_eq!(errors, [error], "`{}`", src);
+        let report = &reports(src)[0];
+        assert!(
+            report.contains(&format!("expected {} to close this", close)),
+            "{}",
+            report
+        );
+    }
+}
+
+#[test]
+fn the_statement_after_a_broken_list_still_parses() {
+    // the `}` of the map is skipped with the statement, not read as the end
+    // of a block that was never opened
+    let (program, errors) = parse("let m = {\"a\": 1 \"b\": 2};\nlet x = 1;");
+    assert_eq!(errors, ["missing `,` between elements (1:17)"]);
+    assert_eq!(program, "<error>;\nlet x = 1;");
+    let (program, errors) = parse("let m = {\"a\": 1\nlet x = 1;");
+    assert_eq!(errors, ["unclosed `{` (1:9)"]);
+    assert_eq!(program, "<error>;\nlet x = 1;");
+    let (program, errors) = parse("[1, 2;\nlet x = [3 4];\nlet y = 5;");
+    assert_eq!(
+        errors,
+        ["unclosed `[` (1:1)", "missing `,` between elements (2:12)"]
+    );
+    assert_eq!(program, "<error>;\n<error>;\nlet y = 5;");
+}
+
+#[test]
+fn lists_accept_a_trailing_comma() {
+    for (src, expected) in [
+        ("[1, 2,];", "[1, 2];"),
+        ("[1,];", "[1];"),
+        (
+            "let m = {\"a\": 1, \"b\": 2,};",
+            "let m = {\"a\": 1, \"b\": 2};",
+        ),
+        ("let t = (1, 2,);", "let t = (1, 2);"),
+        // only the comma makes a tuple of one element
+        ("let t = (1,);", "let t = (1,);"),
+        ("let p = P { x: 1, y: 2, };", "let p = P { x: 1, y: 2 };"),
+        ("f(1, 2,);", "f(1, 2);"),
+    ] {
+        let (program, errors) = parse(src);
+        assert!(errors.is_empty(), "`{}`: {:?}", src, errors);
+        assert_eq!(program, expected);
+    }
+    // but not a comma alone or two in a row
+    for src in ["[,];", "[1,,2];", "let m = {,};", "f(,);"] {
+        let (_, errors) = parse(src);
+        assert_eq!(errors.len(), 1, "`{}`", src);
+    }
+}
@@ -0,0 +1,174 @@
+use std::fs;
+use std::path::PathBuf;
+
+use parser::dump::{Format, dump};
+use parser::modules::{Loader, ModuleError};
+use parser::nodes::stmts::Program;
+use parser::parser::engine::Parser;
+use parser::parser::errors::ParserError;
+
+fn parse(src: &str) -> Program {
+    let tokens = lexer::tokenize(src, "test").unwrap();
+    let mut parser = Parser::new(&tokens, 0);
+    let program = Parser::parse_program(&mut parser);
+    assert!(parser.errors.is_empty(), "`{}` did not parse: {:?}", src, parser.errors);
+    program
+}
+
+/// Writes `files` into a fresh directory named after the test.
+fn write_tree(test: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("modules-{}-{}", std::process::id(), test));
+    let _ = fs::remove_dir_all(&dir);
+    for (name, src) in files {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, src).unwrap();
+    }
+    dir
+}
+
+fn file_name(path: &std::path::Path) -> String {
+    path.file_name().unwrap().to_string_lossy().into_owned()
+}
+
+#[test]
+fn imports_and_public_declarations_parse() {
+    let src = "import \"shapes/circle\";\nimport geometry.{Point, area};\nimport a.b;\n\
+               pub struct Point { x: int }\npub func area() {}\npub enum E { A }";
+    assert_eq!(parse(src).to_string(), src.replace("area() {}", "area() { }"));
+    let sexpr = dump(&parse(src), Format::Sexpr);
+    let lines: Vec<&str> = sexpr.lines().collect();
+    assert!(lines[1].starts_with("(Import :module \"geometry\" (Item :name \"Point\")"));
+    assert!(lines[3].starts_with("(Struct :public true :name \"Point\""));
+}
+
+#[test]
+fn imports_and_pub_only_appear_at_the_top_level() {
+    let src = "func f() { import geometry; }\nlet x = { pub func g() {} };";
+    let tokens = lexer::tokenize(src, "test").unwrap();
+    let mut parser = Parser::new(&tokens, 0);
+    Parser::parse_program(&mut parser);
+    let keywords: Vec<&str> = parser
+        .errors
+        .iter()
+        .filter_map(|e| match e {
+            ParserError::NotTopLevel { keyword } => Some(keyword.value),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(keywords, ["import", "pub"]);
+}
+
+#[test]
+fn items_must_exist_and_be_public() {
+    let dir = write_tree(
+        "visibility",
+        &[
+            ("main.txt", "import geometry.{Point, area, hidden, nope};"),
+            ("geometry.txt", "pub struct Point { x: int }\npub func area() {}\nfunc hidden() {}"),
+        ],
+    );
+    let mut loader = Loader::new();
+    let root = loader.load(dir.join("main.txt")).unwrap();
+    let import = &loader.module(root).imports[0];
+    assert_eq!(import.items, ["Point", "area"]);
+    let errors: Vec<String> = loader.errors.iter().map(|e| e.to_string()).collect();
+    let geometry = dir.join("geometry.txt").display().to_string();
+    assert_eq!(
+        errors,
+        [
+            format!("`hidden` is private to `{}`", geometry),
+            format!("`{}` has no item named `nope`", geometry),
+        ]
+    );
+}
+
+#[test]
+fn modules_are_found_next_to_the_importer_then_in_search_paths() {
+    let dir = write_tree(
+        "search",
+        &[
+            ("app/main.txt", "import \"lib/util\";\nimport shapes.circle;\nimport missing;"),
+            ("app/lib/util.txt", "pub func helper() {}"),
+            ("std/shapes/circle.txt", "pub func area() {}"),
+        ],
+    );
+    let mut loader = Loader::new();
+    loader.search_paths.push(dir.join("std"));
+    let root = loader.load(dir.join("app/main.txt")).unwrap();
+    // a whole module brings in every public item
+    let imports = &loader.module(root).imports;
+    assert_eq!(imports[0].items, ["helper"]);
+    assert_eq!(file_name(&loader.module(imports[1].module).path), "circle.txt");
+    match loader.errors.as_slice() {
+        [ModuleError::NotFound { source, tried, .. }] => {
+            assert_eq!(source, "missing");
+            assert_eq!(tried, &[dir.join("app/missing.txt"), dir.join("std/missing.txt")]);
+        }
+        errors => panic!("unexpected errors: {:?}", errors),
+    }
+}
+
+#[test]
+fn a_module_imported_twice_is_loaded_once() {
+    let dir = write_tree(
+        "diamond",
+        &[
+            ("main.txt", "import left;\nimport right;"),
+            ("left.txt", "import shared;"),
+            ("right.txt", "import \"shared.txt\";"),
+            ("shared.txt", "pub func f() {}"),
+        ],
+    );
+    let mut loader = Loader::new();
+    let root = loader.load(dir.join("main.txt")).unwrap();
+    assert!(loader.errors.is_empty(), "{:?}", loader.errors);
+    assert_eq!(loader.modules().count(), 4);
+    let shared = |side: usize| {
+        let module = loader.module(root).imports[side].module;
+        loader.module(module).imports[0].module
+    };
+    assert_eq!(shared(0), shared(1));
+}
+
+#[test]
+fn cycles_are_reported_with_the_import_chain() {
+    let dir = write_tree(
+        "cycle",
+        &[
+            ("main.txt", "import a;"),
+            ("a.txt", "import b;"),
+            ("b.txt", "import \"main\";"),
+        ],
+    );
+    let mut loader = Loader::new();
+    loader.load(dir.join("main.txt")).unwrap();
+    match loader.errors.as_slice() {
+        [error @ ModuleError::Cycle { chain, at }] => {
+            let names: Vec<String> = chain.iter().map(|p| file_name(p)).collect();
+            assert_eq!(names, ["main.txt", "a.txt", "b.txt", "main.txt"]);
+            assert_eq!(file_name(&loader.module(at.module).path), "b.txt");
+            let report = error.report(&loader).to_string();
+            assert!(report.contains("import \"main\";"), "{}", report);
+        }
+        errors => panic!("unexpected errors: {:?}", errors),
+    }
+}
+
+#[test]
+fn syntax_errors_of_imported_modules_are_collected() {
+    let dir = write_tree(
+        "syntax",
+        &[("main.txt", "import broken;"), ("broken.txt", "pub let x = 1;")],
+    );
+    let mut loader = Loader::new();
+    loader.load(dir.join("main.txt")).unwrap();
+    match loader.errors.as_slice() {
+        [ModuleError::Syntax { path, report }] => {
+            assert_eq!(file_name(path), "broken.txt");
+            assert!(report.contains("found `let`"), "{}", report);
+            assert!(report.contains("pub let x = 1;"), "{}", report);
+        }
+        errors => panic!("unexpected errors: {:?}", errors),
+    }
+}
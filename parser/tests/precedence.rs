@@ -0,0 +1,109 @@
+use parser::parser::engine::Parser;
+use parser::parser::lookups::BindingPower;
+
+/// Parses `src` as one expression and returns its fully parenthesized form.
+fn shape(src: &str) -> String {
+    let tokens = lexer::tokenize(src, "test").unwrap();
+    let mut parser = Parser::new(&tokens, 0);
+    let expr = Parser::parse_expr(&mut parser, BindingPower::Primary).unwrap();
+    assert!(parser.at_end(), "`{}` was not fully parsed", src);
+    parser.ast.display_expr(expr).to_string()
+}
+
+#[test]
+fn additive_and_multiplicative_are_left_associative() {
+    assert_eq!(shape("1 - 2 - 3"), "((1 - 2) - 3)");
+    assert_eq!(shape("1 + 2 - 3 + 4"), "(((1 + 2) - 3) + 4)");
+    assert_eq!(shape("8 / 4 / 2"), "((8 / 4) / 2)");
+    assert_eq!(shape("8 % 3 * 2"), "((8 % 3) * 2)");
+}
+
+#[test]
+fn exponentiation_is_right_associative() {
+    assert_eq!(shape("2 ** 3 ** 2"), "(2 ** (3 ** 2))");
+    assert_eq!(shape("2 ** 3 * 4"), "((2 ** 3) * 4)");
+    assert_eq!(shape("4 * 2 ** 3"), "(4 * (2 ** 3))");
+}
+
+#[test]
+fn multiplication_binds_tighter_than_addition() {
+    assert_eq!(shape("1 + 2 * 3"), "(1 + (2 * 3))");
+    assert_eq!(shape("1 * 2 + 3"), "((1 * 2) + 3)");
+    assert_eq!(shape("1 - 2 * 3 - 4"), "((1 - (2 * 3)) - 4)");
+}
+
+#[test]
+fn prefix_operators_and_exponentiation() {
+    assert_eq!(shape("-2 ** 2"), "(-(2 ** 2))");
+    assert_eq!(shape("2 ** -1"), "(2 ** (-1))");
+    assert_eq!(shape("-1 * 2"), "((-1) * 2)");
+    assert_eq!(shape("!1 && 2"), "((!1) && 2)");
+    assert_eq!(shape("--1"), "(-(-1))");
+}
+
+#[test]
+fn logical_or_is_looser_than_logical_and() {
+    assert_eq!(shape("1 || 2 && 3"), "(1 || (2 && 3))");
+    assert_eq!(shape("1 && 2 || 3"), "((1 && 2) || 3)");
+    assert_eq!(shape("1 || 2 || 3"), "((1 || 2) || 3)");
+}
+
+#[test]
+fn comparison_and_equality() {
+    assert_eq!(shape("1 == 2 < 3"), "(1 == (2 < 3))");
+    assert_eq!(shape("1 < 2 == 3 >= 4"), "((1 < 2) == (3 >= 4))");
+    assert_eq!(shape("1 + 2 <= 3 * 4"), "((1 + 2) <= (3 * 4))");
+    assert_eq!(shape("1 != 2 && 3 > 4"), "((1 != 2) && (3 > 4))");
+}
+
+#[test]
+fn bitwise_and_shift_levels() {
+    assert_eq!(shape("1 | 2 ^ 3 & 4"), "(1 | (2 ^ (3 & 4)))");
+    assert_eq!(shape("1 & 2 == 3"), "((1 & 2) == 3)");
+    assert_eq!(shape("1 << 2 + 3"), "(1 << (2 + 3))");
+    assert_eq!(shape("1 >> 2 >> 3"), "((1 >> 2) >> 3)");
+    assert_eq!(shape("1 & 2 << 3"), "(1 & (2 << 3))");
+}
+
+#[test]
+fn levels_are_ordered_by_strength() {
+    use BindingPower::*;
+    let levels = [
+        Primary,
+        Range,
+        LogicalOr,
+        LogicalAnd,
+        Equality,
+        Comparison,
+        BitOr,
+        BitXor,
+        BitAnd,
+        Shift,
+        Additive,
+        Multiplicative,
+        Cast,
+        Unary,
+        Exponent,
+        Postfix,
+    ];
+    for pair in levels.windows(2) {
+        assert!(pair[0] < pair[1], "{:?} should bind looser than {:?}", pair[0], pair[1]);
+    }
+}
+
+#[test]
+fn postfix_operators_chain_and_bind_tightest() {
+    assert_eq!(shape("a.b(c)[d]"), "a.b(c)[d]");
+    assert_eq!(shape("-f(x)"), "(-f(x))");
+    assert_eq!(shape("2 ** xs[0]"), "(2 ** xs[0])");
+    assert_eq!(shape("f(1, 2 + 3)(4)"), "f(1, (2 + 3))(4)");
+    assert_eq!(shape("(1 + 2) * 3"), "(((1 + 2)) * 3)");
+}
+
+#[test]
+fn casts_sit_between_arithmetic_and_prefix_operators() {
+    assert_eq!(shape("-x as int"), "((-x) as int)");
+    assert_eq!(shape("a * b as f64"), "(a * (b as f64))");
+    assert_eq!(shape("x as i16 as int"), "((x as i16) as int)");
+    assert_eq!(shape("f(x) as [int]?"), "(f(x) as [int]?)");
+}
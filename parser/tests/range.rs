@@ -0,0 +1,74 @@
+use lexer::token_type::TokenKind;
+use parser::nodes::exprs::{BinaryOp, ExprKind};
+use parser::nodes::stmts::{Program, StmtKind};
+use parser::parser::engine::Parser;
+use parser::parser::lookups::BindingPower;
+use parser::unparse::unparse;
+
+fn kinds(src: &str) -> Vec<TokenKind> {
+    let tokens = lexer::tokenize(src, "test").unwrap();
+    tokens.iter().map(|t| t.kind).collect()
+}
+
+fn parse(src: &str) -> Program {
+    let tokens = lexer::tokenize(src, "test").unwrap();
+    let mut parser = Parser::new(&tokens, 0);
+    let program = Parser::parse_program(&mut parser);
+    assert!(parser.errors.is_empty(), "`{}` did not parse: {:?}", src, parser.errors);
+    program
+}
+
+fn shape(src: &str) -> String {
+    let tokens = lexer::tokenize(src, "test").unwrap();
+    let mut parser = Parser::new(&tokens, 0);
+    let expr = Parser::parse_expr(&mut parser, BindingPower::Primary).unwrap();
+    assert!(parser.at_end(), "`{}` was not fully parsed", src);
+    parser.ast.display_expr(expr).to_string()
+}
+
+#[test]
+fn numbers_next_to_a_range_keep_their_dots_apart() {
+    use TokenKind::{DOUBLE_DOT, DOUBLE_DOT_EQUAL, EOF, FLOAT, IDENTIFIER, INTEGER};
+    assert_eq!(kinds("0..10"), [INTEGER, DOUBLE_DOT, INTEGER, EOF]);
+    assert_eq!(kinds("1..=n"), [INTEGER, DOUBLE_DOT_EQUAL, IDENTIFIER, EOF]);
+    assert_eq!(kinds("1.5..2.5"), [FLOAT, DOUBLE_DOT, FLOAT, EOF]);
+    assert_eq!(kinds("a...5"), [IDENTIFIER, DOUBLE_DOT, FLOAT, EOF]);
+}
+
+#[test]
+fn ranges_bind_looser_than_every_other_operator() {
+    assert_eq!(shape("0..n + 1"), "(0 .. (n + 1))");
+    assert_eq!(shape("a || b..=c && d"), "((a || b) ..= (c && d))");
+    assert_eq!(shape("-1..xs.len()"), "((-1) .. xs.len())");
+    assert_eq!(shape("a..b..c"), "((a .. b) .. c)");
+}
+
+#[test]
+fn for_loops_iterate_over_any_expression() {
+    let program = parse(
+        "for i in 0..10 { print(i); }
+        for x in [1, 2, 3] {}
+        for k in {\"a\": 1} {}
+        for c in \"abc\" {}
+        for p in points {}",
+    );
+    let iterables: Vec<&ExprKind> = program
+        .stmts
+        .iter()
+        .map(|stmt| match program.ast.stmt(*stmt) {
+            StmtKind::For { iterable, .. } => program.ast.expr(*iterable),
+            other => panic!("not a loop: {:?}", other),
+        })
+        .collect();
+    assert!(matches!(iterables[0], ExprKind::Binary { op: BinaryOp::Range, .. }));
+    assert!(matches!(iterables[1], ExprKind::Array(_)));
+    assert!(matches!(iterables[2], ExprKind::Map(_)));
+    assert!(matches!(iterables[3], ExprKind::Literal(_)));
+    assert!(matches!(iterables[4], ExprKind::Ident(_)));
+}
+
+#[test]
+fn ranges_unparse_without_spaces() {
+    let src = "for i in 1..=n * 2 {\n    let r = (0..i) == 1.5..x;\n}\n";
+    assert_eq!(unparse(&parse(src)), src);
+}
@@ -0,0 +1,167 @@
+use lexer::token_type::TokenKind;
+use parser::nodes::exprs::ExprKind;
+use parser::nodes::stmts::{Program, StmtKind};
+use parser::parser::engine::Parser;
+use parser::parser::errors::ParserError;
+
+/// What a test looks at in an error: where it points, the token found
+/// there and the kinds that were expected.
+#[derive(Debug)]
+struct Error {
+    at: (usize, usize),
+    found: Option<TokenKind>,
+    expected: Vec<TokenKind>,
+}
+
+/// Parses `src`, returning the program and its errors in the order they
+/// were recorded.
+fn parse(src: &str) -> (Program, Vec<Error>) {
+    let tokens = lexer::tokenize(src, "test").unwrap();
+    let mut parser = Parser::new(&tokens, 0);
+    let program = Parser::parse_program(&mut parser);
+    let errors = parser
+        .errors
+        .iter()
+        .map(|e| Error {
+            at: (e.span().start.line, e.span().start.column),
+            found: match e {
+                ParserError::UnexpectedToken { found, .. } => Some(found.kind),
+                _ => None,
+            },
+            expected: e.expected().to_vec(),
+        })
+        .collect();
+    (program, errors)
+}
+
+fn positions(errors: &[Error]) -> Vec<(usize, usize)> {
+    errors.iter().map(|e| e.at).collect()
+}
+
+#[test]
+fn independent_errors_are_all_reported() {
+    let src = "\
+let a = 1 +;
+let b = 2;
+func f() {
+    let c = ) 3;
+    c = c * 2;
+    return c
+}
+while { }
+let d = [1, 2];
+print(d +);
+if x { y = } else { z; }
+let e = ;
+let g = 5;
+";
+    let (program, errors) = parse(src);
+    assert_eq!(
+        positions(&errors),
+        [
+            (1, 12),
+            (4, 13),
+            (7, 1),
+            (8, 7),
+            (10, 10),
+            (11, 12),
+            (12, 9)
+        ]
+    );
+    use TokenKind::*;
+    let found: Vec<_> = errors.iter().map(|e| e.found).collect();
+    let kinds = [SEMICOLON, RPAREN, RBRACE, LBRACE, RPAREN, RBRACE, SEMICOLON];
+    assert_eq!(found, kinds.map(Some));
+    // a statement that failed is an `Error`, an expression left out before
+    // the `;` an `Error` in the statement, and everything else survives
+    assert_eq!(
+        program.to_string(),
+        "\
+<error>;
+let b = 2;
+func f() { <error>; c = (c * 2); <error>; }
+<error>;
+let d = [1, 2];
+<error>;
+if x { <error>; } else { z; }
+let e = <error>;
+let g = 5;"
+    );
+}
+
+#[test]
+fn recovery_resumes_at_statement_boundaries() {
+    // just past a `;`
+    let (program, errors) = parse("let a = ) 1; let b = 2;");
+    assert_eq!(positions(&errors), [(1, 9)]);
+    assert_eq!(program.to_string(), "<error>;\nlet b = 2;");
+    // before the `}` closing the block
+    let (program, errors) = parse("{ let a = 1 + } let b = 2;");
+    assert_eq!(positions(&errors), [(1, 15)]);
+    assert_eq!(program.to_string(), "{ <error>; }\nlet b = 2;");
+    // before a keyword that starts a statement, even with no `;` between,
+    // unless it can start the missing operand as `if` and `func` can
+    for keyword in ["let", "var", "struct", "enum", "while", "for", "return"] {
+        let src = format!("let a = 1 +\n{} ", keyword);
+        let (_, errors) = parse(&format!("{}b = 2;", src));
+        assert_eq!(
+            positions(&errors).first(),
+            Some(&(2, 1)),
+            "before `{}`",
+            keyword
+        );
+    }
+    let (program, errors) = parse("let a = 1 + let b = 2; while x { b = b - 1; }");
+    assert_eq!(positions(&errors), [(1, 13)]);
+    assert_eq!(
+        program.to_string(),
+        "<error>;\nlet b = 2;\nwhile x { b = (b - 1); }"
+    );
+}
+
+#[test]
+fn recovery_always_makes_progress() {
+    // a statement that cannot start at all is skipped a token at a time
+    let (program, errors) = parse(") ] let a = 1;");
+    assert_eq!(positions(&errors), [(1, 1)]);
+    assert_eq!(program.to_string(), "<error>;\nlet a = 1;");
+    let (_, errors) = parse("let x = 1");
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn a_missing_expression_becomes_an_error_placeholder() {
+    let (program, errors) = parse("let x = ;\nx = ;\nreturn ;");
+    assert_eq!(positions(&errors), [(1, 9), (2, 5)]);
+    let StmtKind::Let {
+        value: Some(value), ..
+    } = program.ast.stmt(program.stmts[0])
+    else {
+        panic!("not a `let`: {}", program);
+    };
+    assert!(matches!(program.ast.expr(*value), ExprKind::Error));
+    assert_eq!(
+        program.to_string(),
+        "let x = <error>;\nx = <error>;\nreturn;"
+    );
+}
+
+#[test]
+fn a_missing_condition_is_reported_at_the_body() {
+    for (src, column) in [
+        ("while { }\nlet x = 1;", 7),
+        ("if { }\nlet x = 1;", 4),
+        ("for x in { }\nlet x = 1;", 10),
+    ] {
+        let (program, errors) = parse(src);
+        // not a missing `{` on the `let` of the next line
+        assert_eq!(positions(&errors), [(1, column)], "{}", src);
+        assert_eq!(errors[0].found, Some(TokenKind::LBRACE));
+        assert!(!errors[0].expected.contains(&TokenKind::LBRACE));
+        assert_eq!(program.to_string(), "<error>;\nlet x = 1;");
+    }
+    // a block is still a condition when the body follows it
+    let (program, errors) = parse("while { x } { y; }");
+    assert!(errors.is_empty());
+    assert_eq!(program.to_string(), "while { x } { y; }");
+}
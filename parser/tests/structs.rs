@@ -0,0 +1,125 @@
+use parser::nodes::exprs::ExprKind;
+use parser::nodes::stmts::StmtKind;
+use parser::parser::engine::Parser;
+
+/// Parses `src`, returning the program as text and its errors, each as
+/// its title with the line and column it points at.
+fn parse(src: &str) -> (String, Vec<String>) {
+    let tokens = lexer::tokenize(src, "test").unwrap();
+    let mut parser = Parser::new(&tokens, 0);
+    let program = Parser::parse_program(&mut parser);
+    let errors = parser.errors.iter().map(|e| e.to_string()).collect();
+    (program.to_string(), errors)
+}
+
+fn errors(src: &str) -> Vec<String> {
+    parse(src).1
+}
+
+#[test]
+fn a_field_given_twice_is_reported_at_the_second() {
+    assert_eq!(
+        errors("struct P { x: int, y: int, x: float }"),
+        ["duplicate field `x` (1:28)"]
+    );
+    assert_eq!(
+        errors("struct P { x: int, y: int }\nlet p = P { x: 1, y: 2, x: 3 };"),
+        ["duplicate field `x` (2:25)"]
+    );
+    // a shorthand field counts as given too
+    assert_eq!(
+        errors("struct P { x: int }\nlet p = P { x, x: 1 };"),
+        ["duplicate field `x` (2:16)"]
+    );
+}
+
+#[test]
+fn a_literal_must_give_every_declared_field() {
+    let src = "struct P { x: int, y: int, z: int }\nlet p = P { y: 1 };";
+    assert_eq!(errors(src), ["missing `x`, `z` in `P` literal (2:9)"]);
+    // the declaration may come after the literal
+    assert_eq!(
+        errors("let p = P { x: 1 };\nstruct P { x: int, y: int }"),
+        ["missing `y` in `P` literal (1:9)"]
+    );
+    // literals of structs declared nowhere are left to a later pass
+    assert!(errors("let q = Q { a: 1 };").is_empty());
+}
+
+#[test]
+fn shorthand_fields_are_given_by_a_variable_of_the_same_name() {
+    let (program, errors) =
+        parse("struct P { x: int, y: int }\nlet x = 1;\nlet p = P { x, y: 2 };");
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(program.lines().last(), Some("let p = P { x, y: 2 };"));
+
+    let tokens = lexer::tokenize("let p = P { x, y: x };", "test").unwrap();
+    let mut parser = Parser::new(&tokens, 0);
+    let program = Parser::parse_program(&mut parser);
+    let StmtKind::Let {
+        value: Some(value), ..
+    } = program.ast.stmt(program.stmts[0])
+    else {
+        panic!("not a `let`: {}", program);
+    };
+    let ExprKind::Struct { fields, .. } = program.ast.expr(*value) else {
+        panic!("not a struct literal: {}", program);
+    };
+    let shorthand: Vec<_> = fields
+        .iter()
+        .map(|f| (f.name.as_str(), f.shorthand))
+        .collect();
+    assert_eq!(shorthand, [("x", true), ("y", false)]);
+    for field in fields {
+        assert!(matches!(program.ast.expr(field.value), ExprKind::Ident(name) if name == "x"));
+    }
+}
+
+#[test]
+fn struct_names_are_scoped_to_their_block() {
+    // a literal goes by the struct of its name in the innermost block that
+    // declares one, anywhere in that block
+    let src = "\
+struct P { x: int, y: int }
+func f() {
+    let a = P { x: 1 };
+    struct P { x: int }
+    if true {
+        let b = P { y: 2 };
+    }
+}
+let c = P { x: 1, y: 2 };
+let d = P { x: 1 };
+";
+    assert_eq!(
+        errors(src),
+        [
+            "missing `x` in `P` literal (6:17)",
+            "missing `y` in `P` literal (10:9)"
+        ]
+    );
+    // a struct declared in a block is unknown outside of it
+    assert!(errors("func f() { struct Q { a: int } }\nlet q = Q { };").is_empty());
+    // and a name declared twice in one block is ambiguous, so unchecked
+    assert!(errors("struct P { x: int }\nstruct P { y: int }\nlet p = P { };").is_empty());
+}
+
+#[test]
+fn missing_fields_are_reported_in_source_order() {
+    let src = "\
+struct P { x: int }
+let a = P { };
+let b = 1 +;
+func f() {
+    let c = P { };
+    let d = ) 1;
+}
+let e = P { };
+";
+    let errors = errors(src);
+    let lines: Vec<&str> = errors
+        .iter()
+        .map(|e| e.rsplit('(').next().unwrap())
+        .collect();
+    assert_eq!(lines, ["2:9)", "3:12)", "5:13)", "6:13)", "8:9)"]);
+}
@@ -0,0 +1,101 @@
+use lexer::token_type::TokenKind;
+use parser::parser::engine::Parser;
+use parser::parser::lookups::BindingPower;
+use parser::parser::trace::{TraceEvent, TraceStep};
+
+fn trace(src: &str) -> Vec<TraceEvent> {
+    let tokens = lexer::tokenize(src, "test").unwrap();
+    let mut parser = Parser::new(&tokens, 0);
+    parser.trace = Some(Vec::new());
+    Parser::parse_program(&mut parser);
+    assert!(parser.errors.is_empty(), "`{}` did not parse", src);
+    parser.trace.unwrap()
+}
+
+fn enter(bp: BindingPower) -> TraceStep {
+    TraceStep::Enter { bp }
+}
+
+fn compare(token: TokenKind, token_bp: BindingPower, min_bp: BindingPower) -> TraceStep {
+    TraceStep::Compare {
+        token,
+        token_bp,
+        min_bp,
+        binds: token_bp > min_bp,
+    }
+}
+
+#[test]
+fn every_decision_is_recorded_in_order() {
+    use BindingPower::{Additive, Primary};
+    use TokenKind::{DASH, SEMICOLON};
+
+    let event = |depth, pos, step| TraceEvent { depth, pos, step };
+    let ident = TraceStep::Nud {
+        token: TokenKind::IDENTIFIER,
+        handler: "parse_primary_expr",
+    };
+    let minus = TraceStep::Led {
+        token: DASH,
+        handler: "parse_binary_expr",
+    };
+    assert_eq!(
+        trace("a - b;"),
+        [
+            event(0, 0, enter(Primary)),
+            event(1, 0, ident),
+            event(1, 1, compare(DASH, Additive, Primary)),
+            event(1, 1, minus),
+            event(2, 2, enter(Additive)),
+            event(3, 2, ident),
+            event(3, 3, compare(SEMICOLON, Primary, Additive)),
+            event(2, 3, TraceStep::Exit),
+            event(1, 3, compare(SEMICOLON, Primary, Primary)),
+            event(0, 3, TraceStep::Exit),
+        ]
+    );
+}
+
+#[test]
+fn right_associative_operands_are_parsed_one_level_lower() {
+    // the right operand of `**` is parsed at `Unary`, so the next `**` binds
+    let steps: Vec<TraceStep> = trace("2 ** 3 ** 4;").into_iter().map(|e| e.step).collect();
+    let enters: Vec<TraceStep> = steps
+        .into_iter()
+        .filter(|s| matches!(s, TraceStep::Enter { .. }))
+        .collect();
+    assert_eq!(
+        enters,
+        [
+            enter(BindingPower::Primary),
+            enter(BindingPower::Unary),
+            enter(BindingPower::Unary),
+        ]
+    );
+}
+
+#[test]
+fn traces_render_one_indented_line_per_event() {
+    let lines: Vec<String> = trace("-x;").iter().map(|e| e.to_string()).collect();
+    assert_eq!(
+        lines,
+        [
+            "parse_expr Primary at 0",
+            "  nud parse_unary_expr for `-` at 0",
+            "    parse_expr Unary at 1",
+            "      nud parse_primary_expr for identifier at 1",
+            "      `;` Primary <= Unary, stop at 2",
+            "    done at 2",
+            "  `;` Primary <= Primary, stop at 2",
+            "done at 2",
+        ]
+    );
+}
+
+#[test]
+fn nothing_is_recorded_unless_asked() {
+    let tokens = lexer::tokenize("1 + 2;", "test").unwrap();
+    let mut parser = Parser::new(&tokens, 0);
+    Parser::parse_program(&mut parser);
+    assert!(parser.trace.is_none());
+}
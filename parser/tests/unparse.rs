@@ -0,0 +1,607 @@
+mod common;
+
+use common::Rng;
+use lexer::token::{Position, SourceSpan};
+use parser::nodes::ast::Ast;
+use parser::nodes::decls::{EnumDecl, FieldDecl, FuncDecl, ImportDecl, ImportItem, ImportSource};
+use parser::nodes::decls::{Param, StructDecl, VariantDecl};
+use parser::nodes::exprs::{
+    ArmBody, BinaryOp, ExprId, ExprKind, FieldInit, LambdaBody, LambdaExpr, LiteralValue, MatchArm,
+    UnaryOp,
+};
+use parser::nodes::fold::{self, Fold};
+use parser::nodes::patterns::{Pattern, PatternKind};
+use parser::nodes::stmts::{Block, Program, StmtId, StmtKind};
+use parser::nodes::type_exprs::{Primitive, TypeExpr, TypeExprKind};
+use parser::parser::engine::Parser;
+use parser::unparse::{unparse, unparse_expr};
+
+fn parse(src: &str) -> Program {
+    let tokens =
+        lexer::tokenize(src, "test").unwrap_or_else(|e| panic!("`{}` did not lex: {}", src, e));
+    let mut parser = Parser::new(&tokens, 0);
+    let program = Parser::parse_program(&mut parser);
+    if let Some(error) = parser.errors.first() {
+        panic!("did not parse:\n{}\n{}", src, error.report(src, "test"));
+    }
+    program
+}
+
+/// Drops every `Grouping`, leaving the expression inside.
+struct StripGroupings;
+
+impl Fold for StripGroupings {
+    fn fold_expr(&mut self, from: &Ast, into: &mut Ast, expr: ExprId) -> ExprId {
+        match from.expr(expr) {
+            ExprKind::Grouping(inner) => self.fold_expr(from, into, *inner),
+            _ => fold::walk_expr(self, from, into, expr),
+        }
+    }
+}
+
+/// The structure of a program: every field of every node once parentheses
+/// are dropped, but none of the spans. Folding allocates the nodes of two
+/// programs of the same structure in the same order, so they come out the
+/// same, down to their ids.
+fn structure(program: Program) -> String {
+    let nodes = format!("{:#?}", StripGroupings.fold_program(program));
+    let mut text = String::new();
+    let mut rest = nodes.as_str();
+    while let Some(start) = rest.find("SourceSpan {") {
+        text.push_str(&rest[..start]);
+        // a span holds two positions, so it ends at its third `}`
+        let (end, _) = rest[start..].match_indices('}').nth(2).unwrap();
+        rest = &rest[start + end + 1..];
+    }
+    text.push_str(rest);
+    text
+}
+
+/// Unparses `src` once the parentheses written in it are dropped.
+fn tidy(src: &str) -> String {
+    unparse(&StripGroupings.fold_program(parse(src)))
+}
+
+fn shape(src: &str) -> String {
+    let program = parse(src);
+    let Some(StmtKind::Expr(expr)) = program.stmts.first().map(|s| program.ast.stmt(*s)) else {
+        panic!("`{}` is not an expression statement", src);
+    };
+    unparse_expr(&program.ast, *expr)
+}
+
+#[test]
+fn only_needed_parentheses_are_added() {
+    let src = "((1 + 2) * 3);\n((a - b) - (c - d));\n((2 ** 3) ** 4);\n(-(a ** b));\n\
+               ((-a) ** b);\n((a as int) as f64);\n(-(x as int));\n((-x) as int);\n\
+               ((a + b)(c));\n((f.g)(h)[0]);";
+    let lines: Vec<String> = tidy(src).lines().map(str::to_string).collect();
+    assert_eq!(
+        lines,
+        [
+            "(1 + 2) * 3;",
+            "a - b - (c - d);",
+            "(2 ** 3) ** 4;",
+            "-a ** b;",
+            "(-a) ** b;",
+            "a as int as f64;",
+            "-(x as int);",
+            "-x as int;",
+            "(a + b)(c);",
+            "f.g(h)[0];",
+        ]
+    );
+}
+
+#[test]
+fn written_parentheses_are_kept() {
+    assert_eq!(shape("((a)) + (b * c);"), "((a)) + (b * c)");
+}
+
+#[test]
+fn statements_are_laid_out_one_per_line() {
+    let src = "func f(a: int) -> int { if a > 0 { return a; } else { return -a; } }";
+    assert_eq!(
+        unparse(&parse(src)),
+        "func f(a: int) -> int {\n    if a > 0 {\n        return a;\n    } else {\n        return -a;\n    }\n}\n"
+    );
+}
+
+#[test]
+fn tokens_that_would_merge_are_separated() {
+    assert_eq!(tidy("&(&x);"), "& &x;\n");
+    assert_eq!(tidy("*(*x);"), "* *x;\n");
+    assert_eq!(tidy("(1).x;"), "(1).x;\n");
+    assert_eq!(tidy("(1.5).x;"), "(1.5).x;\n");
+}
+
+#[test]
+fn braces_that_would_be_blocks_are_parenthesized() {
+    assert_eq!(tidy("({1: 2})[1] = 3;"), "({1: 2})[1] = 3;\n");
+    assert_eq!(
+        tidy("(match x { _ => 1 }) + 1;"),
+        "(match x {\n    _ => 1,\n}) + 1;\n"
+    );
+    assert_eq!(
+        tidy("match x { _ => ({}) }"),
+        "match x {\n    _ => ({}),\n}\n"
+    );
+    assert_eq!(tidy("if (P { x: 1 }) == p {}"), "if (P { x: 1 }) == p {}\n");
+    assert_eq!(tidy("f((P { x: 1 }));"), "f(P { x: 1 });\n");
+}
+
+const NAMES: &[&str] = &["a", "b", "x", "y", "foo", "_tmp", "Val"];
+const FLOATS: &[f64] = &[0.5, 3.0, 0.1, 1e20, 1e-7, 12.25];
+const BINARY_OPS: &[BinaryOp] = &[
+    BinaryOp::Add,
+    BinaryOp::Sub,
+    BinaryOp::Mul,
+    BinaryOp::Div,
+    BinaryOp::Mod,
+    BinaryOp::Equal,
+    BinaryOp::NotEqual,
+    BinaryOp::Less,
+    BinaryOp::LessEqual,
+    BinaryOp::Greater,
+    BinaryOp::GreaterEqual,
+    BinaryOp::And,
+    BinaryOp::Or,
+    BinaryOp::BitAnd,
+    BinaryOp::BitOr,
+    BinaryOp::BitXor,
+    BinaryOp::Shl,
+    BinaryOp::Shr,
+    BinaryOp::Pow,
+    BinaryOp::Range,
+    BinaryOp::RangeInclusive,
+];
+const UNARY_OPS: &[UnaryOp] = &[UnaryOp::Ref, UnaryOp::Deref, UnaryOp::Neg, UnaryOp::Not];
+const PRIMITIVES: &[Primitive] = &[
+    Primitive::Int,
+    Primitive::F64,
+    Primitive::String,
+    Primitive::Bool,
+];
+
+/// Builds random programs straight into an arena, the way a code
+/// generator would, without any `Grouping`.
+struct Gen {
+    rng: Rng,
+    ast: Ast,
+}
+
+impl Gen {
+    fn span() -> SourceSpan {
+        SourceSpan::new(Position::new(1, 1), Position::new(1, 1))
+    }
+
+    fn name(&mut self) -> String {
+        self.rng.pick(NAMES).to_string()
+    }
+
+    /// Up to `max` distinct names.
+    fn names(&mut self, max: usize) -> Vec<String> {
+        let mut names: Vec<String> = (0..self.rng.below(max + 1)).map(|_| self.name()).collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    fn alloc(&mut self, kind: ExprKind) -> ExprId {
+        self.ast.alloc_expr(kind, Gen::span())
+    }
+
+    fn literal(&mut self) -> LiteralValue {
+        match self.rng.below(4) {
+            0 => LiteralValue::Int(self.rng.below(1000) as i128),
+            1 => LiteralValue::Float(*self.rng.pick(FLOATS)),
+            2 => LiteralValue::Str(self.rng.pick(&["", "hi", "a b", "ñ"]).to_string()),
+            _ => LiteralValue::Bool(self.rng.chance(50)),
+        }
+    }
+
+    fn exprs(&mut self, depth: usize, max: usize) -> Vec<ExprId> {
+        (0..self.rng.below(max + 1))
+            .map(|_| self.expr(depth))
+            .collect()
+    }
+
+    fn expr(&mut self, depth: usize) -> ExprId {
+        if depth == 0 || self.rng.chance(20) {
+            return if self.rng.chance(50) {
+                let value = self.literal();
+                self.alloc(ExprKind::Literal(value))
+            } else {
+                let name = self.name();
+                self.alloc(ExprKind::Ident(name))
+            };
+        }
+        let d = depth - 1;
+        let kind = match self.rng.below(16) {
+            0..=2 => ExprKind::Binary {
+                left: self.expr(d),
+                op: *self.rng.pick(BINARY_OPS),
+                right: self.expr(d),
+            },
+            3 => ExprKind::Unary {
+                op: *self.rng.pick(UNARY_OPS),
+                op_span: Gen::span(),
+                expr: self.expr(d),
+            },
+            4 => ExprKind::Call {
+                callee: self.expr(d),
+                args: self.exprs(d, 3),
+            },
+            5 => ExprKind::Index {
+                object: self.expr(d),
+                index: self.expr(d),
+            },
+            6 => ExprKind::Member {
+                object: self.expr(d),
+                field: self.name(),
+                field_span: Gen::span(),
+            },
+            7 => ExprKind::Cast {
+                expr: self.expr(d),
+                ty: self.type_expr(2),
+            },
+            8 => ExprKind::Array(self.exprs(d, 3)),
+            9 => {
+                let entries = (0..self.rng.below(3)).map(|_| (self.expr(d), self.expr(d)));
+                ExprKind::Map(entries.collect())
+            }
+            10 => ExprKind::Tuple(self.exprs(d, 3)),
+            11 => {
+                let names = self.names(3);
+                let fields = names
+                    .into_iter()
+                    .map(|name| {
+                        let shorthand = self.rng.chance(30);
+                        let value = if shorthand {
+                            self.alloc(ExprKind::Ident(name.clone()))
+                        } else {
+                            self.expr(d)
+                        };
+                        FieldInit {
+                            name,
+                            name_span: Gen::span(),
+                            value,
+                            shorthand,
+                        }
+                    })
+                    .collect();
+                ExprKind::Struct {
+                    name: self.rng.pick(&["P", "Q"]).to_string(),
+                    name_span: Gen::span(),
+                    fields,
+                }
+            }
+            12 => {
+                // a return type needs a block body to be written
+                let return_type = self.rng.chance(30).then(|| self.type_expr(2));
+                ExprKind::Lambda(Box::new(LambdaExpr {
+                    params: self.params(),
+                    body: if return_type.is_some() || self.rng.chance(30) {
+                        LambdaBody::Block(self.block(1))
+                    } else {
+                        LambdaBody::Expr(self.expr(d))
+                    },
+                    return_type,
+                }))
+            }
+            13 => ExprKind::If {
+                cond: self.expr(d),
+                then_branch: Box::new(self.block(1)),
+                else_branch: match self.rng.below(3) {
+                    0 => None,
+                    1 => {
+                        let block = self.block(1);
+                        Some(self.alloc(ExprKind::Block(Box::new(block))))
+                    }
+                    _ => {
+                        let kind = ExprKind::If {
+                            cond: self.expr(d),
+                            then_branch: Box::new(self.block(1)),
+                            else_branch: None,
+                        };
+                        Some(self.alloc(kind))
+                    }
+                },
+            },
+            14 => {
+                // an empty block would be written as an empty map
+                let mut block = self.block(1);
+                if block.stmts.is_empty() {
+                    let value = self.expr(d);
+                    block
+                        .stmts
+                        .push(self.ast.alloc_stmt(StmtKind::Expr(value), Gen::span()));
+                    block.tail = self.rng.chance(50);
+                }
+                ExprKind::Block(Box::new(block))
+            }
+            _ => {
+                let arms = (0..self.rng.below(3))
+                    .map(|_| MatchArm {
+                        pattern: self.pattern(2),
+                        guard: self.rng.chance(30).then(|| self.expr(d)),
+                        body: if self.rng.chance(30) {
+                            ArmBody::Block(self.block(1))
+                        } else {
+                            ArmBody::Expr(self.expr(d))
+                        },
+                        span: Gen::span(),
+                    })
+                    .collect();
+                ExprKind::Match {
+                    scrutinee: self.expr(d),
+                    arms,
+                }
+            }
+        };
+        self.alloc(kind)
+    }
+
+    fn params(&mut self) -> Vec<Param> {
+        self.names(3)
+            .into_iter()
+            .map(|name| Param {
+                name,
+                name_span: Gen::span(),
+                type_annotation: self.rng.chance(50).then(|| self.type_expr(2)),
+                span: Gen::span(),
+            })
+            .collect()
+    }
+
+    fn pattern(&mut self, depth: usize) -> Pattern {
+        let kind = match self.rng.below(if depth == 0 { 3 } else { 5 }) {
+            0 => PatternKind::Wildcard,
+            1 => {
+                let value = match self.literal() {
+                    LiteralValue::Int(v) if self.rng.chance(30) => LiteralValue::Int(-v),
+                    value => value,
+                };
+                PatternKind::Literal(value)
+            }
+            2 => PatternKind::Binding(self.name()),
+            3 => PatternKind::Tuple(
+                (0..self.rng.below(3))
+                    .map(|_| self.pattern(depth - 1))
+                    .collect(),
+            ),
+            _ => {
+                let mut path = vec!["Shape".to_string(), "Circle".to_string()];
+                path.truncate(1 + self.rng.below(2));
+                let fields = if path.len() == 1 || self.rng.chance(50) {
+                    Some(
+                        (0..self.rng.below(3))
+                            .map(|_| self.pattern(depth - 1))
+                            .collect(),
+                    )
+                } else {
+                    None
+                };
+                PatternKind::Variant { path, fields }
+            }
+        };
+        Pattern::new(kind, Gen::span())
+    }
+
+    fn type_expr(&mut self, depth: usize) -> TypeExpr {
+        let kind = match self.rng.below(if depth == 0 { 2 } else { 7 }) {
+            0 => TypeExprKind::Primitive(*self.rng.pick(PRIMITIVES)),
+            1 => TypeExprKind::Named(self.rng.pick(&["Point", "Shape"]).to_string()),
+            2 => TypeExprKind::Array(Box::new(self.type_expr(depth - 1))),
+            3 => TypeExprKind::Map(
+                Box::new(self.type_expr(depth - 1)),
+                Box::new(self.type_expr(depth - 1)),
+            ),
+            4 => TypeExprKind::Tuple(
+                (0..self.rng.below(3))
+                    .map(|_| self.type_expr(depth - 1))
+                    .collect(),
+            ),
+            5 => TypeExprKind::Function {
+                params: (0..self.rng.below(3))
+                    .map(|_| self.type_expr(depth - 1))
+                    .collect(),
+                ret: self
+                    .rng
+                    .chance(50)
+                    .then(|| Box::new(self.type_expr(depth - 1))),
+            },
+            _ => TypeExprKind::Optional(Box::new(self.type_expr(depth - 1))),
+        };
+        TypeExpr::new(kind, Gen::span())
+    }
+
+    fn block(&mut self, depth: usize) -> Block {
+        let stmts: Vec<StmtId> = (0..self.rng.below(3)).map(|_| self.stmt(depth)).collect();
+        // only an expression, `if` or block can be the tail
+        let tail = stmts.last().is_some_and(|last| {
+            matches!(
+                self.ast.stmt(*last),
+                StmtKind::Expr(_) | StmtKind::If { .. } | StmtKind::Block(_)
+            )
+        }) && self.rng.chance(50);
+        Block {
+            stmts,
+            span: Gen::span(),
+            tail,
+        }
+    }
+
+    fn place(&mut self, depth: usize) -> ExprId {
+        let kind = match self.rng.below(3) {
+            0 => ExprKind::Ident(self.name()),
+            1 => ExprKind::Index {
+                object: self.expr(depth),
+                index: self.expr(depth),
+            },
+            _ => ExprKind::Member {
+                object: self.expr(depth),
+                field: self.name(),
+                field_span: Gen::span(),
+            },
+        };
+        self.alloc(kind)
+    }
+
+    fn stmt(&mut self, depth: usize) -> StmtId {
+        let choices = if depth == 0 { 5 } else { 12 };
+        let kind = match self.rng.below(choices) {
+            0 => StmtKind::Expr(self.expr(3)),
+            1 => StmtKind::Let {
+                mutable: self.rng.chance(50),
+                name: self.name(),
+                name_span: Gen::span(),
+                ty: self.rng.chance(50).then(|| self.type_expr(2)),
+                value: self.rng.chance(80).then(|| self.expr(3)),
+            },
+            2 => StmtKind::Assign {
+                target: self.place(2),
+                value: self.expr(3),
+            },
+            3 => StmtKind::Return(self.rng.chance(50).then(|| self.expr(3))),
+            4 => self
+                .rng
+                .pick(&[StmtKind::Break, StmtKind::Continue])
+                .clone(),
+            5 => StmtKind::Block(self.block(depth - 1)),
+            6 => StmtKind::If {
+                cond: self.expr(3),
+                then_branch: self.block(depth - 1),
+                else_branch: match self.rng.below(3) {
+                    0 => None,
+                    1 => {
+                        let block = self.block(depth - 1);
+                        Some(self.ast.alloc_stmt(StmtKind::Block(block), Gen::span()))
+                    }
+                    _ => {
+                        let cond = self.expr(3);
+                        let then_branch = self.block(depth - 1);
+                        let kind = StmtKind::If {
+                            cond,
+                            then_branch,
+                            else_branch: None,
+                        };
+                        Some(self.ast.alloc_stmt(kind, Gen::span()))
+                    }
+                },
+            },
+            7 => StmtKind::While {
+                cond: self.expr(3),
+                body: self.block(depth - 1),
+            },
+            8 => StmtKind::For {
+                var: self.name(),
+                var_span: Gen::span(),
+                iterable: self.expr(3),
+                body: self.block(depth - 1),
+            },
+            9 => StmtKind::Func(Box::new(FuncDecl {
+                public: false,
+                name: self.name(),
+                name_span: Gen::span(),
+                params: self.params(),
+                return_type: self.rng.chance(50).then(|| self.type_expr(2)),
+                body: self.block(depth - 1),
+                signature_span: Gen::span(),
+                span: Gen::span(),
+            })),
+            10 => StmtKind::Struct(Box::new(StructDecl {
+                public: false,
+                name: self.rng.pick(&["S", "T"]).to_string(),
+                name_span: Gen::span(),
+                fields: self
+                    .names(3)
+                    .into_iter()
+                    .map(|name| FieldDecl {
+                        name,
+                        name_span: Gen::span(),
+                        ty: self.type_expr(2),
+                        span: Gen::span(),
+                    })
+                    .collect(),
+                span: Gen::span(),
+            })),
+            _ => StmtKind::Enum(Box::new(EnumDecl {
+                public: false,
+                name: "Shape".to_string(),
+                name_span: Gen::span(),
+                variants: self
+                    .names(3)
+                    .into_iter()
+                    .map(|name| VariantDecl {
+                        name,
+                        name_span: Gen::span(),
+                        fields: (0..self.rng.below(3)).map(|_| self.type_expr(1)).collect(),
+                        span: Gen::span(),
+                    })
+                    .collect(),
+                span: Gen::span(),
+            })),
+        };
+        self.ast.alloc_stmt(kind, Gen::span())
+    }
+
+    /// A statement of the top level, the only place for imports and `pub`.
+    fn top_level_stmt(&mut self) -> StmtId {
+        if self.rng.chance(10) {
+            let source = if self.rng.chance(50) {
+                ImportSource::File(self.rng.pick(&["geometry", "shapes/circle"]).to_string())
+            } else {
+                ImportSource::Module(self.names(2).into_iter().chain(["m".to_string()]).collect())
+            };
+            let items = self.rng.chance(50).then(|| {
+                let names = self.names(3).into_iter();
+                names
+                    .map(|name| ImportItem {
+                        name,
+                        span: Gen::span(),
+                    })
+                    .collect()
+            });
+            let decl = ImportDecl {
+                source,
+                source_span: Gen::span(),
+                items,
+                span: Gen::span(),
+            };
+            return self
+                .ast
+                .alloc_stmt(StmtKind::Import(Box::new(decl)), Gen::span());
+        }
+        let stmt = self.stmt(3);
+        let public = self.rng.chance(50);
+        match self.ast.stmt_mut(stmt) {
+            StmtKind::Func(decl) => decl.public = public,
+            StmtKind::Struct(decl) => decl.public = public,
+            StmtKind::Enum(decl) => decl.public = public,
+            _ => {}
+        }
+        stmt
+    }
+}
+
+#[test]
+fn parsing_the_unparsed_source_gives_the_same_tree() {
+    for seed in 1..=500u64 {
+        let mut generator = Gen {
+            rng: Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15)),
+            ast: Ast::new(),
+        };
+        let stmts = (0..1 + generator.rng.below(4))
+            .map(|_| generator.top_level_stmt())
+            .collect();
+        let program = Program {
+            ast: generator.ast,
+            stmts,
+        };
+        let src = unparse(&program);
+        let expected = structure(program);
+        let actual = structure(parse(&src));
+        assert_eq!(actual, expected, "seed {} unparsed to:\n{}", seed, src);
+    }
+}
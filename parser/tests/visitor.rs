@@ -0,0 +1,111 @@
+use parser::nodes::ast::Ast;
+use parser::nodes::exprs::{BinaryOp, ExprId, ExprKind, LiteralValue};
+use parser::nodes::fold::{self, Fold};
+use parser::nodes::stmts::Program;
+use parser::nodes::type_exprs::TypeExpr;
+use parser::nodes::visitor::{self, Visitor};
+use parser::nodes::visitor_mut::{self, VisitorMut};
+use parser::parser::engine::Parser;
+
+fn parse(src: &str) -> Program {
+    let tokens = lexer::tokenize(src, "test").unwrap();
+    let mut parser = Parser::new(&tokens, 0);
+    let program = Parser::parse_program(&mut parser);
+    assert!(parser.errors.is_empty(), "`{}` did not parse", src);
+    program
+}
+
+const SRC: &str = "func f(a: [int]) -> int? { return match a { (x, _) if x > 0 => x, _ => { g(a[0] as f64); } }; }
+let y: {string: int} = {\"k\": 1 + 2 * 3};
+for v in y { while v { v = v - 1; } }";
+
+#[derive(Default)]
+struct Counter {
+    idents: Vec<String>,
+    types: usize,
+    patterns: usize,
+}
+
+impl Visitor for Counter {
+    fn visit_expr(&mut self, ast: &Ast, expr: ExprId) {
+        if let ExprKind::Ident(name) = ast.expr(expr) {
+            self.idents.push(name.clone());
+        }
+        visitor::walk_expr(self, ast, expr);
+    }
+
+    fn visit_type_expr(&mut self, ty: &TypeExpr) {
+        self.types += 1;
+        visitor::walk_type_expr(self, ty);
+    }
+
+    fn visit_pattern(&mut self, pattern: &parser::nodes::patterns::Pattern) {
+        self.patterns += 1;
+        visitor::walk_pattern(self, pattern);
+    }
+}
+
+#[test]
+fn visitor_reaches_every_node() {
+    let program = parse(SRC);
+    let mut counter = Counter::default();
+    counter.visit_program(&program);
+    assert_eq!(counter.idents, ["a", "x", "x", "g", "a", "y", "v", "v", "v"]);
+    // [int], int, int?, int, f64, {string: int}, string, int
+    assert_eq!(counter.types, 8);
+    // (x, _), x, _, _
+    assert_eq!(counter.patterns, 4);
+}
+
+struct Rename;
+
+impl VisitorMut for Rename {
+    fn visit_expr_mut(&mut self, ast: &mut Ast, expr: ExprId) {
+        if let ExprKind::Ident(name) = ast.expr_mut(expr) {
+            name.make_ascii_uppercase();
+        }
+        visitor_mut::walk_expr_mut(self, ast, expr);
+    }
+}
+
+#[test]
+fn visitor_mut_edits_in_place() {
+    let mut program = parse("let z = f(a, b.c) + d[e];");
+    Rename.visit_program_mut(&mut program);
+    assert_eq!(program.to_string(), "let z = (F(A, B.c) + D[E]);");
+}
+
+/// Folds additions and multiplications of integer literals.
+struct ConstFold;
+
+impl Fold for ConstFold {
+    fn fold_expr(&mut self, from: &Ast, into: &mut Ast, expr: ExprId) -> ExprId {
+        let id = fold::walk_expr(self, from, into, expr);
+        let ExprKind::Binary { left, op, right } = into.expr(id) else {
+            return id;
+        };
+        let (ExprKind::Literal(LiteralValue::Int(l)), ExprKind::Literal(LiteralValue::Int(r))) =
+            (into.expr(*left), into.expr(*right))
+        else {
+            return id;
+        };
+        let value = match op {
+            BinaryOp::Add => l + r,
+            BinaryOp::Mul => l * r,
+            _ => return id,
+        };
+        into.alloc_expr(ExprKind::Literal(LiteralValue::Int(value)), into.expr_span(id))
+    }
+}
+
+#[test]
+fn fold_rebuilds_the_program() {
+    let program = parse(SRC);
+    let folded = ConstFold.fold_program(program.clone());
+    let text = folded.to_string();
+    assert!(text.contains("{\"k\": 7}"), "{}", text);
+    assert_eq!(
+        text.replace("{\"k\": 7}", ""),
+        program.to_string().replace("{\"k\": (1 + (2 * 3))}", "")
+    );
+}